@@ -1,24 +1,518 @@
 from __future__ import annotations
+from typing import List, Tuple
 import numpy
+from numpy.typing import NDArray
 from .engeom import DeviationMode
-from .geom3 import Mesh, Iso3
+from .geom2 import Curve2, Iso2
+from .geom3 import Mesh, Iso3, SurfacePoint3
+
+
+class Dof2:
+    """
+    The degrees of freedom that a 2D alignment is allowed to use, expressed in the alignment's reference frame. Each flag
+    is True if the solver may change that parameter, or False to lock it at its initial value.
+    """
+
+    def __init__(self, tx: bool = True, ty: bool = True, r: bool = True):
+        """
+        Create a set of 2D degrees of freedom. By default all three are free, so only the ones to lock need to be given,
+        for example `Dof2(r=False)` allows translation but no rotation.
+
+        :param tx: allow translation along the x axis of the reference frame
+        :param ty: allow translation along the y axis of the reference frame
+        :param r: allow rotation about the origin of the reference frame
+        """
+        ...
+
+    @property
+    def tx(self) -> bool:
+        ...
+
+    @property
+    def ty(self) -> bool:
+        ...
+
+    @property
+    def r(self) -> bool:
+        ...
+
+
+class Dof3:
+    """
+    The degrees of freedom that an alignment is allowed to use, expressed in the alignment's reference frame. Each
+    flag is True if the solver may change that parameter, or False to lock it at its initial value. Translations are
+    along the x, y, and z axes of the reference frame, and rotations are about those same axes through the frame's
+    origin.
+    """
+
+    def __init__(
+            self,
+            tx: bool = True,
+            ty: bool = True,
+            tz: bool = True,
+            rx: bool = True,
+            ry: bool = True,
+            rz: bool = True
+    ):
+        """
+        Create a set of degrees of freedom. By default all six are free, so only the ones to lock need to be given,
+        for example `Dof3(rx=False, ry=False)` allows translation in any direction but rotation only about z.
+
+        :param tx: allow translation along the x axis of the reference frame
+        :param ty: allow translation along the y axis of the reference frame
+        :param tz: allow translation along the z axis of the reference frame
+        :param rx: allow rotation about the x axis of the reference frame
+        :param ry: allow rotation about the y axis of the reference frame
+        :param rz: allow rotation about the z axis of the reference frame
+        """
+        ...
+
+    @property
+    def tx(self) -> bool:
+        ...
+
+    @property
+    def ty(self) -> bool:
+        ...
+
+    @property
+    def tz(self) -> bool:
+        ...
+
+    @property
+    def rx(self) -> bool:
+        ...
+
+    @property
+    def ry(self) -> bool:
+        ...
+
+    @property
+    def rz(self) -> bool:
+        ...
+
+
+class RobustLoss:
+    """
+    A robust loss function for an alignment, which limits how much the correspondences with large residuals can pull
+    the result away from the rest. These are useful when some of the points being aligned don't belong to the target,
+    such as a few points from a fixture in a scan.
+    """
+
+    class Huber:
+        """
+        The Huber loss, which treats residuals smaller than `k` as ordinary least squares and gives larger residuals a
+        cost that only grows linearly.
+        """
+        def __init__(self, k: float):
+            ...
+
+    class Tukey:
+        """
+        Tukey's biweight loss, where the influence of a residual falls off smoothly until residuals larger than `c`
+        have no influence on the result at all.
+        """
+        def __init__(self, c: float):
+            ...
+
+    class Cauchy:
+        """
+        The Cauchy loss, whose cost only grows logarithmically once residuals are larger than the scale `c`.
+        """
+        def __init__(self, c: float):
+            ...
+
+    class Trimmed:
+        """
+        Ordinary least squares on all but a fraction of the correspondences, where the fraction with the largest
+        residuals are ignored each iteration. The fraction must be at least 0 and less than 1.
+        """
+        def __init__(self, fraction: float):
+            ...
+
+
+class AlignmentResult:
+    """
+    The result of an alignment, containing the transform along with the information needed to judge whether it can be
+    trusted.
+    """
+
+    @property
+    def transform(self) -> Iso3:
+        """
+        The isometry which moves the aligned points onto the target.
+        """
+        ...
+
+    @property
+    def iterations(self) -> int:
+        """
+        The number of iterations the optimizer ran for.
+        """
+        ...
+
+    @property
+    def converged(self) -> bool:
+        """
+        True if the optimizer stopped because the solution stopped improving, False if it ran out of iterations first.
+        """
+        ...
+
+    @property
+    def rms(self) -> float:
+        """
+        The root mean square of the residuals at the final transform.
+        """
+        ...
+
+    @property
+    def max_residual(self) -> float:
+        """
+        The largest absolute residual at the final transform.
+        """
+        ...
+
+    @property
+    def residuals(self) -> NDArray[float]:
+        """
+        A numpy array of shape (n, ) with the signed distance from each transformed point to its corresponding point on
+        the target, calculated according to the `DeviationMode` of the alignment. Points which had no correspondence
+        are NaN.
+        """
+        ...
+
+    @property
+    def weights(self) -> NDArray[float]:
+        """
+        A numpy array of shape (n, ) with the weight each point had in the final iteration of the alignment. Without a
+        robust loss every point with a correspondence has a weight of 1. Points which were rejected by the robust loss
+        or the maximum correspondence distance, or which had no correspondence, have a weight of 0.
+        """
+        ...
+
+    @property
+    def covariance(self) -> NDArray[float]:
+        """
+        A 6x6 numpy array with the estimated covariance of the transform parameters, in the order `[tx, ty, tz, rx, ry,
+        rz]`. Rotations are in radians about the x, y, and z axes of the reference frame, which unless one was given is
+        located at the centroid of the initially placed points. The square roots of the diagonal are the standard
+        uncertainties of each parameter. The rows and columns of locked parameters are zero, and if the points do not
+        constrain the free parameters, every entry will be infinite.
+        """
+        ...
 
 
 def points_to_mesh(
         points: numpy.ndarray[float],
         mesh: Mesh,
         initial: Iso3,
-        mode: DeviationMode
+        mode: DeviationMode,
+        dof: Dof3 | None = None,
+        frame: Iso3 | None = None,
+        loss: RobustLoss | None = None,
+        max_distance: float | None = None,
 ) -> Iso3:
     """
     Perform a Levenberg-Marquardt, least squares optimization to align a set of points to a mesh. This will return the
     isometry that best aligns the points to the mesh, or will throw an exception if the optimization fails.
 
+    Unless one of `dof`, `frame`, `loss`, or `max_distance` is given, this is done by engeom's own point to mesh
+    alignment. Those options are only available in the optimizer behind `fit_points_to_mesh`, which is used instead
+    when any of them are.
+
     :param points: a numpy array of shape (n, 3) containing the points to align.
     :param mesh: the mesh to align the points to.
     :param initial: the initial guess for the isometry. This will be used as the starting point for the optimization.
     :param mode: the mode to use for the deviation calculation. This will determine how the deviation between the points
     and the mesh is calculated.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all six.
+    :param frame: the reference frame that the degrees of freedom are expressed in, in world coordinates. If None, a
+    frame aligned with the world axes and located at the centroid of the initially placed points is used.
+    :param loss: a robust loss function to reduce the influence of outlying points, or None for ordinary least squares.
+    :param max_distance: if given, points further than this from their closest point on the mesh are ignored.
     :return: the isometry that best aligns the points to the mesh.
     """
-    ...
\ No newline at end of file
+    ...
+
+
+def fit_points_to_mesh(
+        points: numpy.ndarray[float],
+        mesh: Mesh,
+        initial: Iso3,
+        mode: DeviationMode,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        threads: int | None = None,
+        dof: Dof3 | None = None,
+        frame: Iso3 | None = None,
+        loss: RobustLoss | None = None,
+        max_distance: float | None = None,
+) -> AlignmentResult:
+    """
+    Perform a Levenberg-Marquardt, least squares optimization to align a set of points to a mesh like `points_to_mesh`,
+    but return an `AlignmentResult` with the residuals, convergence information, and an uncertainty estimate of the
+    transform instead of only the transform.
+
+    This always runs the optimizer which supports `dof`, `frame`, `loss`, and `max_distance`, so the iteration count
+    and convergence flag describe the optimization which produced the transform. It finds the same minimum as engeom's
+    own alignment used by `points_to_mesh`, up to the tolerance.
+
+    :param points: a numpy array of shape (n, 3) containing the points to align.
+    :param mesh: the mesh to align the points to.
+    :param initial: the initial guess for the isometry. This will be used as the starting point for the optimization.
+    :param mode: the mode to use for the deviation calculation. This will determine how the deviation between the points
+    and the mesh is calculated.
+    :param max_iterations: the maximum number of iterations the optimizer may run for.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all six.
+    :param frame: the reference frame that the degrees of freedom and the covariance are expressed in, in world
+    coordinates. If None, a frame aligned with the world axes and located at the centroid of the initially placed
+    points is used.
+    :param loss: a robust loss function to reduce the influence of outlying points, or None for ordinary least squares.
+    :param max_distance: if given, points further than this from their closest point on the mesh are ignored.
+    :return: the result of the alignment.
+    """
+    ...
+
+
+def points_to_rps(
+        measured: numpy.ndarray[float],
+        references: List[SurfacePoint3],
+        axes: List[str],
+        initial: Iso3,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        dof: Dof3 | None = None,
+        frame: Iso3 | None = None,
+) -> AlignmentResult:
+    """
+    Perform a reference point system (RPS) alignment, such as a 3-2-1 alignment, where each measured point is only
+    constrained to its nominal reference in certain directions. For example, a 3-2-1 alignment would have three points
+    active in "z", two in "y", and one in "x". The transform which minimizes the sum of squared deviations in the
+    active directions is found with the same optimizer as `fit_points_to_mesh`.
+
+    :param measured: a numpy array of shape (n, 3) containing the measured points, in the coordinates they will be
+    moved from.
+    :param references: a list of n nominal `SurfacePoint3` references, where `references[i]` is the nominal location of
+    `measured[i]`.
+    :param axes: a list of n strings with the active axes of each point. Each character is one active direction: "x",
+    "y", or "z" for the world axes, or "n" for the direction of the reference point's normal. For example, "z" for a
+    point that only constrains height or "xy" for a point in a round hole.
+    :param initial: the initial guess for the isometry. This will be used as the starting point for the optimization.
+    :param max_iterations: the maximum number of iterations the optimizer may run for.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all six.
+    :param frame: the reference frame that the degrees of freedom and the covariance are expressed in, in world
+    coordinates. If None, a frame aligned with the world axes and located at the centroid of the initially placed
+    points is used.
+    :return: the result of the alignment. There is one residual for every point, in the same order as `measured`, which
+    is the distance of the aligned point from its reference counting only the point's active axes. Each point's weight
+    is the mean of the loss weights of its axes, and `rms` and `max_residual` are taken over the per point residuals.
+    """
+    ...
+
+
+def coarse_points_to_mesh(
+        points: numpy.ndarray[float],
+        mesh: Mesh,
+        all_axes: bool = False,
+        threads: int | None = None,
+) -> List[Iso3]:
+    """
+    Find candidate initial transforms for aligning a set of points to a mesh when nothing is known about their pose.
+    The principal axes of the points are matched with the principal axes of the mesh surface, and because the
+    principal axes have no inherent direction every rotation which maps the axes onto each other is tried. The
+    candidates are returned in order from the best coarse fit to the worst, and are meant to be used as the `initial`
+    transform for a fine alignment like `points_to_mesh`.
+
+    This works best when the points cover roughly the same extent of the part as the mesh does, so that their principal
+    axes agree.
+
+    :param points: a numpy array of shape (n, 3) containing the points to align.
+    :param mesh: the mesh to align the points to.
+    :param all_axes: if False, the principal axes are assumed to be in the same order for the points and the mesh, and
+    only the four rotations which flip the direction of the axes are tried. If True, all twenty-four rotations which
+    map axes onto axes are tried, which is needed when two of the principal axes have similar variances.
+    :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+    :return: a list of candidate isometries, best first.
+    """
+    ...
+
+
+def global_points_to_mesh(
+        points: numpy.ndarray[float],
+        mesh: Mesh,
+        mode: DeviationMode,
+        all_axes: bool = False,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        threads: int | None = None,
+        loss: RobustLoss | None = None,
+        max_distance: float | None = None,
+) -> AlignmentResult:
+    """
+    Align a set of points in an arbitrary pose to a mesh in a single call. The candidates from `coarse_points_to_mesh`
+    are each refined with the same optimization as `fit_points_to_mesh`, and the refined alignment with the lowest
+    final cost is returned.
+
+    :param points: a numpy array of shape (n, 3) containing the points to align.
+    :param mesh: the mesh to align the points to.
+    :param mode: the mode to use for the deviation calculation in the fine alignment.
+    :param all_axes: try all twenty-four axis rotations instead of only the four axis flips, see
+    `coarse_points_to_mesh`.
+    :param max_iterations: the maximum number of iterations the optimizer may run for on each candidate.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+    :param loss: a robust loss function to reduce the influence of outlying points, or None for ordinary least squares.
+    :param max_distance: if given, points further than this from their closest point on the mesh are ignored.
+    :return: the result of the best alignment.
+    """
+    ...
+
+
+def mesh_to_mesh(
+        moving: Mesh,
+        target: Mesh,
+        initial: Iso3,
+        mode: DeviationMode,
+        spacing: float,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        threads: int | None = None,
+        dof: Dof3 | None = None,
+        frame: Iso3 | None = None,
+) -> Iso3:
+    """
+    Align one mesh to another with a symmetric iterative closest point optimization. Both meshes are sampled with
+    Poisson disk sampling, and the samples from each mesh are matched with the closest point on the other, so the
+    result is not biased towards either mesh when they only partially overlap, such as two scans of the same part or
+    a scan and its CAD model.
+
+    :param moving: the mesh to move, in its own coordinates.
+    :param target: the mesh to align to.
+    :param initial: the initial guess for the isometry which moves `moving` onto `target`.
+    :param mode: the mode to use for the deviation calculation. In `DeviationMode.Plane` the distance is measured along
+    the normal of the target side of each match.
+    :param spacing: the minimum distance between the samples taken from each mesh.
+    :param max_iterations: the maximum number of iterations the optimizer may run for.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all six.
+    :param frame: the reference frame that the degrees of freedom are expressed in, in world coordinates. If None, a
+    frame aligned with the world axes and located at the centroid of the initially placed samples is used.
+    :return: the isometry that best aligns `moving` to `target`.
+    """
+    ...
+
+
+def points_to_points(
+        points: numpy.ndarray[float],
+        targets: numpy.ndarray[float],
+        weights: numpy.ndarray[float] | None = None,
+) -> Iso3:
+    """
+    Find the rigid transform which best moves a set of points onto a set of corresponding target points, in the least
+    squares sense. This is solved in closed form with the Kabsch/Umeyama method, so no initial guess is needed.
+
+    :param points: a numpy array of shape (n, 3) containing the points to move.
+    :param targets: a numpy array of shape (n, 3) containing the target points, where `targets[i]` corresponds with
+    `points[i]`.
+    :param weights: an optional numpy array of shape (n, ) with a non-negative weight for each pair of points.
+    :return: the isometry that best aligns the points to the targets.
+    """
+    ...
+
+
+def points_to_points_scaled(
+        points: numpy.ndarray[float],
+        targets: numpy.ndarray[float],
+        weights: numpy.ndarray[float] | None = None,
+) -> Tuple[Iso3, float]:
+    """
+    Find the similarity transform which best moves a set of points onto a set of corresponding target points, in the
+    least squares sense. This is the same as `points_to_points` except that a uniform scale factor is also solved for.
+    An isometry can't hold a scale, so the result is returned as an isometry and a scale such that each target is
+    approximately `iso.transform_points(points * scale)`.
+
+    :param points: a numpy array of shape (n, 3) containing the points to move.
+    :param targets: a numpy array of shape (n, 3) containing the target points, where `targets[i]` corresponds with
+    `points[i]`.
+    :param weights: an optional numpy array of shape (n, ) with a non-negative weight for each pair of points.
+    :return: a tuple of the isometry and the scale factor.
+    """
+    ...
+
+
+def points_to_points_icp(
+        points: numpy.ndarray[float],
+        targets: numpy.ndarray[float],
+        initial: Iso3,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        threads: int | None = None,
+        dof: Dof3 | None = None,
+        frame: Iso3 | None = None,
+) -> Iso3:
+    """
+    Align a set of points to a second set of points when the correspondences between them are not known. This is an
+    iterative closest point optimization where each point is matched with the nearest target point every time the
+    transform is evaluated, so it needs an initial guess that is close to the answer.
+
+    :param points: a numpy array of shape (n, 3) containing the points to move.
+    :param targets: a numpy array of shape (m, 3) containing the points to align to.
+    :param initial: the initial guess for the isometry. This will be used as the starting point for the optimization.
+    :param max_iterations: the maximum number of iterations the optimizer may run for.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all six.
+    :param frame: the reference frame that the degrees of freedom are expressed in, in world coordinates. If None, a
+    frame aligned with the world axes and located at the centroid of the initially placed points is used.
+    :return: the isometry that best aligns the points to the targets.
+    """
+    ...
+
+
+def curve_to_curve(
+        moving: Curve2 | numpy.ndarray[float],
+        target: Curve2,
+        initial: Iso2,
+        mode: DeviationMode,
+        max_iterations: int = 100,
+        tolerance: float = 1e-10,
+        dof: Dof2 | None = None,
+        frame: Iso2 | None = None,
+        loss: RobustLoss | None = None,
+        max_distance: float | None = None,
+) -> Iso2:
+    """
+    Best fit a 2D curve or set of points onto a nominal curve, such as a section cut from a scan and projected to 2D
+    against its nominal profile. This uses the same Levenberg-Marquardt optimization as the 3D alignments, with the
+    closest point on the target curve found again each iteration.
+
+    The moving curve may cover only part of the target. If the target is an open curve, any moving points whose closest
+    point on the target is one of its ends are treated as hanging past the end and are ignored, so they don't pull the
+    alignment towards the ends.
+
+    :param moving: the curve to move, or a numpy array of shape (n, 2) containing the points to move. For a curve, its
+    vertices are used as the points.
+    :param target: the nominal curve to align to.
+    :param initial: the initial guess for the isometry. This will be used as the starting point for the optimization.
+    :param mode: `DeviationMode.Point` to measure the distance to the closest point on the target, or
+    `DeviationMode.Plane` to measure only the distance along the target curve's normal.
+    :param max_iterations: the maximum number of iterations the optimizer may run for.
+    :param tolerance: the optimizer stops when an iteration reduces the sum of squared residuals by less than this
+    fraction.
+    :param dof: the degrees of freedom the alignment may use, or None to allow all three.
+    :param frame: the reference frame that the degrees of freedom are expressed in, in world coordinates. If None, a
+    frame aligned with the world axes and located at the centroid of the initially placed points is used.
+    :param loss: a robust loss function to reduce the influence of outlying points, or None for ordinary least squares.
+    :param max_distance: if given, points further than this from their closest point on the target are ignored.
+    :return: the isometry that best aligns the moving curve to the target.
+    """
+    ...
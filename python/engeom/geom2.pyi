@@ -154,6 +154,23 @@ class Vector2(Iterable[float]):
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Vector2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Vector2:
+        """
+        Create a `Vector2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Vector2`
+        """
+        ...
+
 
 class Point2(Iterable[float]):
     """
@@ -275,6 +292,23 @@ class Point2(Iterable[float]):
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Point2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Point2:
+        """
+        Create a `Point2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Point2`
+        """
+        ...
+
 
 class SurfacePoint2:
     """
@@ -433,6 +467,23 @@ class SurfacePoint2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `SurfacePoint2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> SurfacePoint2:
+        """
+        Create a `SurfacePoint2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `SurfacePoint2`
+        """
+        ...
+
 
 class Iso2:
     """
@@ -525,6 +576,23 @@ class Iso2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Iso2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Iso2:
+        """
+        Create a `Iso2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Iso2`
+        """
+        ...
+
 
 class SvdBasis2:
     """
@@ -612,6 +680,23 @@ class SvdBasis2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `SvdBasis2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> SvdBasis2:
+        """
+        Create a `SvdBasis2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `SvdBasis2`
+        """
+        ...
+
 
 class CurveStation2:
     """
@@ -682,6 +767,23 @@ class CurveStation2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `CurveStation2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> CurveStation2:
+        """
+        Create a `CurveStation2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `CurveStation2`
+        """
+        ...
+
 
 class Curve2:
     """
@@ -967,6 +1069,23 @@ class Curve2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Curve2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Curve2:
+        """
+        Create a `Curve2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Curve2`
+        """
+        ...
+
 
 
 class Circle2:
@@ -1068,6 +1187,23 @@ class Circle2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Circle2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Circle2:
+        """
+        Create a `Circle2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Circle2`
+        """
+        ...
+
 
 class Arc2:
     """
@@ -1168,6 +1304,23 @@ class Arc2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Arc2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Arc2:
+        """
+        Create a `Arc2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Arc2`
+        """
+        ...
+
 
 class Aabb2:
     """
@@ -1290,3 +1443,20 @@ class Aabb2:
         :return: True if the point is contained within the AABB, False otherwise.
         """
         ...
+
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Aabb2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Aabb2:
+        """
+        Create a `Aabb2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Aabb2`
+        """
+        ...
@@ -1,6 +1,7 @@
 from __future__ import annotations
 
 from pathlib import Path
+from enum import Enum
 from typing import Tuple, Iterable, List, TypeVar, Iterator, Any
 
 import numpy
@@ -177,6 +178,23 @@ class Vector3(Iterable[float]):
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Vector3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Vector3:
+        """
+        Create a `Vector3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Vector3`
+        """
+        ...
+
 
 class Point3(Iterable[float]):
     """
@@ -321,6 +339,23 @@ class Point3(Iterable[float]):
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Point3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Point3:
+        """
+        Create a `Point3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Point3`
+        """
+        ...
+
 
 class SurfacePoint3:
     """
@@ -459,6 +494,23 @@ class SurfacePoint3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `SurfacePoint3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> SurfacePoint3:
+        """
+        Create a `SurfacePoint3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `SurfacePoint3`
+        """
+        ...
+
 
 class Iso3:
     """
@@ -769,6 +821,23 @@ class Iso3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Iso3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Iso3:
+        """
+        Create a `Iso3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Iso3`
+        """
+        ...
+
 
 class SvdBasis3:
     """
@@ -853,6 +922,23 @@ class SvdBasis3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `SvdBasis3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> SvdBasis3:
+        """
+        Create a `SvdBasis3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `SvdBasis3`
+        """
+        ...
+
 
 class Plane3:
     """
@@ -902,6 +988,23 @@ class Plane3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Plane3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Plane3:
+        """
+        Create a `Plane3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Plane3`
+        """
+        ...
+
 
 class Mesh:
     """
@@ -965,6 +1068,162 @@ class Mesh:
         """
         ...
 
+    @staticmethod
+    def load_ply(path: str | Path, merge_duplicates: bool = False, delete_degenerate: bool = False) -> Mesh:
+        """
+        Load a mesh from a PLY file in either the ASCII or the binary (little or big endian) encoding. Faces with more
+        than three vertices will be split into triangles.
+
+        Vertex normals (`nx`, `ny`, `nz`) and colors (`red`, `green`, `blue`, `alpha`) in the file are kept on the
+        mesh, and any other scalar vertex properties (for example `quality` or `intensity`) are kept as named scalar
+        fields which can be retrieved with `get_scalar_field`.
+
+        :param path: the path to the PLY file to load.
+        :param merge_duplicates: merge vertices with identical positions, keeping the attributes of the first one
+        :param delete_degenerate: delete triangles which repeat a vertex or have zero area
+        :return: the mesh object containing the data from the file.
+        """
+        ...
+
+    @staticmethod
+    def load_obj(path: str | Path, merge_duplicates: bool = False, delete_degenerate: bool = False) -> Mesh:
+        """
+        Load a mesh from a Wavefront OBJ file. Polygonal faces will be split into triangles, and texture coordinates,
+        groups, and materials are ignored.
+
+        Vertex normals (`vn`) referenced by the faces are kept on the mesh, averaged if a vertex is referenced with more
+        than one normal. Vertex colors written with the common `v x y z r g b` extension are kept if every vertex has
+        them.
+
+        :param path: the path to the OBJ file to load.
+        :param merge_duplicates: merge vertices with identical positions, keeping the attributes of the first one
+        :param delete_degenerate: delete triangles which repeat a vertex or have zero area
+        :return: the mesh object containing the data from the file.
+        """
+        ...
+
+    @staticmethod
+    def load_3mf(path: str | Path, merge_duplicates: bool = False, delete_degenerate: bool = False) -> Mesh:
+        """
+        Load a mesh from a 3MF package. All mesh objects referenced by the build are placed with their build item and
+        component transforms and merged into a single mesh.
+
+        Colors assigned to triangles through a color group or base materials are kept as vertex colors.
+
+        :param path: the path to the 3MF file to load.
+        :param merge_duplicates: merge vertices with identical positions, keeping the attributes of the first one
+        :param delete_degenerate: delete triangles which repeat a vertex or have zero area
+        :return: the mesh object containing the data from the file.
+        """
+        ...
+
+    @staticmethod
+    def from_organized_grid(
+            points: NDArray[float],
+            max_edge_length: float | None = None,
+            max_angle: float | None = None,
+            viewpoint: Point3 | None = None,
+    ) -> Mesh:
+        """
+        Create a mesh from an organized grid of points, such as a range image or the organized output of a sensor, by
+        connecting samples which are next to each other in the grid. Each cell of four valid samples is split into two
+        triangles along its shorter diagonal, and a cell with three valid samples becomes a single triangle. Samples
+        with a NaN coordinate are treated as missing.
+
+        Only samples which are part of a triangle become vertices of the mesh, and they are kept in row major order.
+
+        :param points: an array of shape (rows, cols, 3) with the position of each sample.
+        :param max_edge_length: if given, triangles with an edge longer than this are left out, which keeps separate
+        surfaces from being joined across a jump in depth.
+        :param max_angle: if given, triangles whose normal is more than this angle (in radians) away from the line of
+        sight to the viewpoint are left out, which removes triangles seen nearly edge on. Requires a viewpoint.
+        :param viewpoint: if given, every triangle is wound so that it faces this point. Otherwise, triangles are wound
+        counter-clockwise when the grid is seen with the columns increasing to the right and the rows increasing
+        upwards.
+        :return: a new mesh with the triangulated grid.
+        """
+        ...
+
+    def write_ply(self, path: str | Path, binary: bool = True):
+        """
+        Write the mesh to a PLY file. Vertex positions and any explicit vertex normals and scalar fields are written as
+        doubles, and vertex colors are written as RGBA bytes.
+
+        :param path: the path to the PLY file to write.
+        :param binary: if True the file is written in binary little endian format, otherwise it is written as ASCII.
+        """
+        ...
+
+    def write_obj(self, path: str | Path):
+        """
+        Write the mesh to a Wavefront OBJ file. Explicit vertex normals are written as `vn` entries and vertex colors
+        are written with the `v x y z r g b` extension. The OBJ format has no place for alpha values or scalar fields,
+        so they are not written.
+
+        :param path: the path to the OBJ file to write.
+        """
+        ...
+
+    def write_3mf(self, path: str | Path):
+        """
+        Write the mesh to a 3MF package as a single object. Vertex colors are written as a color group from the 3MF
+        materials extension. The 3MF format has no place for vertex normals or scalar fields, so they are not written.
+
+        :param path: the path to the 3MF file to write.
+        """
+        ...
+
+    @property
+    def vertex_colors(self) -> NDArray[numpy.uint8] | None:
+        """
+        Will return an immutable view of the RGBA vertex colors of the mesh as a numpy array of shape (n, 4), or None
+        if the mesh does not have vertex colors.
+        """
+        ...
+
+    def set_vertex_colors(self, colors: NDArray[numpy.uint8] | None = None):
+        """
+        Set or remove the vertex colors of the mesh.
+
+        :param colors: a numpy array of shape (n, 3) or (n, 4) of uint8 RGB or RGBA values, with one row for each
+        vertex. If the alpha channel is omitted it will be set to 255. If None, the colors will be removed.
+        """
+        ...
+
+    @property
+    def scalar_field_names(self) -> List[str]:
+        """
+        The names of the per-vertex scalar fields carried by the mesh, in the order they were added.
+        """
+        ...
+
+    def get_scalar_field(self, name: str) -> NDArray[float]:
+        """
+        Get a copy of a per-vertex scalar field by name. Raises a `KeyError` if the mesh has no field with that name.
+
+        :param name: the name of the scalar field.
+        :return: a numpy array of shape (n,) with one value for each vertex.
+        """
+        ...
+
+    def set_scalar_field(self, name: str, values: NDArray[float]):
+        """
+        Add or replace a named per-vertex scalar field, which will be written to PLY files.
+
+        :param name: the name of the scalar field.
+        :param values: a numpy array of shape (n,) with one value for each vertex.
+        """
+        ...
+
+    def remove_scalar_field(self, name: str) -> bool:
+        """
+        Remove a per-vertex scalar field by name.
+
+        :param name: the name of the scalar field.
+        :return: True if the field existed and was removed.
+        """
+        ...
+
     def cloned(self) -> Mesh:
         """
         Will return a copy of the mesh. This is a copy of the data, so modifying the returned mesh will not modify the
@@ -1014,8 +1273,9 @@ class Mesh:
     def vertex_normals(self) -> NDArray[float]:
         """
         Will return an immutable view of the vertex normals of the mesh as a numpy array of shape (n, 3), where n is the
-        number of vertices in the mesh.  If a vertex has no faces, the normal will be (0, 0, 0), otherwise the normal
-        will have been averaged from the normals of the faces that share the vertex.
+        number of vertices in the mesh.  If the mesh was loaded from a file which contained vertex normals, those normals
+        are returned. Otherwise, if a vertex has no faces, the normal will be (0, 0, 0), and if it does the normal will
+        have been averaged from the normals of the faces that share the vertex.
         :return: a numpy array of shape (n, 3) containing the normals of the vertices of the mesh.
         """
         ...
@@ -1040,7 +1300,9 @@ class Mesh:
         """
         ...
 
-    def deviation(self, points: NDArray[float], mode: engeom.DeviationMode) -> NDArray[float]:
+    def deviation(
+            self, points: NDArray[float], mode: engeom.DeviationMode, threads: int | None = None
+    ) -> NDArray[float]:
         """
         Calculate the deviation between a set of points and their respective closest points on the mesh surface.
         There are two possible modes of computing the distance, specified using the `DeviationMode` enum.  The two
@@ -1062,11 +1324,13 @@ class Mesh:
 
         :param points: a numpy array of shape (n, 3) containing the points to calculate the deviation for.
         :param mode: the mode to calculate the deviation in.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core. The GIL is released
+        while the work runs, so other Python threads can continue in the meantime.
         :return: a numpy array of shape (n, ) containing the deviation for each point.
         """
         ...
 
-    def sample_poisson(self, radius: float) -> NDArray[float]:
+    def sample_poisson(self, radius: float, threads: int | None = None) -> NDArray[float]:
         """
         Sample the surface of the mesh using a Poisson disk sampling algorithm. This will return a numpy array of points
         and their normals that are approximately evenly distributed across the surface of the mesh. The radius parameter
@@ -1077,18 +1341,27 @@ class Mesh:
         points. This means that the output points are not based on the mesh vertices, so large triangles will not be
         under-represented and small triangles will not be over-represented.
 
+        The sampling runs without holding the GIL, and any parallel work inside it runs on the worker threads. The
+        poisson disk thinning itself is sequential, since whether a point is kept depends on the points kept before it.
+
         :param radius: the minimum distance between points.
+        :param threads: the number of worker threads to use for the parallel part of the sampling, or None to use one
+        thread per core.
         :return: a numpy array of shape (n, 6) containing the sampled points.
         """
         ...
 
-    def section(self, plane: Plane3, tol: float | None = None) -> List[Curve3]:
+    def section(self, plane: Plane3, tol: float | None = None, threads: int | None = None) -> List[Curve3]:
         """
-        Calculate and return the intersection curves between the mesh and a plane.
+        Calculate and return the intersection curves between the mesh and a plane. The faces are cut by the plane in
+        parallel without holding the GIL, and the cuts are joined into curves where they meet at an edge or vertex of
+        the mesh. Vertices at exactly the same position are treated as one, so curves run across the seams of a mesh
+        with split vertices. Curves which form a closed loop come back to their first point at the end.
 
         :param plane: The plane to intersect the mesh with.
-        :param tol: The curve tolerance to use when constructing the intersection curves. See the `Curve3` class
-        initializer for more information on the tolerance parameter.
+        :param tol: The curve tolerance to use when constructing the intersection curves, 1e-6 if None. See the
+        `Curve3` class initializer for more information on the tolerance parameter.
+        :param threads: the number of worker threads to use, or None to use one thread per core.
         :return: a list of `Curve3` objects representing the intersection curves.
         """
         ...
@@ -1208,6 +1481,30 @@ class Mesh:
         """
         ...
 
+    def feature_edges(
+            self,
+            angle: float | None,
+            boundaries: bool = True,
+            regions: NDArray[int] | None = None,
+    ) -> List[Curve3]:
+        """
+        Extract the view independent feature lines of the mesh as polylines. Unlike `visual_outline`, the result does
+        not depend on a viewing direction. The edges which are found are chained together into curves which run between
+        the vertices where the feature lines end or branch, such as the corners of a box. Feature lines which form a
+        loop without any branches come back to their first point at the end. Vertices at exactly the same position are
+        treated as one, so the seams of a mesh with split vertices along its sharp edges are found as creases rather
+        than as boundaries.
+
+        :param angle: edges where the normals of the two faces differ by more than this angle, in radians, are included
+        as sharp creases. Edges shared by more than two faces are always included as creases. If None, no creases are
+        included.
+        :param boundaries: include the open boundary edges, which are used by only one face.
+        :param regions: an optional array with an integer label for each face, such as a material or a region found by
+        selecting faces. The edges between faces with different labels are included.
+        :return: a list of `Curve3` polylines following the feature edges.
+        """
+        ...
+
     def visual_outline(
             self,
             facing: Vector3,
@@ -1241,6 +1538,342 @@ class Mesh:
         """
         ...
 
+    def decimate(
+            self,
+            target_faces: int | None = None,
+            ratio: float | None = None,
+            max_error: float | None = None,
+            preserve_boundary: bool = False,
+            feature_angle: float | None = None,
+            threads: int | None = None,
+    ) -> Tuple[Mesh, float]:
+        """
+        Reduce the number of faces in the mesh by repeatedly collapsing the edge which changes the shape the least,
+        measured with quadric error metrics (Garland and Heckbert, 1997). Collapses which would make the mesh
+        non-manifold or turn a face over are skipped. This will not modify the original mesh.
+
+        Decimation stops once the face count target is reached or the next collapse would exceed `max_error`,
+        whichever happens first. At least one of `target_faces`, `ratio` or `max_error` must be given, and
+        `target_faces` and `ratio` cannot be given together.
+
+        Vertex attributes are carried over from the vertex which survived each collapse.
+
+        :param target_faces: stop once the mesh has this many faces or fewer.
+        :param ratio: stop once the mesh has this fraction of its original faces or fewer, in (0, 1].
+        :param max_error: only make collapses where the new vertex stays within this distance of the planes of the
+        original faces it replaces. The quadric error sums the squared distances to all of those planes, so this is a
+        conservative limit.
+        :param preserve_boundary: if True, vertices on the boundary of the mesh are never moved or removed.
+        :param feature_angle: if given, vertices on edges where the two faces meet at more than this angle (in radians)
+        are never moved or removed, which keeps sharp features exactly in place.
+        :param threads: the number of worker threads to use for measuring the error, or None to use one thread per CPU
+        core.
+        :return: the decimated mesh, and the Hausdorff distance between it and the original mesh. This is measured in
+        both directions, from the vertices, edge midpoints, and face centroids of each mesh to the surface of the other,
+        so it is a close estimate from below of the true distance rather than an exact value.
+        """
+        ...
+
+    def remesh(
+            self,
+            target_length: float,
+            iterations: int = 10,
+            feature_angle: float | None = None,
+            threads: int | None = None,
+    ) -> Mesh:
+        """
+        Rebuild the mesh with edges close to a target length and triangles close to equilateral, following Botsch and
+        Kobbelt (2004). Each iteration splits long edges, collapses short ones, flips edges to even out the number of
+        edges at each vertex, and then relaxes the vertices along the surface and projects them back onto the original
+        mesh. This is useful for replacing the long sliver triangles of tessellated CAD models. This will not modify
+        the original mesh.
+
+        Boundary edges and feature edges are split but otherwise left alone, so boundaries and features keep exactly
+        their original shape. Their vertices are only removed where the edges run in a straight line.
+
+        The vertices of the new mesh are all new, so each one takes its vertex attributes (normals, colors, and scalar
+        fields) from the closest vertex of the original mesh.
+
+        :param target_length: the edge length to aim for.
+        :param iterations: the number of rounds of splitting, collapsing, flipping and smoothing.
+        :param feature_angle: if given, edges where the two faces meet at more than this angle (in radians) are kept as
+        feature edges.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: a new mesh with the remeshed surface.
+        """
+        ...
+
+    def smooth_laplacian(
+            self,
+            iterations: int = 10,
+            factor: float = 0.5,
+            mask: NDArray[bool] | None = None,
+            region: FaceFilterHandle | None = None,
+            threads: int | None = None,
+    ) -> Mesh:
+        """
+        Smooth the mesh by repeatedly moving each vertex part of the way towards the average of its neighbors. This
+        removes noise quickly, but also shrinks the mesh and rounds off its sharp edges, see `smooth_taubin` and
+        `smooth_bilateral` for filters which avoid this. Vertices on the open boundaries of the mesh are not moved.
+
+        The result is a new mesh with the same faces. Vertex colors and scalar fields are kept, but vertex normals are
+        dropped since they no longer fit the surface.
+
+        :param iterations: the number of smoothing steps.
+        :param factor: the fraction of the way each vertex moves towards the average of its neighbors in each step.
+        :param mask: an optional boolean array with a value for each vertex, where only vertices marked True are moved.
+        :param region: an optional face selection on this mesh, where only the vertices of selected faces are moved.
+        If both a mask and a region are given, only vertices allowed by both are moved.
+        :param threads: the number of worker threads to use, or None to use one per core.
+        :return: a new, smoothed mesh.
+        """
+        ...
+
+    def smooth_taubin(
+            self,
+            iterations: int = 10,
+            shrink: float = 0.5,
+            inflate: float = -0.53,
+            mask: NDArray[bool] | None = None,
+            region: FaceFilterHandle | None = None,
+            threads: int | None = None,
+    ) -> Mesh:
+        """
+        Smooth the mesh with Taubin's filter, where each Laplacian step which shrinks the mesh is followed by a step
+        with a negative factor which inflates it again. This removes noise without the shrinking of `smooth_laplacian`.
+        Vertices on the open boundaries of the mesh are not moved.
+
+        The result is a new mesh with the same faces. Vertex colors and scalar fields are kept, but vertex normals are
+        dropped since they no longer fit the surface.
+
+        :param iterations: the number of pairs of shrinking and inflating steps.
+        :param shrink: the positive factor for the shrinking step.
+        :param inflate: the negative factor for the inflating step, which must be larger in magnitude than `shrink`.
+        :param mask: an optional boolean array with a value for each vertex, where only vertices marked True are moved.
+        :param region: an optional face selection on this mesh, where only the vertices of selected faces are moved.
+        If both a mask and a region are given, only vertices allowed by both are moved.
+        :param threads: the number of worker threads to use, or None to use one per core.
+        :return: a new, smoothed mesh.
+        """
+        ...
+
+    def smooth_bilateral(
+            self,
+            normal_iterations: int = 10,
+            vertex_iterations: int = 10,
+            sigma: float = 0.35,
+            mask: NDArray[bool] | None = None,
+            region: FaceFilterHandle | None = None,
+            threads: int | None = None,
+    ) -> Mesh:
+        """
+        Smooth the mesh with bilateral normal filtering, which keeps sharp edges and corners. The face normals are first
+        smoothed by averaging them with the normals of nearby faces, weighted so that faces whose normals differ by much
+        more than `sigma` have little influence on each other. The vertices are then moved so that the faces follow the
+        filtered normals.
+
+        The result is a new mesh with the same faces. Vertex colors and scalar fields are kept, but vertex normals are
+        dropped since they no longer fit the surface.
+
+        :param normal_iterations: the number of times the face normals are filtered. More iterations remove more noise.
+        :param vertex_iterations: the number of steps taken to move the vertices to fit the filtered normals.
+        :param sigma: the distance between unit face normals at which their influence on each other falls off. Smaller
+        values keep more of the edges between faces, larger values smooth more strongly.
+        :param mask: an optional boolean array with a value for each vertex, where only vertices marked True are moved.
+        :param region: an optional face selection on this mesh, where only the vertices of selected faces are moved.
+        If both a mask and a region are given, only vertices allowed by both are moved.
+        :param threads: the number of worker threads to use, or None to use one per core.
+        :return: a new, smoothed mesh.
+        """
+        ...
+
+    def fill_holes(
+            self,
+            max_edges: int | None,
+            method: HoleFill = HoleFill.MinimumArea,
+            max_perimeter: float | None = None,
+    ) -> Tuple[Mesh, MeshRepairReport]:
+        """
+        Close the holes in the mesh, where a hole is a loop of boundary edges like the ones found by
+        `get_patch_boundaries`. Each hole is covered with a patch of triangles wound to agree with the faces around it.
+        This will not modify the original mesh.
+
+        The outer boundary of an open surface is a loop of boundary edges too, which is why `max_edges` must always be
+        given. Pass `None` only to fill every loop, such as when the mesh is meant to be closed. Vertices at the same
+        position count as one when finding the loops, so the seams of a mesh with split vertices are not holes.
+
+        Vertices added by a curvature fill take the average of the vertex attributes of the vertices around the hole.
+
+        :param max_edges: holes whose loop has more edges than this are left open, or `None` to fill holes of any size.
+        :param method: how the holes are covered, see `HoleFill`.
+        :param max_perimeter: if given, holes whose loop of edges is longer than this are left open.
+        :return: the mesh with the holes filled, and a report of the holes which were filled and skipped.
+        """
+        ...
+
+    def repair(
+            self,
+            duplicates: bool = True,
+            non_manifold: bool = True,
+            orient: bool = True,
+            min_island_faces: int | None = None,
+    ) -> Tuple[Mesh, MeshRepairReport]:
+        """
+        Fix common problems with the topology of the mesh. This will not modify the original mesh. Faces which use the
+        same vertex more than once are always removed, and the other repairs run in the order of the parameters below.
+        Vertices which are no longer used by any face are removed at the end. Vertices at the same position count as one
+        when finding shared edges and connected patches, so a mesh with split vertices along its seams is repaired as
+        one surface.
+
+        :param duplicates: remove faces with the same three vertices as an earlier face, in either winding.
+        :param non_manifold: remove faces from edges shared by more than two faces, keeping the first face and one which
+        runs the edge the opposite way, and then split vertices where separate fans of faces only touch at a single
+        vertex, giving each fan its own copy of the vertex.
+        :param orient: make the winding of the faces consistent across each connected patch. A closed patch is wound so
+        that its faces point outwards, and an open patch keeps the winding of the majority of its faces.
+        :param min_island_faces: if given, remove groups of connected faces with fewer faces than this.
+        :return: the repaired mesh, and a report of what was changed.
+        """
+        ...
+
+    def check(self, self_intersections: bool = True, threads: int | None = None) -> MeshCheckReport:
+        """
+        Inspect the topology of the mesh, finding the elements which would cause problems for operations that expect a
+        clean surface, such as `section`, `boundary_first_flatten`, or a `MeshCollisionSet`. The mesh is not modified,
+        see `repair` and `fill_holes` for fixing the problems found here.
+
+        Vertices at the same position count as one, the same as for `volume` and the booleans, so the seams of a mesh
+        with split vertices are not reported as boundaries. The vertex indices in the report are those of the first
+        vertex at each position.
+
+        :param self_intersections: search for pairs of faces which cut through each other. This is the most expensive
+        part of the check, and can be turned off for large meshes.
+        :param threads: the number of worker threads to use, or None to use one per core.
+        :return: a report of the topology of the mesh and the indices of the offending elements.
+        """
+        ...
+
+    def curvature(self, radius: float | None = None, threads: int | None = None) -> MeshCurvature:
+        """
+        Estimate the principal curvatures and directions at each vertex of the mesh by fitting a quadratic surface to
+        the vertices around it. The per-vertex arrays in the result line up with the `vertices` array.
+
+        Curvature is positive where the surface bends away from the direction its faces point, as it does everywhere on
+        the outside of a sphere with outward facing normals, and negative in a dent or on the inside of a fillet.
+
+        :param radius: if given, fit to all vertices within this distance whose normals face the same way as the
+        vertex's normal, which smooths out noise on dense scans. If None, fit to the vertices within two edges of each
+        vertex.
+        :param threads: the number of worker threads to use, or None to use one per core.
+        :return: a `MeshCurvature` holding the results.
+        """
+        ...
+
+    def surface_area(self) -> float:
+        """
+        Calculate the total area of the faces of the mesh.
+
+        :return: the surface area of the mesh.
+        """
+        ...
+
+    def surface_centroid(self) -> Point3:
+        """
+        Calculate the area weighted centroid of the faces of the mesh, which is the center of mass of a thin shell with
+        the shape of the surface. Unlike `centroid`, this works for open meshes.
+
+        :return: the centroid of the surface.
+        """
+        ...
+
+    def volume(self, require_closed: bool = True) -> float:
+        """
+        Calculate the volume enclosed by the mesh. The volume is signed, and will be negative if the faces of the mesh
+        point inwards.
+
+        :param require_closed: if True, a `ValueError` is raised when the mesh is not closed and consistently wound,
+        that is when any edge is not shared by exactly two faces running it in opposite directions. Vertices at exactly
+        the same position count as one vertex for this check. The volume of such a mesh depends on where it is measured
+        from and is not meaningful.
+        :return: the signed volume enclosed by the mesh.
+        """
+        ...
+
+    def centroid(self, require_closed: bool = True) -> Point3:
+        """
+        Calculate the centroid of the volume enclosed by the mesh, which is the center of mass of a solid of uniform
+        density. See `surface_centroid` for the centroid of the surface itself.
+
+        :param require_closed: if True, a `ValueError` is raised when the mesh is not closed and consistently wound.
+        :return: the centroid of the enclosed volume.
+        """
+        ...
+
+    def inertia_tensor(self, density: float = 1.0, require_closed: bool = True) -> NDArray[float]:
+        """
+        Calculate the inertia tensor of a solid of uniform density with the shape of the mesh, about its centroid and
+        in the world coordinate axes. The solid is the one enclosed by the mesh regardless of which way its faces point.
+
+        :param density: the mass per unit volume of the solid. With the default of 1.0 the mass is the volume.
+        :param require_closed: if True, a `ValueError` is raised when the mesh is not closed and consistently wound.
+        :return: a numpy array of shape (3, 3) with the inertia tensor.
+        """
+        ...
+
+    def principal_axes(self, require_closed: bool = True) -> Iso3:
+        """
+        Find the principal axes of inertia of the solid enclosed by the mesh. The result is an isometry which moves
+        points from the principal frame into world coordinates: its origin is at the centroid, and its X, Y, and Z axes
+        are the principal axes in order of increasing moment of inertia. Use its inverse to move the mesh into the
+        principal frame.
+
+        The principal axes have no preferred direction, so an axis may point either way along its line. Where two
+        moments are equal, the axes in their plane are arbitrary.
+
+        :param require_closed: if True, a `ValueError` is raised when the mesh is not closed and consistently wound.
+        :return: an isometry with the centroid as its origin and the principal axes as its basis.
+        """
+        ...
+
+    def union(self, other: Mesh) -> Mesh:
+        """
+        Create a new mesh enclosing everything inside either this mesh or the other one. Unlike `append`, the parts of
+        each mesh which lie inside the other are removed, and new vertices are added where their surfaces cross, so
+        that the result is a single closed surface wherever the two solids overlap.
+
+        Both meshes must be closed and consistently wound with their faces pointing outwards. Vertices at the same
+        position are merged before the operation. The result has no vertex normals, colors, or scalars. Faces of the
+        two meshes which lie in the same plane, such as boxes which touch or sit flush against each other, are split
+        along the outline of their overlap and kept once where the result needs them. Solids which only touch along an
+        edge or at a point give a result which is closed but not manifold there. A `ValueError` is raised if the
+        surfaces meet at such a shallow angle that the cuts through a face cross each other.
+
+        :param other: the mesh to combine with this one.
+        :return: a new mesh enclosing the union of the two solids.
+        """
+        ...
+
+    def difference(self, other: Mesh) -> Mesh | None:
+        """
+        Create a new mesh enclosing everything inside this mesh which is not inside the other one, such as a part with
+        a fixture or a cut-out removed from it. The requirements on the meshes are the same as for `union`.
+
+        :param other: the mesh to remove from this one.
+        :return: a new mesh enclosing the difference of the two solids, or `None` if the other mesh encloses this one
+        entirely.
+        """
+        ...
+
+    def intersection(self, other: Mesh) -> Mesh | None:
+        """
+        Create a new mesh enclosing everything inside both this mesh and the other one. The requirements on the meshes
+        are the same as for `union`.
+
+        :param other: the mesh to intersect with this one.
+        :return: a new mesh enclosing the intersection of the two solids, or `None` if they don't overlap.
+        """
+        ...
+
     @staticmethod
     def create_box(length: float, width: float, height: float) -> Mesh:
         """
@@ -1346,42 +1979,278 @@ class Mesh:
         """
         ...
 
-
-class FaceFilterHandle:
-    """
-    A class that acts as a handle to a filtering (selection/deselection) operation of faces on a mesh.
-
-    A filtering operation is started using the `face_select_all` or `face_select_none` methods on a `Mesh` object, and
-    then further filtering operations can be done on the handle to select or deselect faces based on various criteria.
-
-    Once finished, the handle can be finalized into a list of the final indices of the triangles that passed the filter,
-    or used to directly create a new mesh containing only the filtered triangles.
-    """
-
-    def collect(self) -> List[int]:
+    def to_bytes(self) -> bytes:
         """
-        Finalize the handle by collecting the final indices of the triangles that passed the filter.
-        :return: a list of the final indices of the triangles that passed the filter.
+        Serialize the `Mesh` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
         """
         ...
 
-    def create_mesh(self) -> Mesh:
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Mesh:
         """
-        Create a new mesh from the filtered triangles. This will build a new mesh object containing only the triangles
-        (and their respective vertices) that are still retained in the filter.
-        :return: a new mesh object containing only the filtered triangles.
+        Create a `Mesh` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Mesh`
         """
         ...
 
-    def facing(self, x: float, y: float, z: float, angle: float, mode: engeom.SelectOp) -> FaceFilterHandle:
-        """
-        Add, remove, or keep only the faces whose normals are facing a given direction within a certain angle tolerance.
 
-        This method will alter the filter handle object in place and return `self` to allow for the use of a fluent-like
-        interface if desired.
+class HoleFill(Enum):
+    """
+    The ways of covering a hole in a mesh with `Mesh.fill_holes`.
+    """
 
-        :param x: the x component of the direction to check against
-        :param y: the y component of the direction to check against
+    Flat = 0
+    """ Triangulate the loop around the hole in its plane of best fit, which suits holes in flat regions. """
+
+    MinimumArea = 1
+    """
+    Triangulate the loop around the hole with the least total area, which also works for loops that aren't flat. This
+    takes time cubic in the length of the loop, so loops of more than 400 edges are triangulated flat instead, and are
+    left open if that fails.
+    """
+
+    Curvature = 2
+    """
+    Start from the minimum area (or, for long loops, the flat) triangulation, add vertices until the triangles are about the size of the edges around
+    the hole, and then move the new vertices so the patch carries on the curvature of the surrounding surface.
+    """
+
+
+class MeshRepairReport:
+    """
+    A report of the changes made by `Mesh.fill_holes` or `Mesh.repair`. Counts for the operations which were not run
+    are zero. The report is truthy if anything was changed.
+    """
+
+    @property
+    def holes_filled(self) -> int:
+        """ The number of holes which were filled. """
+        ...
+
+    @property
+    def holes_skipped(self) -> int:
+        """ The number of holes which were left open because they were larger than the limits. """
+        ...
+
+    @property
+    def faces_added(self) -> int:
+        """ The number of faces added to fill holes. """
+        ...
+
+    @property
+    def vertices_added(self) -> int:
+        """ The number of vertices added to fill holes. """
+        ...
+
+    @property
+    def degenerate_faces_removed(self) -> int:
+        """ The number of faces removed because they used the same vertex more than once. """
+        ...
+
+    @property
+    def duplicate_faces_removed(self) -> int:
+        """ The number of faces removed because they repeated an earlier face. """
+        ...
+
+    @property
+    def non_manifold_faces_removed(self) -> int:
+        """ The number of faces removed from edges shared by more than two faces. """
+        ...
+
+    @property
+    def non_manifold_vertices_split(self) -> int:
+        """ The number of vertex copies made to separate fans of faces which only touched at a vertex. """
+        ...
+
+    @property
+    def faces_flipped(self) -> int:
+        """ The number of faces whose winding was reversed. """
+        ...
+
+    @property
+    def islands_removed(self) -> int:
+        """ The number of small groups of connected faces which were removed. """
+        ...
+
+    @property
+    def island_faces_removed(self) -> int:
+        """ The number of faces in the removed groups. """
+        ...
+
+
+class MeshCurvature:
+    """
+    The discrete curvature of a mesh, produced by `Mesh.curvature`. The per-vertex arrays line up with the vertices of
+    the mesh and the per-face arrays line up with its faces. Vertices with too few neighbors to fit a surface get NaN
+    curvatures and zero directions.
+    """
+
+    @property
+    def k1(self) -> NDArray[float]:
+        """ The larger principal curvature at each vertex. """
+        ...
+
+    @property
+    def k2(self) -> NDArray[float]:
+        """ The smaller principal curvature at each vertex. """
+        ...
+
+    @property
+    def d1(self) -> NDArray[float]:
+        """ An array of shape (n, 3) with the unit direction of the larger principal curvature at each vertex. """
+        ...
+
+    @property
+    def d2(self) -> NDArray[float]:
+        """ An array of shape (n, 3) with the unit direction of the smaller principal curvature at each vertex. """
+        ...
+
+    @property
+    def mean(self) -> NDArray[float]:
+        """ The mean curvature at each vertex, the average of `k1` and `k2`. """
+        ...
+
+    @property
+    def gaussian(self) -> NDArray[float]:
+        """ The Gaussian curvature at each vertex, the product of `k1` and `k2`. """
+        ...
+
+    @property
+    def face_mean(self) -> NDArray[float]:
+        """ The mean curvature of each face, averaged from its three vertices. """
+        ...
+
+    @property
+    def face_gaussian(self) -> NDArray[float]:
+        """ The Gaussian curvature of each face, averaged from its three vertices. """
+        ...
+
+
+class MeshCheckReport:
+    """
+    A report on the topology of a mesh, produced by `Mesh.check`. Edges are given as pairs of vertex indices with the
+    lower index first. The report is truthy if the mesh is sound, see `is_sound`.
+    """
+
+    @property
+    def is_watertight(self) -> bool:
+        """ True if every edge is shared by exactly two faces. """
+        ...
+
+    @property
+    def is_manifold(self) -> bool:
+        """ True if there are no non-manifold edges or vertices. """
+        ...
+
+    @property
+    def is_oriented(self) -> bool:
+        """ True if every pair of neighboring faces is wound consistently. """
+        ...
+
+    @property
+    def is_sound(self) -> bool:
+        """
+        True if the mesh is manifold, consistently wound, and has no degenerate faces or self-intersections. A sound
+        mesh may still have boundaries, check `is_watertight` for a closed surface.
+        """
+        ...
+
+    @property
+    def boundary_loops(self) -> int:
+        """ The number of separate loops formed by the boundary edges. """
+        ...
+
+    @property
+    def components(self) -> int:
+        """ The number of groups of faces connected through shared vertices. """
+        ...
+
+    @property
+    def euler_characteristic(self) -> int:
+        """ The number of vertices used by faces, less the number of edges, plus the number of faces. """
+        ...
+
+    @property
+    def genus(self) -> int | None:
+        """
+        The total number of handles of the surface, found from the Euler characteristic, the number of components, and
+        the number of boundary loops. This is None if the mesh is not manifold or not consistently wound, as the
+        genus is not meaningful for such a mesh.
+        """
+        ...
+
+    @property
+    def boundary_edges(self) -> NDArray[numpy.uint32]:
+        """ An array of shape (n, 2) with the edges used by only one face. """
+        ...
+
+    @property
+    def non_manifold_edges(self) -> NDArray[numpy.uint32]:
+        """ An array of shape (n, 2) with the edges used by more than two faces. """
+        ...
+
+    @property
+    def non_manifold_vertices(self) -> NDArray[numpy.uint32]:
+        """ The indices of the vertices where separate fans of faces only touch at the vertex. """
+        ...
+
+    @property
+    def inconsistent_edges(self) -> NDArray[numpy.uint32]:
+        """ An array of shape (n, 2) with the edges whose two faces both run the edge in the same direction. """
+        ...
+
+    @property
+    def degenerate_faces(self) -> NDArray[numpy.uint64]:
+        """ The indices of the faces which use a vertex more than once or have no area. """
+        ...
+
+    @property
+    def self_intersections(self) -> NDArray[numpy.uint64]:
+        """
+        An array of shape (n, 2) with the pairs of faces which cut through each other. Faces which share a vertex, and
+        faces which only touch or lie in the same plane, are not counted. Empty if the search was turned off.
+        """
+        ...
+
+
+class FaceFilterHandle:
+    """
+    A class that acts as a handle to a filtering (selection/deselection) operation of faces on a mesh.
+
+    A filtering operation is started using the `face_select_all` or `face_select_none` methods on a `Mesh` object, and
+    then further filtering operations can be done on the handle to select or deselect faces based on various criteria.
+
+    Once finished, the handle can be finalized into a list of the final indices of the triangles that passed the filter,
+    or used to directly create a new mesh containing only the filtered triangles.
+    """
+
+    def collect(self) -> List[int]:
+        """
+        Finalize the handle by collecting the final indices of the triangles that passed the filter.
+        :return: a list of the final indices of the triangles that passed the filter.
+        """
+        ...
+
+    def create_mesh(self) -> Mesh:
+        """
+        Create a new mesh from the filtered triangles. This will build a new mesh object containing only the triangles
+        (and their respective vertices) that are still retained in the filter.
+        :return: a new mesh object containing only the filtered triangles.
+        """
+        ...
+
+    def facing(self, x: float, y: float, z: float, angle: float, mode: engeom.SelectOp) -> FaceFilterHandle:
+        """
+        Add, remove, or keep only the faces whose normals are facing a given direction within a certain angle tolerance.
+
+        This method will alter the filter handle object in place and return `self` to allow for the use of a fluent-like
+        interface if desired.
+
+        :param x: the x component of the direction to check against
+        :param y: the y component of the direction to check against
         :param z: the z component of the direction to check against
         :param angle: the maximum angle in radians between the face normal and the filter direction
         :param mode: the operation to perform on the faces, one of `SelectOp.Add`, `SelectOp.Remove`, or `SelectOp.Keep`
@@ -1421,6 +2290,23 @@ class FaceFilterHandle:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `FaceFilterHandle` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> FaceFilterHandle:
+        """
+        Create a `FaceFilterHandle` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `FaceFilterHandle`
+        """
+        ...
+
 
 class MeshCollisionSet:
     """
@@ -1467,6 +2353,432 @@ class MeshCollisionSet:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `MeshCollisionSet` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> MeshCollisionSet:
+        """
+        Create a `MeshCollisionSet` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `MeshCollisionSet`
+        """
+        ...
+
+
+class PointCloud:
+    """
+    A class holding an unordered set of points in 3D space, with optional per-point normals, colors, and named scalar
+    fields. A spatial index over the points is built when the cloud is created and kept with it, so that repeated
+    nearest neighbor queries do not have to rebuild it.
+    """
+
+    def __init__(
+            self,
+            points: NDArray[float],
+            normals: NDArray[float] | None = None,
+            colors: NDArray[numpy.uint8] | None = None
+    ):
+        """
+        Create a point cloud from a numpy array of points, optionally with a normal and a color for each point.
+
+        :param points: a numpy array of shape (n, 3) containing the points of the cloud.
+        :param normals: an optional numpy array of shape (n, 3) containing a normal for each point.
+        :param colors: an optional numpy array of shape (n, 3) or (n, 4) of uint8 RGB or RGBA values, with one row for
+        each point. If the alpha channel is omitted it will be set to 255.
+        """
+        ...
+
+    def __len__(self) -> int:
+        ...
+
+    def cloned(self) -> PointCloud:
+        """
+        Will return a copy of the point cloud. This is a copy of the data, so modifying the returned cloud will not
+        modify the original.
+
+        :return: an independent copy of the point cloud.
+        """
+        ...
+
+    @property
+    def points(self) -> NDArray[float]:
+        """
+        Will return an immutable view of the points of the cloud as a numpy array of shape (n, 3).
+        :return: a numpy array of shape (n, 3) containing the points of the cloud.
+        """
+        ...
+
+    @property
+    def normals(self) -> NDArray[float] | None:
+        """
+        Will return an immutable view of the normals of the cloud as a numpy array of shape (n, 3), or None if the
+        cloud does not have normals.
+        """
+        ...
+
+    def set_normals(self, normals: NDArray[float] | None = None):
+        """
+        Set or remove the normals of the point cloud.
+
+        :param normals: a numpy array of shape (n, 3) with one normal for each point. If None, the normals will be
+        removed.
+        """
+        ...
+
+    def estimate_normals(self, k: int | None = None, radius: float | None = None, threads: int | None = None):
+        """
+        Estimate a normal for every point by principal component analysis of its neighborhood, taking the direction
+        of least variance of the neighboring points. Exactly one of `k` or `radius` must be given to choose the
+        neighborhood. Points with fewer than three points in their neighborhood receive a zero normal.
+
+        PCA can't tell which side of the surface a normal should point to, so if the cloud already has normals each
+        new normal is flipped to agree with the old one. Otherwise the signs are arbitrary, and one of the
+        `orient_normals_...` methods should be used to make them consistent.
+
+        :param k: use the `k` nearest points (including the point itself) as the neighborhood, must be at least 3.
+        :param radius: use all points within this distance as the neighborhood.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        """
+        ...
+
+    def orient_normals_towards(self, point: Point3):
+        """
+        Flip the normals of the cloud so that each one points towards a single viewpoint, which works well for a
+        cloud seen from one position, such as the output of a single scan.
+
+        :param point: the viewpoint which the normals should face.
+        """
+        ...
+
+    def orient_normals_to_origins(self, origins: NDArray[float]):
+        """
+        Flip the normals of the cloud so that each one points towards the position of the sensor which measured that
+        point, for clouds which were combined from scans taken at different positions.
+
+        :param origins: a numpy array of shape (n, 3) containing the sensor position for each point.
+        """
+        ...
+
+    def orient_normals_mst(self, k: int = 16, threads: int | None = None):
+        """
+        Make the signs of the normals consistent across the surface by propagating the orientation over a minimum
+        spanning tree of the k-nearest neighbor graph, where neighbors with nearly parallel normals are connected
+        first (Hoppe et al., 1992). This needs no viewpoint, but the result has one of two overall signs: each
+        connected part of the cloud is started from its highest point in z, whose normal is made to point up.
+
+        :param k: the number of neighbors each point is connected to in the graph.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        """
+        ...
+
+    @property
+    def colors(self) -> NDArray[numpy.uint8] | None:
+        """
+        Will return an immutable view of the RGBA colors of the cloud as a numpy array of shape (n, 4), or None if the
+        cloud does not have colors.
+        """
+        ...
+
+    def set_colors(self, colors: NDArray[numpy.uint8] | None = None):
+        """
+        Set or remove the colors of the point cloud.
+
+        :param colors: a numpy array of shape (n, 3) or (n, 4) of uint8 RGB or RGBA values, with one row for each
+        point. If the alpha channel is omitted it will be set to 255. If None, the colors will be removed.
+        """
+        ...
+
+    @property
+    def scalar_field_names(self) -> List[str]:
+        """
+        The names of the per-point scalar fields carried by the cloud, in the order they were added.
+        """
+        ...
+
+    def get_scalar_field(self, name: str) -> NDArray[float]:
+        """
+        Get a copy of a per-point scalar field by name. Raises a `KeyError` if the cloud has no field with that name.
+
+        :param name: the name of the scalar field.
+        :return: a numpy array of shape (n,) with one value for each point.
+        """
+        ...
+
+    def set_scalar_field(self, name: str, values: NDArray[float]):
+        """
+        Add or replace a named per-point scalar field.
+
+        :param name: the name of the scalar field.
+        :param values: a numpy array of shape (n,) with one value for each point.
+        """
+        ...
+
+    def remove_scalar_field(self, name: str) -> bool:
+        """
+        Remove a per-point scalar field by name.
+
+        :param name: the name of the scalar field.
+        :return: True if the field existed and was removed.
+        """
+        ...
+
+    def transform_by(self, iso: Iso3):
+        """
+        Transforms the points (and rotates the normals) of the cloud by an isometry. This will modify the cloud in
+        place and rebuild its spatial index.  Any copies made of the points will no longer match the cloud after this
+        operation.
+        :param iso: the isometry to transform the cloud by.
+        """
+        ...
+
+    def append(self, other: PointCloud):
+        """
+        Append the points of another cloud to the end of this one, changing this one and leaving the other one
+        unmodified. Normals, colors, or scalar fields which are present on only one of the two clouds are kept, with
+        the points from the other cloud filled in with zero normals, white, or NaN respectively.
+
+        :param other: the point cloud to append to this one, will not be modified in this operation
+        """
+        ...
+
+    def create_from_indices(self, indices: List[int]) -> PointCloud:
+        """
+        Create a new point cloud from a list of point indices, carrying along the normals, colors, and scalar fields of
+        each point. The points are placed in the order of the list.
+        :param indices: the indices of the points to include in the new cloud
+        :return: a new point cloud containing only the specified points
+        """
+        ...
+
+    def nearest(self, points: NDArray[float], threads: int | None = None) -> Tuple[NDArray[numpy.uintp], NDArray[float]]:
+        """
+        Find the closest point in the cloud to each of a set of query points.
+
+        :param points: a numpy array of shape (m, 3) containing the query points.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: a tuple of two numpy arrays of shape (m,), the first with the index of the closest point in the cloud
+        and the second with the distance to it.
+        """
+        ...
+
+    def nearest_k(
+            self,
+            points: NDArray[float],
+            k: int,
+            threads: int | None = None
+    ) -> Tuple[NDArray[numpy.uintp], NDArray[float]]:
+        """
+        Find the `k` closest points in the cloud to each of a set of query points. `k` must be at least 1 and no more
+        than the number of points in the cloud.
+
+        :param points: a numpy array of shape (m, 3) containing the query points.
+        :param k: the number of neighbors to find for each query point.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: a tuple of two numpy arrays of shape (m, k), the first with the indices of the neighbors in the cloud
+        and the second with the distances to them, with each row ordered from nearest to farthest.
+        """
+        ...
+
+    def within_radius(
+            self,
+            points: NDArray[float],
+            radius: float,
+            threads: int | None = None
+    ) -> List[NDArray[numpy.uintp]]:
+        """
+        Find all points in the cloud within a distance of each of a set of query points.
+
+        :param points: a numpy array of shape (m, 3) containing the query points.
+        :param radius: the search radius, points at exactly this distance are included.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: a list of m numpy arrays, each holding the indices of the points in the cloud within the radius of
+        the corresponding query point, ordered from nearest to farthest.
+        """
+        ...
+
+    def voxel_downsample(self, voxel_size: float, threads: int | None = None) -> NDArray[numpy.uintp]:
+        """
+        Thin the cloud to at most one point per cubic voxel of a regular grid, keeping in each voxel the original point
+        closest to the centroid of the points that fall in it. Use `create_from_indices` to build the thinned cloud,
+        or index any other per-point arrays with the result.
+
+        :param voxel_size: the edge length of the voxels.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: the indices of the kept points, in ascending order.
+        """
+        ...
+
+    def voxel_centroids(self, voxel_size: float) -> PointCloud:
+        """
+        Thin the cloud by replacing the points in each cubic voxel of a regular grid with their centroid. Because the
+        centroids are new points this returns a new cloud rather than indices, with the normals, colors, and scalar
+        fields of each voxel averaged (and the normals re-normalized). The voxels are in the order of their first
+        original point.
+
+        :param voxel_size: the edge length of the voxels.
+        :return: a new point cloud with one point for each occupied voxel.
+        """
+        ...
+
+    def sample_random(self, count: int, seed: int | None = None) -> NDArray[numpy.uintp]:
+        """
+        Choose points uniformly at random, without replacement.
+
+        :param count: the number of points to keep. If this is more than the number of points, all are kept.
+        :param seed: a seed for the random generator to get a repeatable selection, or None to seed it from the OS.
+        :return: the indices of the kept points, in ascending order.
+        """
+        ...
+
+    def sample_farthest(self, count: int, start: int = 0, threads: int | None = None) -> NDArray[numpy.uintp]:
+        """
+        Choose points by farthest point sampling, which starts from one point and repeatedly takes the point furthest
+        from all the points taken so far. This spreads the kept points evenly over the cloud regardless of its
+        density, but takes time proportional to the number of points times `count`.
+
+        :param count: the number of points to keep. If this is more than the number of points, all are kept.
+        :param start: the index of the first point to take.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: the indices of the kept points, in the order they were taken.
+        """
+        ...
+
+    def filter_statistical_outliers(
+            self,
+            k: int,
+            std_ratio: float,
+            threads: int | None = None
+    ) -> NDArray[numpy.uintp]:
+        """
+        Remove points which are far from their neighbors compared to the rest of the cloud. The mean distance from
+        each point to its `k` nearest neighbors is found, and points whose mean distance is more than `std_ratio`
+        standard deviations above the average over the whole cloud are removed.
+
+        :param k: the number of neighbors to consider for each point.
+        :param std_ratio: how many standard deviations above the average a point's mean distance may be.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: the indices of the kept points, in ascending order.
+        """
+        ...
+
+    def filter_radius_outliers(
+            self,
+            radius: float,
+            min_neighbors: int,
+            threads: int | None = None
+    ) -> NDArray[numpy.uintp]:
+        """
+        Remove points which have fewer than `min_neighbors` other points within `radius` of them.
+
+        :param radius: the distance within which neighbors are counted.
+        :param min_neighbors: the number of other points a point needs within the radius to be kept.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: the indices of the kept points, in ascending order.
+        """
+        ...
+
+    def cluster_euclidean(
+            self,
+            tolerance: float,
+            min_size: int = 1,
+    ) -> NDArray[numpy.int64]:
+        """
+        Separate the cloud into clusters of points which can be reached from one another in steps no longer than
+        `tolerance`, such as the individual parts in a scan of several parts which don't touch.
+
+        Clusters are numbered from 0 in the order of their lowest point index. Points in clusters with fewer than
+        `min_size` points are labeled -1.
+
+        :param tolerance: the largest gap between two points of the same cluster.
+        :param min_size: the smallest number of points a cluster may have.
+        :return: a numpy array of shape (n,) with the cluster label of each point.
+        """
+        ...
+
+    def cluster_dbscan(self, eps: float, min_points: int, threads: int | None = None) -> NDArray[numpy.int64]:
+        """
+        Cluster the cloud by density with DBSCAN. A point with at least `min_points` points (counting itself) within
+        `eps` of it is a core point. Core points within `eps` of each other belong to the same cluster, along with the
+        points within `eps` of them, and everything else is noise. Unlike `cluster_euclidean`, a thin trail of stray
+        points will not join two clusters together.
+
+        Clusters are numbered from 0 in the order of their lowest point index, and noise points are labeled -1.
+
+        :param eps: the neighborhood radius.
+        :param min_points: the number of points in a neighborhood for its center to be a core point.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core.
+        :return: a numpy array of shape (n,) with the cluster label of each point.
+        """
+        ...
+
+    def cluster_region_growing(
+            self,
+            max_angle: float,
+            k: int | None = None,
+            radius: float | None = None,
+            min_size: int = 1,
+    ) -> NDArray[numpy.int64]:
+        """
+        Separate the cloud into smooth regions by growing each region across the neighborhoods of its points, only
+        crossing from a point to a neighbor when the angle between their normals is no more than `max_angle`. This
+        splits surfaces at sharp edges even where they touch, so the cloud must have normals (see `estimate_normals`).
+        The normals don't need to be oriented, and points with a zero normal are left out.
+
+        Exactly one of `k` or `radius` must be given to choose the neighborhood. Regions are numbered from 0 in the
+        order of their lowest point index. Points in regions with fewer than `min_size` points are labeled -1.
+
+        :param max_angle: the largest angle in radians between the normals of neighboring points in a region.
+        :param k: use the `k` nearest points as the neighborhood, must be at least 3.
+        :param radius: use all points within this distance as the neighborhood.
+        :param min_size: the smallest number of points a region may have.
+        :return: a numpy array of shape (n,) with the region label of each point.
+        """
+        ...
+
+    def reconstruct_ball_pivoting(self, radii: List[float]) -> Mesh:
+        """
+        Reconstruct a triangle mesh from the points with the ball pivoting algorithm (Bernardini et al., 1999). A ball
+        of a given radius is placed on three points so that it contains no others, and is then rolled over the edges
+        of the growing mesh, making a new triangle with each point it touches. The points become the vertices of the
+        mesh without being moved, so the mesh passes exactly through the measured points.
+
+        The cloud must have normals which are consistently oriented (see `estimate_normals` and the
+        `orient_normals_...` methods), and the triangles will face the same way as the normals. The radius should be a
+        little larger than the typical spacing between points. Passing several radii runs a pass with each one from
+        smallest to largest, where the larger balls close gaps in sparser areas without smoothing over details that
+        the small ball could follow.
+
+        Points which are not reached by the ball are left out of the mesh, and the normals, colors, and scalar fields
+        of the points which are used are carried over as vertex attributes.
+
+        :param radii: one or more ball radii.
+        :return: a new `Mesh` reconstructed from the points.
+        """
+        ...
+
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `PointCloud` to a byte string in the versioned engeom binary format, which is also what is used
+        when the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> PointCloud:
+        """
+        Create a `PointCloud` from a byte string produced by `to_bytes`.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `PointCloud`
+        """
+        ...
+
 
 class CurveStation3:
     """
@@ -1516,6 +2828,23 @@ class CurveStation3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `CurveStation3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> CurveStation3:
+        """
+        Create a `CurveStation3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `CurveStation3`
+        """
+        ...
+
 
 class Curve3:
     """
@@ -1640,6 +2969,23 @@ class Curve3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Curve3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Curve3:
+        """
+        Create a `Curve3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Curve3`
+        """
+        ...
+
 
 class Aabb3:
     """
@@ -1771,6 +3117,23 @@ class Aabb3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Aabb3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Aabb3:
+        """
+        Create a `Aabb3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Aabb3`
+        """
+        ...
+
 
 class RayBundle3:
     """
@@ -1787,12 +3150,19 @@ class RayBundle3:
         """
         ...
 
-    def intersect_mesh(self, mesh: Mesh, iso: Iso3 | None = None, angle: float | None = None) -> NDArray[float]:
+    def intersect_mesh(
+            self, mesh: Mesh, iso: Iso3 | None = None, angle: float | None = None, threads: int | None = None
+    ) -> NDArray[float]:
         """
+        Cast every ray in the bundle against a mesh and return the points where they hit it. Rays which miss the mesh
+        are left out, so the result may have fewer rows than there are rays.
 
-        :param angle:
-        :param mesh:
-        :param iso:
-        :return:
+        :param mesh: the mesh to intersect the rays with.
+        :param iso: an optional transform to apply to the mesh before the rays are cast.
+        :param angle: if given, hits where the angle between the ray and the reversed surface normal is larger than
+        this value (in radians) are discarded.
+        :param threads: the number of worker threads to use, or None to use one thread per CPU core. The GIL is released
+        while the work runs, so other Python threads can continue in the meantime.
+        :return: a numpy array of shape (n, 3) containing the intersection points.
         """
         ...
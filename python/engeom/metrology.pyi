@@ -66,6 +66,23 @@ class Distance2:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Distance2` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Distance2:
+        """
+        Create a `Distance2` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Distance2`
+        """
+        ...
+
 
 class Distance3:
     """
@@ -135,3 +152,20 @@ class Distance3:
         """
         ...
 
+    def to_bytes(self) -> bytes:
+        """
+        Serialize the `Distance3` to a byte string in the versioned engeom binary format, which is also what is used when
+        the object is pickled.
+        :return: a byte string containing the serialized data
+        """
+        ...
+
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Distance3:
+        """
+        Create a `Distance3` from a byte string produced by `to_bytes`, including data written by older versions of engeom.
+        :param data: the byte string containing the serialized data
+        :return: the deserialized `Distance3`
+        """
+        ...
+
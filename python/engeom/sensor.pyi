@@ -16,24 +16,40 @@ class LaserLine:
             angle_limit: float | None = None,
     ):
         """
+        A simulated laser line profile sensor. All of the points are in the sensor's own frame.
 
-        :param ray_origin:
-        :param detect_origin:
-        :param line_start:
-        :param line_end:
-        :param min_range:
-        :param max_range:
-        :param rays:
-        :param angle_limit:
+        :param ray_origin: the point the laser rays are cast from.
+        :param detect_origin: the point the detector views the laser from. A spot where a ray lands is only measured if
+        neither the target nor the obstruction is in the way of the detector.
+        :param line_start: the rays are aimed at evenly spaced points from `line_start` to `line_end`.
+        :param line_end: see `line_start`.
+        :param min_range: a ray whose first hit on the target is closer than this to the ray origin measures nothing.
+        :param max_range: the furthest a ray reaches from the ray origin.
+        :param rays: the number of rays in the line.
+        :param angle_limit: if given, spots where the surface normal is more than this angle, in radians, from the
+        direction back to either the ray origin or the detector are not measured.
         """
         ...
 
-    def get_points(self, target: Mesh, obstruction: Mesh | None, iso: Iso3) -> NDArray[float]:
+    def get_points(
+            self,
+            target: Mesh,
+            obstruction: Mesh | None,
+            iso: Iso3,
+            organized: bool = False,
+            threads: int | None = None,
+    ) -> NDArray[float]:
         """
 
         :param target:
         :param obstruction:
         :param iso:
+        :param organized: if True, return an array of shape (1, rays, 3) with one entry for each ray of the laser line in
+        order from the line start to the line end, where rays that did not hit the target are NaN. If False, return the
+        hit points only as an array of shape (n, 3). The organized array holds exactly the same points, each one at the
+        ray which found it.
+        :param threads: the rays are cast in parallel without holding the GIL, on this many worker threads, or on one
+        thread per core if None.
         :return:
         """
         ...
@@ -48,11 +64,25 @@ class PanningLaserLine:
         """
         ...
 
-    def get_points(self, target: Mesh, obstruction: Mesh | None, iso: Iso3) -> NDArray[float]:
+    def get_points(
+            self,
+            target: Mesh,
+            obstruction: Mesh | None,
+            iso: Iso3,
+            organized: bool = False,
+            threads: int | None = None,
+    ) -> NDArray[float]:
         """
         :param target:
         :param obstruction:
         :param iso:
+        :param organized: if True, return an array of shape (steps, rays, 3) with one row for each step of the pan and
+        one entry for each ray of the laser line, where rays that did not hit the target are NaN. Row `i` is the laser
+        line at `iso` moved by `pan_vector * i` in the sensor's own frame, for `i` from 0 to `steps - 1`. If False,
+        return the hit points only as an array of shape (n, 3). The organized array holds exactly the same points, each
+        one at the step and ray which found it.
+        :param threads: the rays of every step are cast in parallel without holding the GIL, on this many worker
+        threads, or on one thread per core if None.
         :return:
         """
         ...
@@ -0,0 +1,237 @@
+"""
+    Tests of the alignment functions in the align module.
+"""
+import pytest
+import numpy
+from engeom import DeviationMode
+from engeom.geom2 import Curve2, Iso2
+from engeom.geom3 import Mesh, Iso3, SurfacePoint3
+from engeom.align import (
+    points_to_mesh,
+    fit_points_to_mesh,
+    mesh_to_mesh,
+    coarse_points_to_mesh,
+    global_points_to_mesh,
+    points_to_rps,
+    curve_to_curve,
+    Dof2,
+    points_to_points,
+    points_to_points_scaled,
+    points_to_points_icp,
+    Dof3,
+    RobustLoss,
+)
+
+
+@pytest.fixture
+def box_points():
+    mesh = Mesh.create_box(3.0, 2.0, 1.0)
+    points = mesh.sample_poisson(0.1)[:, :3]
+    return mesh, points
+
+
+def test_fit_points_to_mesh_recovers_offset(box_points):
+    mesh, points = box_points
+    moved = (Iso3.from_translation(0.05, -0.03, 0.02) @ Iso3.from_rz(0.02)).transform_points(points)
+
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), DeviationMode.Point)
+    aligned = result.transform.transform_points(moved)
+
+    assert result.converged
+    assert 0 < result.iterations < 100
+    assert result.rms < 1e-6
+    assert numpy.allclose(aligned, points, atol=1e-5)
+    assert result.residuals.shape == (len(points),)
+    assert result.covariance.shape == (6, 6)
+
+
+def test_fit_points_to_mesh_reports_unconverged(box_points):
+    mesh, points = box_points
+    moved = (Iso3.from_translation(0.2, -0.1, 0.05) @ Iso3.from_rz(0.05)).transform_points(points)
+
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), DeviationMode.Point, max_iterations=1)
+    assert result.iterations == 1
+    assert not result.converged
+
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), DeviationMode.Point)
+    assert result.converged
+    assert 1 < result.iterations < 100
+
+
+@pytest.mark.parametrize("mode", [DeviationMode.Point, DeviationMode.Plane])
+def test_solver_matches_engeom_alignment(box_points, mode):
+    mesh, points = box_points
+    moved = (Iso3.from_translation(0.03, -0.02, 0.01) @ Iso3.from_rx(0.01)).transform_points(points)
+
+    # points_to_mesh runs engeom's optimizer when none of the in-house options are given
+    expected = points_to_mesh(moved, mesh, Iso3.identity(), mode)
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), mode)
+
+    assert result.converged
+    assert numpy.allclose(result.transform.as_numpy(), expected.as_numpy(), atol=1e-6)
+
+
+def test_locked_dof_stay_fixed(box_points):
+    mesh, points = box_points
+    moved = Iso3.from_translation(0.05, 0.0, 0.02).transform_points(points)
+
+    dof = Dof3(tx=False, ty=False, rx=False, ry=False, rz=False)
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), DeviationMode.Plane, dof=dof, frame=Iso3.identity())
+    m = result.transform.as_numpy()
+
+    assert numpy.allclose(m[:3, :3], numpy.eye(3), atol=1e-12)
+    assert abs(m[0, 3]) < 1e-12
+    assert abs(m[1, 3]) < 1e-12
+    assert m[2, 3] == pytest.approx(-0.02, abs=1e-3)
+    assert result.covariance[0, 0] == 0.0
+
+
+@pytest.mark.parametrize("loss", [RobustLoss.Tukey(0.1), RobustLoss.Trimmed(0.1), None])
+def test_robust_alignment_rejects_outliers(box_points, loss):
+    mesh, _ = box_points
+    samples = mesh.sample_poisson(0.1)
+    points = samples[:, :3]
+    moved = Iso3.from_translation(0.02, -0.01, 0.01).transform_points(points)
+
+    # Lift a few of the points well off the surface along their normals
+    outliers = numpy.arange(0, len(moved), 25)
+    moved[outliers] += samples[outliers, 3:] * 0.5
+
+    max_distance = 0.2 if loss is None else None
+    result = fit_points_to_mesh(moved, mesh, Iso3.identity(), DeviationMode.Point, loss=loss, max_distance=max_distance)
+    aligned = result.transform.transform_points(moved)
+
+    inliers = numpy.ones(len(moved), dtype=bool)
+    inliers[outliers] = False
+    assert numpy.allclose(aligned[inliers], points[inliers], atol=1e-5)
+    assert numpy.all(result.weights[outliers] == 0.0)
+
+
+def test_global_points_to_mesh_from_arbitrary_pose(box_points):
+    mesh, points = box_points
+    pose = Iso3.from_translation(4.0, -2.0, 7.0) @ Iso3.from_rx(1.1) @ Iso3.from_rz(-2.3)
+    moved = pose.transform_points(points)
+
+    candidates = coarse_points_to_mesh(moved, mesh)
+    assert len(candidates) == 4
+    assert len(coarse_points_to_mesh(moved, mesh, all_axes=True)) == 24
+
+    # The box is symmetric, so any of its symmetric poses is a correct answer
+    result = global_points_to_mesh(moved, mesh, DeviationMode.Plane)
+    assert result.rms < 1e-6
+
+
+def test_points_to_rps_321():
+    references = [
+        SurfacePoint3(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(2.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(0.0, 1.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(0.0, 0.5, 0.5, 0.0, 1.0, 0.0),
+        SurfacePoint3(2.0, 0.5, 0.5, 0.0, 1.0, 0.0),
+        SurfacePoint3(1.0, 0.0, 0.5, 1.0, 0.0, 0.0),
+    ]
+    axes = ["z", "z", "z", "y", "y", "x"]
+    nominal = numpy.array([[r.point.x, r.point.y, r.point.z] for r in references])
+
+    truth = Iso3.from_translation(0.1, -0.2, 0.05) @ Iso3.from_rz(0.03) @ Iso3.from_rx(0.02)
+    measured = truth.inverse().transform_points(nominal)
+
+    result = points_to_rps(measured, references, axes, Iso3.identity())
+    assert numpy.allclose(result.transform.as_numpy(), truth.as_numpy(), atol=1e-9)
+    assert result.residuals.shape == (6,)
+    assert numpy.allclose(result.residuals, 0.0, atol=1e-9)
+
+    # The same alignment expressed through the reference normals
+    result = points_to_rps(measured, references, ["n"] * 6, Iso3.identity())
+    assert numpy.allclose(result.transform.as_numpy(), truth.as_numpy(), atol=1e-9)
+
+
+def test_points_to_rps_residuals_per_point():
+    references = [
+        SurfacePoint3(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(1.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(0.0, 1.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(1.0, 1.0, 0.0, 0.0, 0.0, 1.0),
+        SurfacePoint3(2.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+    ]
+    axes = ["z", "z", "xy", "x", "yz"]
+    nominal = numpy.array([[r.point.x, r.point.y, r.point.z] for r in references])
+    offsets = numpy.array([[0.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.3, 0.4, 0.0], [-0.2, 0.0, 0.0], [0.0, 0.1, 0.0]])
+
+    # Only tz may move, which the offsets leave at zero, so each point keeps its own deviation
+    dof = Dof3(tx=False, ty=False, rx=False, ry=False, rz=False)
+    result = points_to_rps(nominal + offsets, references, axes, Iso3.identity(), dof=dof, frame=Iso3.identity())
+
+    assert result.residuals.shape == (5,)
+    assert numpy.allclose(result.residuals, [0.0, 0.0, 0.5, 0.2, 0.1], atol=1e-9)
+    assert result.max_residual == pytest.approx(0.5)
+    assert numpy.all(result.weights == 1.0)
+
+
+def test_points_to_rps_bad_axis():
+    references = [SurfacePoint3(0.0, 0.0, 0.0, 0.0, 0.0, 1.0)]
+    with pytest.raises(ValueError):
+        points_to_rps(numpy.zeros((1, 3)), references, ["q"], Iso3.identity())
+
+
+def test_points_to_points_known_correspondence():
+    points = numpy.random.default_rng(0).uniform(-1.0, 1.0, (50, 3))
+    truth = Iso3.from_translation(0.5, -1.0, 2.0) @ Iso3.from_rz(0.3) @ Iso3.from_rx(-0.2)
+    targets = truth.transform_points(points)
+
+    iso = points_to_points(points, targets)
+    assert numpy.allclose(iso.as_numpy(), truth.as_numpy(), atol=1e-9)
+
+    iso, scale = points_to_points_scaled(points, truth.transform_points(points * 2.5))
+    assert scale == pytest.approx(2.5)
+    assert numpy.allclose(iso.as_numpy(), truth.as_numpy(), atol=1e-9)
+
+
+def test_points_to_points_length_mismatch():
+    with pytest.raises(ValueError):
+        points_to_points(numpy.zeros((5, 3)), numpy.zeros((4, 3)))
+
+
+def test_points_to_points_icp(box_points):
+    _, points = box_points
+    moved = (Iso3.from_translation(0.02, 0.01, -0.01) @ Iso3.from_rz(0.01)).transform_points(points)
+
+    iso = points_to_points_icp(moved, points, Iso3.identity())
+    assert numpy.allclose(iso.transform_points(moved), points, atol=1e-5)
+
+
+def test_mesh_to_mesh(box_points):
+    mesh, _ = box_points
+    truth = Iso3.from_translation(0.03, -0.02, 0.01) @ Iso3.from_rz(0.01)
+    moving = mesh.cloned()
+    moving.transform_by(truth.inverse())
+
+    iso = mesh_to_mesh(moving, mesh, Iso3.identity(), DeviationMode.Plane, 0.1)
+    assert numpy.allclose(iso.as_numpy(), truth.as_numpy(), atol=1e-4)
+
+
+@pytest.fixture
+def wavy_profile():
+    x = numpy.linspace(0.0, 10.0, 400)
+    points = numpy.stack((x, numpy.sin(x * 1.7) * 0.8 + 0.1 * x), axis=1)
+    return Curve2(points), points
+
+
+@pytest.mark.parametrize("mode", [DeviationMode.Point, DeviationMode.Plane])
+def test_curve_to_curve_partial_overlap(wavy_profile, mode):
+    nominal, points = wavy_profile
+    truth = Iso2(0.05, -0.03, 0.02)
+    section = Curve2(truth.inverse().transform_points(points[100:250]))
+
+    iso = curve_to_curve(section, nominal, Iso2.identity(), mode)
+    aligned = iso.transform_points(section.points)
+    assert numpy.allclose(aligned, points[100:250], atol=1e-6)
+
+
+def test_curve_to_curve_locked_rotation(wavy_profile):
+    nominal, points = wavy_profile
+    moved = points[100:250] - numpy.array([0.05, -0.03])
+
+    iso = curve_to_curve(moved, nominal, Iso2.identity(), DeviationMode.Point, dof=Dof2(r=False))
+    assert abs(iso.as_numpy()[1, 0]) < 1e-12
+    assert numpy.allclose(iso.transform_points(moved), points[100:250], atol=1e-6)
@@ -0,0 +1,478 @@
+"""
+    Tests of mesh operations in the geom3 module.
+"""
+import pytest
+import numpy
+from engeom import DeviationMode, SelectOp
+from engeom.geom3 import HoleFill, Mesh, Plane3, Point3
+
+
+def test_deviation_threads_match():
+    mesh = Mesh.create_sphere(1.0, 20, 20)
+    points = numpy.random.default_rng(0).normal(size=(500, 3))
+
+    serial = mesh.deviation(points, DeviationMode.Point, threads=1)
+    parallel = mesh.deviation(points, DeviationMode.Point)
+    assert numpy.allclose(serial, parallel)
+
+
+def test_zero_threads_raises():
+    mesh = Mesh.create_sphere(1.0, 20, 20)
+    with pytest.raises(ValueError):
+        mesh.deviation(numpy.zeros((1, 3)), DeviationMode.Plane, threads=0)
+
+
+def _grid(rows, cols):
+    y, x = numpy.mgrid[0:rows, 0:cols].astype(float)
+    return numpy.stack([x, y, numpy.zeros_like(x)], axis=-1)
+
+
+def _edges(mesh):
+    v = mesh.vertices[mesh.faces]
+    return v[:, 1] - v[:, 0], v[:, 2] - v[:, 0]
+
+
+def test_organized_grid_skips_missing():
+    grid = _grid(4, 5)
+    grid[1, 1] = numpy.nan
+    mesh = Mesh.from_organized_grid(grid)
+
+    # The four cells around the missing sample lose one triangle each
+    assert len(mesh.faces) == 2 * 3 * 4 - 4
+    assert len(mesh.vertices) == 4 * 5 - 1
+    assert numpy.all(numpy.cross(*_edges(mesh))[:, 2] > 0)
+
+
+def test_organized_grid_edge_cutoff():
+    grid = _grid(4, 5)
+    grid[:, 3:, 2] = 10.0
+    mesh = Mesh.from_organized_grid(grid, max_edge_length=2.0)
+    assert len(mesh.faces) == 2 * 3 * 4 - 2 * 3
+
+
+def test_organized_grid_faces_viewpoint():
+    grid = _grid(3, 3)
+    mesh = Mesh.from_organized_grid(grid, viewpoint=Point3(1, 1, -5))
+    assert numpy.all(numpy.cross(*_edges(mesh))[:, 2] < 0)
+
+
+def test_decimate_ratio():
+    mesh = Mesh.create_sphere(1.0, 40, 40)
+    small, error = mesh.decimate(ratio=0.2)
+
+    assert len(small.faces) <= round(0.2 * len(mesh.faces))
+    assert 0 < error < 0.1
+    assert numpy.allclose(numpy.linalg.norm(small.vertices, axis=1), 1.0, atol=0.1)
+
+
+def test_decimate_max_error():
+    mesh = Mesh.create_sphere(1.0, 40, 40)
+    coarse, coarse_error = mesh.decimate(max_error=0.01)
+    fine, fine_error = mesh.decimate(max_error=0.001)
+
+    assert len(coarse.faces) < len(fine.faces) < len(mesh.faces)
+    assert fine_error <= coarse_error
+
+
+def test_decimate_needs_a_target():
+    mesh = Mesh.create_sphere(1.0, 10, 10)
+    with pytest.raises(ValueError):
+        mesh.decimate()
+
+
+def _edge_lengths(mesh):
+    v = mesh.vertices[mesh.faces]
+    return numpy.linalg.norm(v - numpy.roll(v, 1, axis=1), axis=2).ravel()
+
+
+def test_remesh_evens_out_edges():
+    mesh = Mesh.create_box(2.0, 2.0, 2.0)
+    remeshed = mesh.remesh(0.2, feature_angle=0.5)
+
+    lengths = _edge_lengths(remeshed)
+    assert abs(lengths.mean() - 0.2) < 0.05
+    assert lengths.max() < 0.4
+
+    # The corners of the box are features and stay where they are
+    corners = numpy.all(numpy.isclose(numpy.abs(remeshed.vertices), 1.0), axis=1)
+    assert len(numpy.unique(remeshed.vertices[corners].round(9), axis=0)) == 8
+    assert numpy.allclose(numpy.abs(remeshed.vertices).max(axis=1), 1.0)
+
+
+def _octahedron():
+    vertices = numpy.array([[1, 0, 0], [-1, 0, 0], [0, 1, 0], [0, -1, 0], [0, 0, 1], [0, 0, -1]], dtype=float)
+    faces = numpy.array([
+        [0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4],
+        [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5],
+    ], dtype=numpy.uint32)
+    return vertices, faces
+
+
+def _split_box():
+    # A unit box with four vertices of its own on every side, the way it comes from a file with flat normals
+    vertices, faces = [], []
+    for axis in range(3):
+        u, v = (axis + 1) % 3, (axis + 2) % 3
+        for side in (0.0, 1.0):
+            k = len(vertices)
+            for a, b in [(0, 0), (1, 0), (1, 1), (0, 1)]:
+                p = [0.0, 0.0, 0.0]
+                p[axis], p[u], p[v] = side, a, b
+                vertices.append(p)
+            quad = [[k, k + 1, k + 2], [k, k + 2, k + 3]]
+            faces.extend(quad if side else [f[::-1] for f in quad])
+    return numpy.array(vertices), numpy.array(faces, dtype=numpy.uint32)
+
+
+def _is_closed(mesh):
+    # Every directed edge appears once and is matched by its reverse
+    f = mesh.faces
+    edges = {tuple(e) for e in numpy.concatenate([f[:, [0, 1]], f[:, [1, 2]], f[:, [2, 0]]])}
+    return len(edges) == 3 * len(f) and all((b, a) in edges for a, b in edges)
+
+
+@pytest.mark.parametrize("method", [HoleFill.Flat, HoleFill.MinimumArea, HoleFill.Curvature])
+def test_fill_holes_closes_mesh(method):
+    vertices, faces = _octahedron()
+    mesh = Mesh(vertices, faces[2:])
+    filled, report = mesh.fill_holes(4, method)
+
+    assert report.holes_filled == 1
+    assert report.faces_added == len(filled.faces) - 6
+    assert _is_closed(filled)
+
+
+def test_fill_holes_skips_large():
+    vertices, faces = _octahedron()
+    mesh = Mesh(vertices, faces[2:])
+    filled, report = mesh.fill_holes(max_edges=3)
+
+    assert report.holes_filled == 0
+    assert report.holes_skipped == 1
+    assert not report
+    assert len(filled.faces) == 6
+
+
+def test_fill_holes_long_loop():
+    # A cone with its base left open, which is too long a loop for the minimum area fill
+    n = 600
+    t = numpy.linspace(0, 2 * numpy.pi, n, endpoint=False)
+    vertices = numpy.vstack([numpy.stack([numpy.cos(t), numpy.sin(t), numpy.zeros(n)], axis=1), [[0, 0, 1]]])
+    faces = numpy.array([[i, (i + 1) % n, n] for i in range(n)], dtype=numpy.uint32)
+    filled, report = Mesh(vertices, faces).fill_holes(None)
+
+    assert report.holes_filled == 1
+    assert report.faces_added == n - 2
+    assert _is_closed(filled)
+
+
+def test_repair_report():
+    vertices, faces = _octahedron()
+    vertices = numpy.vstack([vertices, [[5, 0, 0], [5, 1, 0], [5, 0, 1]]])
+    faces = numpy.vstack([faces, faces[:1], [[6, 7, 8]]]).astype(numpy.uint32)
+    faces[1] = faces[1, ::-1]
+
+    repaired, report = Mesh(vertices, faces).repair(min_island_faces=2)
+
+    assert report.duplicate_faces_removed == 1
+    assert report.faces_flipped == 1
+    assert report.islands_removed == 1
+    assert len(repaired.vertices) == 6
+    assert _is_closed(repaired)
+
+
+def test_check_closed_mesh():
+    vertices, faces = _octahedron()
+    report = Mesh(vertices, faces).check()
+
+    assert report
+    assert report.is_watertight
+    assert report.euler_characteristic == 2
+    assert report.genus == 0
+    assert report.boundary_edges.shape == (0, 2)
+
+
+def test_check_finds_problems():
+    vertices, faces = _octahedron()
+    faces = faces[1:].copy()
+    faces[0] = faces[0, ::-1]
+    report = Mesh(vertices, faces).check()
+
+    assert not report
+    assert not report.is_watertight
+    assert report.boundary_loops == 1
+    assert report.boundary_edges.shape == (3, 2)
+    assert report.inconsistent_edges.shape == (2, 2)
+    assert report.genus is None
+
+
+def test_check_self_intersections():
+    vertices, faces = _octahedron()
+    vertices = numpy.vstack([vertices, vertices + [0.5, 0.3, 0.2]])
+    faces = numpy.vstack([faces, faces + 6]).astype(numpy.uint32)
+    report = Mesh(vertices, faces).check()
+
+    assert report.components == 2
+    assert len(report.self_intersections) > 0
+    assert numpy.all(report.self_intersections[:, 0] < 8)
+    assert numpy.all(report.self_intersections[:, 1] >= 8)
+    assert len(Mesh(vertices, faces).check(self_intersections=False).self_intersections) == 0
+
+
+def test_check_split_vertices():
+    report = Mesh(*_split_box()).check()
+
+    assert report
+    assert report.is_watertight
+    assert report.components == 1
+    assert report.euler_characteristic == 2
+    assert report.genus == 0
+
+
+def test_fill_holes_split_vertices():
+    # Only the missing top is a hole, the seams between the sides are not
+    vertices, faces = _split_box()
+    filled, report = Mesh(vertices, faces[:-2]).fill_holes(4)
+
+    assert report.holes_filled == 1
+    assert report.faces_added == 2
+    assert filled.check().is_watertight
+    assert filled.volume() == pytest.approx(1.0)
+
+
+def test_repair_split_vertices():
+    vertices, faces = _split_box()
+    faces[0] = faces[0, ::-1]
+    repaired, report = Mesh(vertices, faces).repair(min_island_faces=3)
+
+    assert report.faces_flipped == 1
+    assert report.islands_removed == 0
+    assert len(repaired.faces) == 12
+    assert repaired.volume() == pytest.approx(1.0)
+
+
+def test_mass_properties():
+    vertices, faces = _octahedron()
+    mesh = Mesh(vertices, faces)
+
+    assert mesh.surface_area() == pytest.approx(4 * numpy.sqrt(3))
+    assert mesh.volume() == pytest.approx(4 / 3)
+    assert mesh.centroid().coords.norm() == pytest.approx(0, abs=1e-12)
+    assert numpy.allclose(mesh.inertia_tensor(density=3.0), numpy.eye(3) * 0.8)
+
+
+def test_volume_signed_and_checked():
+    vertices, faces = _octahedron()
+    flipped = Mesh(vertices + [1, 2, 3], faces[:, ::-1].copy())
+
+    assert flipped.volume() == pytest.approx(-4 / 3)
+    assert flipped.centroid().coords.norm() == pytest.approx(numpy.sqrt(14))
+
+    opened = Mesh(vertices, faces[1:])
+    with pytest.raises(ValueError):
+        opened.volume()
+    opened.volume(require_closed=False)
+    assert opened.surface_centroid().z < 0
+
+
+def test_mass_properties_split_vertices():
+    vertices, faces = _split_box()
+    mesh = Mesh(vertices, faces)
+
+    assert mesh.volume() == pytest.approx(1.0)
+    assert numpy.allclose(mesh.centroid().coords.as_numpy(), [0.5, 0.5, 0.5])
+    assert numpy.allclose(mesh.inertia_tensor(), numpy.eye(3) / 6)
+
+
+def test_section_split_vertices():
+    vertices, faces = _split_box()
+    mesh = Mesh(vertices * 2 - 1, faces)
+
+    curves = mesh.section(Plane3(0, 0, 1, 0.3))
+    assert len(curves) == 1
+    assert curves[0].length() == pytest.approx(8.0)
+    assert numpy.allclose(numpy.abs(curves[0].points[:, 2]), 0.3)
+
+    assert len(mesh.section(Plane3(0, 0, 1, 0.3), threads=1)) == 1
+    assert mesh.section(Plane3(0, 0, 1, 5.0)) == []
+
+
+def test_principal_axes():
+    vertices, faces = _octahedron()
+    mesh = Mesh(vertices * [1, 3, 2] + [5, -1, 2], faces)
+    iso = mesh.principal_axes()
+
+    assert numpy.allclose(iso.origin.coords.as_numpy(), [5, -1, 2])
+    axes = iso.as_numpy()[:3, :3]
+    assert numpy.allclose(numpy.abs(axes), [[0, 0, 1], [1, 0, 0], [0, 1, 0]], atol=1e-9)
+
+
+def test_curvature_sphere():
+    mesh = Mesh.create_sphere(2.0, 60, 60)
+    curvature = mesh.curvature()
+
+    assert curvature.k1.shape == (len(mesh.vertices),)
+    assert curvature.d1.shape == mesh.vertices.shape
+    assert curvature.face_mean.shape == (len(mesh.faces),)
+    assert numpy.nanmedian(curvature.mean) == pytest.approx(0.5, abs=0.02)
+    assert numpy.nanmedian(curvature.gaussian) == pytest.approx(0.25, abs=0.02)
+
+
+def test_curvature_saddle_directions():
+    xs = numpy.linspace(-0.5, 0.5, 21)
+    x, y = numpy.meshgrid(xs, xs)
+    grid = numpy.stack([x, y, x ** 2 - y ** 2], axis=-1)
+    mesh = Mesh.from_organized_grid(grid)
+    curvature = mesh.curvature(radius=0.2)
+
+    i = numpy.argmin(numpy.linalg.norm(mesh.vertices[:, :2], axis=1))
+    assert curvature.k1[i] == pytest.approx(2.0, abs=0.05)
+    assert curvature.k2[i] == pytest.approx(-2.0, abs=0.05)
+    assert abs(curvature.d1[i, 1]) == pytest.approx(1.0, abs=1e-3)
+    assert abs(curvature.d2[i, 0]) == pytest.approx(1.0, abs=1e-3)
+
+
+def test_feature_edges_cube():
+    vertices = numpy.array([[x, y, z] for x in (0, 1) for y in (0, 1) for z in (0, 1)], dtype=float)
+    faces = numpy.array([
+        [0, 1, 3], [0, 3, 2], [4, 6, 7], [4, 7, 5], [0, 4, 5], [0, 5, 1],
+        [2, 3, 7], [2, 7, 6], [0, 2, 6], [0, 6, 4], [1, 5, 7], [1, 7, 3],
+    ], dtype=numpy.uint32)
+    mesh = Mesh(vertices, faces)
+    curves = mesh.feature_edges(numpy.radians(30))
+
+    assert len(curves) == 12
+    assert sum(c.length() for c in curves) == pytest.approx(12.0)
+    assert mesh.feature_edges(None) == []
+
+
+def test_feature_edges_split_vertices():
+    vertices, faces = _split_box()
+    mesh = Mesh(vertices, faces)
+    curves = mesh.feature_edges(numpy.radians(30))
+
+    assert len(curves) == 12
+    assert sum(c.length() for c in curves) == pytest.approx(12.0)
+    assert mesh.feature_edges(None) == []
+
+
+def test_feature_edges_regions_and_boundary():
+    xs = numpy.linspace(0, 1, 11)
+    x, y = numpy.meshgrid(xs, xs)
+    grid = numpy.stack([x, y, numpy.zeros_like(x)], axis=-1)
+    mesh = Mesh.from_organized_grid(grid)
+
+    boundary = mesh.feature_edges(numpy.radians(30))
+    assert len(boundary) == 1
+    assert boundary[0].length() == pytest.approx(4.0)
+
+    regions = (mesh.vertices[mesh.faces].mean(axis=1)[:, 0] > 0.5).astype(int)
+    border = mesh.feature_edges(None, boundaries=False, regions=regions)
+    assert len(border) == 1
+    assert border[0].length() == pytest.approx(1.0)
+
+    with pytest.raises(ValueError):
+        mesh.feature_edges(None, regions=regions[1:])
+
+
+def _noisy_sphere():
+    mesh = Mesh.create_sphere(1.0, 40, 40)
+    rng = numpy.random.default_rng(5)
+    vertices = mesh.vertices
+    vertices = vertices * (1 + rng.uniform(-0.02, 0.02, len(vertices)))[:, None]
+    return Mesh(vertices, mesh.faces)
+
+
+def test_smooth_taubin_keeps_size():
+    mesh = _noisy_sphere()
+    radius = numpy.linalg.norm(mesh.vertices, axis=1)
+    laplacian = numpy.linalg.norm(mesh.smooth_laplacian().vertices, axis=1)
+    taubin = numpy.linalg.norm(mesh.smooth_taubin().vertices, axis=1)
+
+    assert laplacian.std() < radius.std()
+    assert taubin.std() < radius.std()
+    assert abs(taubin.mean() - 1) < abs(laplacian.mean() - 1)
+
+
+def test_smooth_bilateral_reduces_noise():
+    mesh = _noisy_sphere()
+    radius = numpy.linalg.norm(mesh.vertices, axis=1)
+    smoothed = numpy.linalg.norm(mesh.smooth_bilateral().vertices, axis=1)
+
+    assert smoothed.std() < radius.std() / 2
+    assert smoothed.mean() == pytest.approx(1.0, abs=0.01)
+
+
+def test_smooth_limited_to_region():
+    mesh = _noisy_sphere()
+    region = mesh.face_select_none().facing(0, 0, 1, numpy.radians(45), SelectOp.Add)
+    smoothed = mesh.smooth_taubin(region=region)
+
+    moved = numpy.linalg.norm(smoothed.vertices - mesh.vertices, axis=1) > 0
+    assert moved.any()
+    assert not moved[mesh.vertices[:, 2] < 0].any()
+
+    mask = mesh.vertices[:, 0] > 0
+    smoothed = mesh.smooth_laplacian(mask=mask, region=region)
+    moved = numpy.linalg.norm(smoothed.vertices - mesh.vertices, axis=1) > 0
+    assert not moved[~mask].any()
+
+
+def _offset_boxes():
+    box = Mesh.create_box(2.0, 2.0, 2.0)
+    moved = Mesh(box.vertices + [1.0, 0.5, 0.3], box.faces)
+    return box, moved
+
+
+def test_boolean_volumes():
+    box, moved = _offset_boxes()
+    overlap = 1.0 * 1.5 * 1.7
+
+    union = box.union(moved)
+    difference = box.difference(moved)
+    intersection = box.intersection(moved)
+
+    assert union.volume() == pytest.approx(16 - overlap)
+    assert difference.volume() == pytest.approx(8 - overlap)
+    assert intersection.volume() == pytest.approx(overlap)
+    for mesh in (union, difference, intersection):
+        assert mesh.check().is_watertight
+
+
+def test_boolean_disjoint_and_open():
+    box, _ = _offset_boxes()
+    far = Mesh(box.vertices + [5.0, 0.0, 0.0], box.faces)
+
+    assert box.intersection(far) is None
+    assert box.difference(far).volume() == pytest.approx(8)
+    union = box.union(far)
+    assert union.volume() == pytest.approx(16)
+    assert union.check().is_watertight
+    assert box.difference(Mesh(box.vertices * 2, box.faces)) is None
+
+    vertices, faces = _octahedron()
+    with pytest.raises(ValueError):
+        box.union(Mesh(vertices, faces[1:]))
+
+
+@pytest.mark.parametrize(
+    "offset, volumes",
+    [
+        ([1.0, 0.0, 0.0], (2.0, 1.0, None)),
+        ([0.5, 0.25, 0.0], (1.625, 0.625, 0.375)),
+        ([0.0, 0.0, 0.0], (1.0, None, 1.0)),
+    ],
+)
+def test_boolean_coplanar_faces(offset, volumes):
+    # Boxes which touch along a face, overlap with their top and bottom faces in the same planes, or are the same box
+    vertices, faces = _split_box()
+    box = Mesh(vertices, faces)
+    other = Mesh(vertices + offset, faces)
+
+    for op, volume in zip((box.union, box.difference, box.intersection), volumes):
+        result = op(other)
+        if volume is None:
+            assert result is None
+        else:
+            assert result.volume() == pytest.approx(volume)
+            assert result.check().is_watertight
@@ -0,0 +1,71 @@
+"""
+    Round trip tests of the mesh file formats.
+"""
+import pytest
+import numpy
+from engeom.geom3 import Mesh
+
+
+def _quad() -> Mesh:
+    vertices = numpy.array([[0, 0, 0], [1, 0, 0], [1, 1, 0], [0, 1, 0]], dtype=numpy.float64)
+    faces = numpy.array([[0, 1, 2], [0, 2, 3]], dtype=numpy.uint32)
+    mesh = Mesh(vertices, faces)
+    mesh.set_vertex_colors(numpy.array([[255, 0, 0], [0, 255, 0], [0, 0, 255], [10, 20, 30]], dtype=numpy.uint8))
+    mesh.set_scalar_field("quality", numpy.array([0.5, 1.0, 1.5, 2.0]))
+    return mesh
+
+
+@pytest.mark.parametrize("binary", [True, False])
+def test_ply_round_trip(tmp_path, binary):
+    mesh = _quad()
+    path = tmp_path / "quad.ply"
+    mesh.write_ply(path, binary=binary)
+
+    loaded = Mesh.load_ply(path)
+    assert numpy.allclose(loaded.vertices, mesh.vertices)
+    assert numpy.array_equal(loaded.faces, mesh.faces)
+    assert numpy.array_equal(loaded.vertex_colors, mesh.vertex_colors)
+    assert loaded.scalar_field_names == ["quality"]
+    assert numpy.allclose(loaded.get_scalar_field("quality"), [0.5, 1.0, 1.5, 2.0])
+
+
+@pytest.mark.parametrize("index", ["-1", "1.5", "4"])
+def test_ply_invalid_index_raises(tmp_path, index):
+    path = tmp_path / "bad.ply"
+    path.write_text("\n".join([
+        "ply", "format ascii 1.0", "element vertex 3",
+        "property float x", "property float y", "property float z",
+        "element face 1", "property list uchar int vertex_indices", "end_header",
+        "0 0 0", "1 0 0", "0 1 0", f"3 0 1 {index}", "",
+    ]))
+
+    with pytest.raises(IOError):
+        Mesh.load_ply(path)
+
+
+def test_obj_round_trip(tmp_path):
+    mesh = _quad()
+    path = tmp_path / "quad.obj"
+    mesh.write_obj(path)
+
+    loaded = Mesh.load_obj(path)
+    assert numpy.allclose(loaded.vertices, mesh.vertices)
+    assert numpy.array_equal(loaded.faces, mesh.faces)
+    assert numpy.array_equal(loaded.vertex_colors, mesh.vertex_colors)
+
+
+def test_3mf_round_trip(tmp_path):
+    mesh = _quad()
+    path = tmp_path / "quad.3mf"
+    mesh.write_3mf(path)
+
+    loaded = Mesh.load_3mf(path)
+    assert numpy.allclose(loaded.vertices, mesh.vertices)
+    assert numpy.array_equal(loaded.faces, mesh.faces)
+    assert numpy.array_equal(loaded.vertex_colors, mesh.vertex_colors)
+
+
+def test_missing_scalar_field_raises():
+    mesh = _quad()
+    with pytest.raises(KeyError):
+        mesh.get_scalar_field("intensity")
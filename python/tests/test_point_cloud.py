@@ -0,0 +1,207 @@
+"""
+    Tests of the PointCloud class in the geom3 module.
+"""
+import pickle
+import pytest
+import numpy
+from engeom.geom3 import PointCloud, Iso3, Point3
+
+
+def _cloud():
+    rng = numpy.random.default_rng(0)
+    points = rng.uniform(-1, 1, size=(2000, 3))
+    return points, PointCloud(points)
+
+
+def test_nearest_matches_brute_force():
+    points, cloud = _cloud()
+    queries = numpy.random.default_rng(1).uniform(-1.5, 1.5, size=(50, 3))
+    indices, distances = cloud.nearest(queries)
+
+    d = numpy.linalg.norm(points[None, :, :] - queries[:, None, :], axis=2)
+    assert numpy.array_equal(indices, d.argmin(axis=1))
+    assert numpy.allclose(distances, d.min(axis=1))
+
+
+def test_nearest_k_and_radius_match_brute_force():
+    points, cloud = _cloud()
+    queries = numpy.random.default_rng(2).uniform(-1, 1, size=(20, 3))
+    indices, distances = cloud.nearest_k(queries, 8)
+    found = cloud.within_radius(queries, 0.2)
+
+    d = numpy.linalg.norm(points[None, :, :] - queries[:, None, :], axis=2)
+    for i in range(len(queries)):
+        assert numpy.allclose(distances[i], numpy.sort(d[i])[:8])
+        assert set(found[i]) == set(numpy.nonzero(d[i] <= 0.2)[0])
+
+
+def test_nearest_k_out_of_range():
+    _, cloud = _cloud()
+    with pytest.raises(ValueError):
+        cloud.nearest_k(numpy.zeros((1, 3)), len(cloud) + 1)
+
+
+def test_attributes_follow_points():
+    points, cloud = _cloud()
+    cloud.set_normals(numpy.tile([0.0, 0.0, 1.0], (len(points), 1)))
+    cloud.set_scalar_field("index", numpy.arange(len(points), dtype=numpy.float64))
+
+    subset = cloud.create_from_indices([5, 3, 1])
+    assert numpy.allclose(subset.points, points[[5, 3, 1]])
+    assert numpy.allclose(subset.get_scalar_field("index"), [5, 3, 1])
+
+    iso = Iso3.from_rotation(numpy.pi / 2, 1, 0, 0)
+    subset.transform_by(iso)
+    assert numpy.allclose(subset.normals, [[0, -1, 0]] * 3)
+    assert numpy.allclose(subset.points, iso.transform_points(points[[5, 3, 1]]))
+
+    subset.append(PointCloud(numpy.zeros((2, 3))))
+    assert len(subset) == 5
+    assert numpy.isnan(subset.get_scalar_field("index")[3:]).all()
+    assert subset.nearest(numpy.zeros((1, 3)))[1][0] == 0
+
+
+def test_pickle_round_trip():
+    points, cloud = _cloud()
+    cloud.set_colors(numpy.full((len(points), 3), 128, dtype=numpy.uint8))
+    loaded = pickle.loads(pickle.dumps(cloud))
+    assert numpy.allclose(loaded.points, points)
+    assert (loaded.colors[:, 3] == 255).all()
+
+
+def _sphere():
+    rng = numpy.random.default_rng(3)
+    points = rng.normal(size=(3000, 3))
+    points /= numpy.linalg.norm(points, axis=1)[:, None]
+    return points, PointCloud(points * 2.0 + [1.0, 2.0, 3.0])
+
+
+@pytest.mark.parametrize("k, radius", [(12, None), (None, 0.5)])
+def test_estimate_normals_orient_mst(k, radius):
+    outward, cloud = _sphere()
+    cloud.estimate_normals(k=k, radius=radius)
+    cloud.orient_normals_mst()
+
+    dots = numpy.einsum("ij,ij->i", cloud.normals, outward)
+    assert dots.min() > 0.99
+
+
+def test_orient_normals_towards():
+    outward, cloud = _sphere()
+    cloud.estimate_normals(k=12)
+    cloud.orient_normals_towards(Point3(1.0, 2.0, 3.0))
+    assert (numpy.einsum("ij,ij->i", cloud.normals, outward) < 0).all()
+
+    cloud.orient_normals_to_origins(cloud.points + outward)
+    assert (numpy.einsum("ij,ij->i", cloud.normals, outward) > 0).all()
+
+    # Re-estimating keeps the orientation
+    cloud.estimate_normals(radius=0.5)
+    assert (numpy.einsum("ij,ij->i", cloud.normals, outward) > 0).all()
+
+
+def test_estimate_normals_needs_one_neighborhood():
+    _, cloud = _sphere()
+    with pytest.raises(ValueError):
+        cloud.estimate_normals()
+    with pytest.raises(ValueError):
+        cloud.estimate_normals(k=10, radius=0.5)
+    with pytest.raises(ValueError):
+        cloud.orient_normals_mst()
+
+
+def _plane_with_flyers():
+    rng = numpy.random.default_rng(4)
+    points = numpy.zeros((4000, 3))
+    points[:, :2] = rng.uniform(-1, 1, size=(4000, 2))
+    flyers = rng.uniform(-1, 1, size=(20, 3)) + [0, 0, 3]
+    return PointCloud(numpy.vstack((points, flyers)))
+
+
+def test_outlier_filters_remove_flyers():
+    cloud = _plane_with_flyers()
+    statistical = cloud.filter_statistical_outliers(10, 2.0)
+    radius = cloud.filter_radius_outliers(0.15, 3)
+
+    assert statistical.max() < 4000 and len(statistical) > 3900
+    assert numpy.array_equal(radius, numpy.arange(4000))
+
+
+def test_voxel_downsample():
+    cloud = _plane_with_flyers()
+    cloud.set_scalar_field("z", cloud.points[:, 2].copy())
+    kept = cloud.voxel_downsample(0.5)
+    centroids = cloud.voxel_centroids(0.5)
+
+    # 16 voxels on the plane, plus whatever the flyers fall in
+    assert len(kept) == len(centroids)
+    assert 16 < len(kept) <= 36
+    assert numpy.allclose(centroids.get_scalar_field("z"), centroids.points[:, 2])
+
+
+def test_sampling():
+    cloud = _plane_with_flyers()
+    random = cloud.sample_random(100, seed=7)
+    assert len(numpy.unique(random)) == 100
+    assert numpy.array_equal(random, cloud.sample_random(100, seed=7))
+
+    farthest = cloud.sample_farthest(10)
+    assert farthest[0] == 0 and len(numpy.unique(farthest)) == 10
+    assert farthest[1] >= 4000
+
+
+def _two_blobs():
+    rng = numpy.random.default_rng(5)
+    a = rng.uniform(-0.5, 0.5, size=(1000, 3))
+    b = rng.uniform(-0.5, 0.5, size=(1000, 3)) + [3, 0, 0]
+    trail = numpy.array([[10.0 + i, 5.0, 0.0] for i in range(5)])
+    return PointCloud(numpy.vstack((a, b, trail)))
+
+
+@pytest.mark.parametrize("method", ["euclidean", "dbscan"])
+def test_clusters_separate_parts(method):
+    cloud = _two_blobs()
+    if method == "euclidean":
+        labels = cloud.cluster_euclidean(0.2, min_size=2)
+    else:
+        labels = cloud.cluster_dbscan(0.2, 5)
+
+    assert (labels[:1000] == 0).all()
+    assert (labels[1000:2000] == 1).all()
+    assert (labels[2000:] == -1).all()
+
+
+def test_region_growing_splits_at_edge():
+    x, y = numpy.meshgrid(numpy.linspace(0, 2, 40), numpy.linspace(0, 2, 40))
+    floor = numpy.column_stack((x.ravel(), y.ravel(), numpy.zeros(x.size)))
+    wall = numpy.column_stack((x.ravel(), numpy.zeros(x.size), y.ravel() + 0.05))
+    cloud = PointCloud(numpy.vstack((floor, wall)))
+    cloud.estimate_normals(k=8)
+
+    labels = cloud.cluster_region_growing(0.2, k=8, min_size=100)
+    assert labels.max() == 1
+    assert numpy.bincount(labels[:1600] + 1).argmax() == 1
+    assert numpy.bincount(labels[1600:] + 1).argmax() == 2
+
+
+def test_ball_pivoting_closes_sphere():
+    n = 2000
+    i = numpy.arange(n) + 0.5
+    y = 1 - 2 * i / n
+    theta = numpy.pi * (3 - numpy.sqrt(5)) * i
+    r = numpy.sqrt(1 - y * y)
+    normals = numpy.column_stack((r * numpy.cos(theta), y, r * numpy.sin(theta)))
+    cloud = PointCloud(normals * 5.0, normals=normals)
+
+    mesh = cloud.reconstruct_ball_pivoting([0.5, 0.8])
+    assert len(mesh.vertices) == n
+    assert len(mesh.faces) == 2 * n - 4
+
+    outward = numpy.einsum("ij,ij->i", mesh.face_normals, mesh.vertices[mesh.faces[:, 0]])
+    assert (outward > 0).all()
+
+
+def test_ball_pivoting_needs_normals():
+    _, cloud = _cloud()
+    with pytest.raises(ValueError):
+        cloud.reconstruct_ball_pivoting([0.1])
@@ -0,0 +1,74 @@
+"""
+    Tests of the simulated sensors in the sensor module.
+"""
+import pytest
+import numpy
+from engeom.geom3 import Mesh, Iso3, Point3, Vector3
+from engeom.sensor import LaserLine, PanningLaserLine
+
+
+@pytest.fixture
+def laser_line():
+    return LaserLine(
+        ray_origin=Point3(0.0, 0.0, 5.0),
+        detect_origin=Point3(0.5, 0.0, 5.0),
+        line_start=Point3(-1.0, 0.0, 0.0),
+        line_end=Point3(1.0, 0.0, 0.0),
+        min_range=0.5,
+        max_range=10.0,
+        rays=50,
+    )
+
+
+def _sorted_rows(points):
+    return points[numpy.lexsort(points.T[::-1])]
+
+
+def _assert_same_points(organized, flat):
+    found = organized.reshape(-1, 3)
+    found = found[~numpy.isnan(found).any(axis=1)]
+    assert found.shape == flat.shape
+    assert numpy.allclose(_sorted_rows(found), _sorted_rows(flat))
+
+
+def test_laser_line_organized_matches_flat(laser_line):
+    target = Mesh.create_sphere(0.8, 20, 20)
+    iso = Iso3.from_translation(0.1, 0.05, 0.0)
+
+    flat = laser_line.get_points(target, None, iso)
+    organized = laser_line.get_points(target, None, iso, organized=True)
+
+    assert organized.shape == (1, 50, 3)
+    assert len(flat) > 0
+    _assert_same_points(organized, flat)
+    assert numpy.array_equal(laser_line.get_points(target, None, iso, threads=1), flat)
+    assert numpy.allclose(numpy.linalg.norm(flat, axis=1), 0.8, atol=0.01)
+
+
+def test_panning_laser_line_organized_matches_flat(laser_line):
+    target = Mesh.create_sphere(0.8, 20, 20)
+    sensor = PanningLaserLine(laser_line, Vector3(0.0, 0.05, 0.0), 30)
+    iso = Iso3.from_translation(0.0, -0.75, 0.0) @ Iso3.from_rz(0.1)
+
+    flat = sensor.get_points(target, None, iso)
+    organized = sensor.get_points(target, None, iso, organized=True)
+
+    assert organized.shape == (30, 50, 3)
+    assert len(flat) > 0
+    _assert_same_points(organized, flat)
+
+
+def test_panning_laser_line_rows_are_steps(laser_line):
+    # Panning along the laser line keeps the rays in one plane, so the points alone can't tell which step cast them
+    target = Mesh.create_sphere(0.8, 20, 20)
+    sensor = PanningLaserLine(laser_line, Vector3(0.02, 0.0, 0.0), 5)
+    iso = Iso3.from_translation(0.1, 0.05, 0.0)
+
+    organized = sensor.get_points(target, None, iso, organized=True)
+
+    assert organized.shape == (5, 50, 3)
+    for i in range(5):
+        step = iso @ Iso3.from_translation(0.02 * i, 0.0, 0.0)
+        row = laser_line.get_points(target, None, step, organized=True)
+        assert numpy.allclose(organized[i], row[0], equal_nan=True)
+        assert numpy.isnan(organized[i]).any(axis=1).sum() < 50
@@ -0,0 +1,74 @@
+"""
+    Tests of the binary serialization and pickling of geometry classes.
+"""
+import pickle
+import pytest
+import numpy
+from engeom.geom2 import Point2, Iso2, Circle2, Curve2
+from engeom.geom3 import Point3, Vector3, Iso3, Plane3, Mesh, Curve3, SvdBasis3
+from engeom.metrology import Distance3
+
+
+def test_point3_round_trip():
+    p = Point3(1, 2, 3)
+    loaded = Point3.from_bytes(p.to_bytes())
+    assert loaded.x == 1 and loaded.y == 2 and loaded.z == 3
+
+
+def test_iso3_pickle():
+    iso = Iso3.from_rotation(0.5, 0, 0, 1) @ Iso3.from_translation(1, 2, 3)
+    loaded = pickle.loads(pickle.dumps(iso))
+    assert numpy.allclose(loaded.as_numpy(), iso.as_numpy())
+
+
+def test_geom2_pickle():
+    iso = pickle.loads(pickle.dumps(Iso2(1, 2, 0.25)))
+    p = iso @ Point2(1, 0)
+    expected = Iso2(1, 2, 0.25) @ Point2(1, 0)
+    assert abs(p.x - expected.x) < 1e-12 and abs(p.y - expected.y) < 1e-12
+
+    circle = pickle.loads(pickle.dumps(Circle2(1, 2, 3)))
+    assert circle.r == 3
+
+    curve = Curve2(numpy.array([[0, 0], [1, 0], [1, 1]], dtype=numpy.float64))
+    loaded = pickle.loads(pickle.dumps(curve))
+    assert numpy.allclose(loaded.points, curve.points)
+
+
+def test_mesh_pickle_keeps_attributes():
+    vertices = numpy.array([[0, 0, 0], [1, 0, 0], [1, 1, 0]], dtype=numpy.float64)
+    faces = numpy.array([[0, 1, 2]], dtype=numpy.uint32)
+    mesh = Mesh(vertices, faces)
+    mesh.set_scalar_field("quality", numpy.array([1.0, 2.0, 3.0]))
+
+    loaded = pickle.loads(pickle.dumps(mesh))
+    assert numpy.allclose(loaded.vertices, mesh.vertices)
+    assert numpy.array_equal(loaded.faces, mesh.faces)
+    assert numpy.allclose(loaded.get_scalar_field("quality"), [1.0, 2.0, 3.0])
+
+
+def test_other_geom3_pickle():
+    plane = pickle.loads(pickle.dumps(Plane3(0, 0, 1, 2)))
+    assert abs(plane.signed_distance_to_point(Point3(0, 0, 5)) - 3) < 1e-12
+
+    curve = Curve3(numpy.array([[0, 0, 0], [1, 0, 0], [1, 1, 1]], dtype=numpy.float64))
+    assert numpy.allclose(pickle.loads(pickle.dumps(curve)).points, curve.points)
+
+    points = numpy.random.default_rng(0).normal(size=(50, 3))
+    basis = SvdBasis3(points)
+    loaded = pickle.loads(pickle.dumps(basis))
+    assert numpy.allclose(loaded.basis_stdevs(), basis.basis_stdevs())
+
+    d = pickle.loads(pickle.dumps(Distance3(Point3(0, 0, 0), Point3(0, 0, 2))))
+    assert abs(d.value - 2) < 1e-12
+
+
+def test_wrong_class_raises():
+    data = Vector3(1, 2, 3).to_bytes()
+    with pytest.raises(ValueError):
+        Point3.from_bytes(data)
+
+
+def test_not_a_payload_raises():
+    with pytest.raises(ValueError):
+        Mesh.from_bytes(b"not a payload")
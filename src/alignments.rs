@@ -1,29 +1,706 @@
+mod coarse;
+mod kabsch;
+mod lm;
+mod robust;
+mod solver;
+mod solver2;
+
 use crate::common::DeviationMode;
-use crate::conversions::array_to_points3;
-use crate::geom3::Iso3;
+use crate::conversions::{array_to_points2, array_to_points3};
+use crate::geom2::{Curve2, Iso2};
+use crate::geom3::{Iso3, SurfacePoint3};
+use crate::kdtree::KdTree3;
 use crate::mesh::Mesh;
-use numpy::PyReadonlyArrayDyn;
+use crate::parallel;
+use engeom::{Point3, Vector3};
+use numpy::ndarray::{Array1, Array2};
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyReadonlyArray1, PyReadonlyArrayDyn};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use robust::{Loss, Robust};
+use solver::{Fixed, PointsToTarget, Solution, SolverOptions, Symmetric};
+use solver2::SolverOptions2;
+
+/// The degrees of freedom a 2D alignment may use, expressed in the alignment's reference frame.
+#[pyclass]
+#[derive(Clone, Copy)]
+pub struct Dof2 {
+    free: [bool; 3],
+}
+
+#[pymethods]
+impl Dof2 {
+    #[new]
+    #[pyo3(signature=(tx = true, ty = true, r = true))]
+    fn new(tx: bool, ty: bool, r: bool) -> Self {
+        Self { free: [tx, ty, r] }
+    }
+
+    fn __repr__(&self) -> String {
+        let [tx, ty, r] = self.free.map(|f| if f { "True" } else { "False" });
+        format!("<Dof2 tx={tx} ty={ty} r={r}>")
+    }
+
+    #[getter]
+    fn tx(&self) -> bool {
+        self.free[0]
+    }
+
+    #[getter]
+    fn ty(&self) -> bool {
+        self.free[1]
+    }
+
+    #[getter]
+    fn r(&self) -> bool {
+        self.free[2]
+    }
+}
+
+#[derive(FromPyObject)]
+pub enum CurveOrPoints2<'py> {
+    Curve(PyRef<'py, Curve2>),
+    Points(PyReadonlyArrayDyn<'py, f64>),
+}
+
+/// A robust loss function, which limits the influence of correspondences with large residuals on
+/// an alignment.
+#[pyclass]
+#[derive(Copy, Clone, Debug)]
+pub enum RobustLoss {
+    Huber(f64),
+    Tukey(f64),
+    Cauchy(f64),
+    Trimmed(f64),
+}
+
+#[pymethods]
+impl RobustLoss {
+    fn __repr__(&self) -> String {
+        match self {
+            RobustLoss::Huber(k) => format!("RobustLoss.Huber({})", k),
+            RobustLoss::Tukey(c) => format!("RobustLoss.Tukey({})", c),
+            RobustLoss::Cauchy(c) => format!("RobustLoss.Cauchy({})", c),
+            RobustLoss::Trimmed(fraction) => format!("RobustLoss.Trimmed({})", fraction),
+        }
+    }
+}
+
+impl From<RobustLoss> for Loss {
+    fn from(val: RobustLoss) -> Self {
+        match val {
+            RobustLoss::Huber(k) => Loss::Huber(k),
+            RobustLoss::Tukey(c) => Loss::Tukey(c),
+            RobustLoss::Cauchy(c) => Loss::Cauchy(c),
+            RobustLoss::Trimmed(fraction) => Loss::Trimmed(fraction),
+        }
+    }
+}
+
+/// The degrees of freedom an alignment may use, expressed in the alignment's reference frame.
+#[pyclass]
+#[derive(Clone, Copy)]
+pub struct Dof3 {
+    free: [bool; 6],
+}
+
+#[pymethods]
+impl Dof3 {
+    #[new]
+    #[pyo3(signature=(tx = true, ty = true, tz = true, rx = true, ry = true, rz = true))]
+    fn new(tx: bool, ty: bool, tz: bool, rx: bool, ry: bool, rz: bool) -> Self {
+        Self {
+            free: [tx, ty, tz, rx, ry, rz],
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let [tx, ty, tz, rx, ry, rz] = self.free.map(|f| if f { "True" } else { "False" });
+        format!("<Dof3 tx={tx} ty={ty} tz={tz} rx={rx} ry={ry} rz={rz}>")
+    }
+
+    #[getter]
+    fn tx(&self) -> bool {
+        self.free[0]
+    }
+
+    #[getter]
+    fn ty(&self) -> bool {
+        self.free[1]
+    }
+
+    #[getter]
+    fn tz(&self) -> bool {
+        self.free[2]
+    }
+
+    #[getter]
+    fn rx(&self) -> bool {
+        self.free[3]
+    }
+
+    #[getter]
+    fn ry(&self) -> bool {
+        self.free[4]
+    }
+
+    #[getter]
+    fn rz(&self) -> bool {
+        self.free[5]
+    }
+}
+
+/// The outcome of an alignment, with the information needed to judge whether it can be trusted.
+#[pyclass]
+pub struct AlignmentResult {
+    transform: engeom::Iso3,
+    iterations: usize,
+    converged: bool,
+    rms: f64,
+    max_residual: f64,
+    residuals: Vec<f64>,
+    weights: Vec<f64>,
+    covariance: [[f64; 6]; 6],
+}
+
+impl AlignmentResult {
+    fn from_solution(solution: Solution) -> Self {
+        let c = &solution.covariance;
+        Self {
+            transform: solution.transform,
+            iterations: solution.iterations,
+            converged: solution.converged,
+            rms: solution.rms(),
+            max_residual: solution.max_residual(),
+            covariance: std::array::from_fn(|i| std::array::from_fn(|j| c[(i, j)])),
+            residuals: solution.residuals,
+            weights: solution.weights,
+        }
+    }
+
+    /// Combine the residuals and weights of consecutive rows into one for each point, where point
+    /// `i` had `rows[i]` rows. A point's residual is the norm of the residuals of its rows, and its
+    /// weight is the mean of their weights.
+    fn per_point(self, rows: &[usize]) -> Self {
+        let mut residuals = Vec::with_capacity(rows.len());
+        let mut weights = Vec::with_capacity(rows.len());
+        let mut start = 0;
+        for &n in rows {
+            let (r, w) = (
+                &self.residuals[start..start + n],
+                &self.weights[start..start + n],
+            );
+            residuals.push(r.iter().map(|v| v * v).sum::<f64>().sqrt());
+            weights.push(w.iter().sum::<f64>() / n as f64);
+            start += n;
+        }
+
+        Self {
+            rms: (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt(),
+            max_residual: residuals.iter().fold(0.0, |m, r| r.max(m)),
+            residuals,
+            weights,
+            ..self
+        }
+    }
+}
+
+#[pymethods]
+impl AlignmentResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "<AlignmentResult converged={} iterations={} rms={} max_residual={}>",
+            self.converged, self.iterations, self.rms, self.max_residual
+        )
+    }
+
+    #[getter]
+    fn transform(&self) -> Iso3 {
+        Iso3::from_inner(self.transform)
+    }
+
+    #[getter]
+    fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    #[getter]
+    fn converged(&self) -> bool {
+        self.converged
+    }
+
+    #[getter]
+    fn rms(&self) -> f64 {
+        self.rms
+    }
+
+    #[getter]
+    fn max_residual(&self) -> f64 {
+        self.max_residual
+    }
+
+    #[getter]
+    fn residuals<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.residuals.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn weights<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.weights.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        Array2::from_shape_fn((6, 6), |(i, j)| self.covariance[i][j]).into_pyarray(py)
+    }
+}
+
+/// Solver options centered on the moving points, with the caller's overrides applied.
+fn solver_options(
+    points: &[Point3],
+    initial: &engeom::Iso3,
+    mode: DeviationMode,
+    max_iterations: usize,
+    tolerance: f64,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
+) -> SolverOptions {
+    let mut options = SolverOptions::centered(points, initial, mode);
+    options.max_iterations = max_iterations;
+    options.tolerance = tolerance;
+    if let Some(dof) = dof {
+        options.free = dof.free;
+    }
+    if let Some(frame) = frame {
+        options.frame = *frame.get_inner();
+    }
+    options
+}
+
+#[pyfunction]
+#[pyo3(signature=(points, mesh, initial, mode, max_iterations = 100, tolerance = 1e-10, threads = None, dof = None, frame = None, loss = None, max_distance = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn fit_points_to_mesh(
+    py: Python<'_>,
+    points: PyReadonlyArrayDyn<'_, f64>,
+    mesh: &Mesh,
+    initial: &Iso3,
+    mode: DeviationMode,
+    max_iterations: usize,
+    tolerance: f64,
+    threads: Option<usize>,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
+    loss: Option<RobustLoss>,
+    max_distance: Option<f64>,
+) -> PyResult<AlignmentResult> {
+    let points = array_to_points3(&points.as_array())?;
+    let initial = initial.get_inner();
+    let mut options = solver_options(
+        &points,
+        initial,
+        mode,
+        max_iterations,
+        tolerance,
+        dof,
+        frame,
+    );
+    options.robust = Robust {
+        loss: loss.map_or(Loss::Squared, Loss::from),
+        max_distance,
+    };
+
+    let pairs = PointsToTarget {
+        points: &points,
+        target: mesh.get_inner(),
+    };
+    let solution = parallel::run(py, threads, || solver::solve(&pairs, initial, &options))?
+        .map_err(PyValueError::new_err)?;
+
+    Ok(AlignmentResult::from_solution(solution))
+}
 
 #[pyfunction]
+#[pyo3(signature=(points, mesh, initial, mode, dof = None, frame = None, loss = None, max_distance = None))]
+#[allow(clippy::too_many_arguments)]
 pub fn points_to_mesh(
+    py: Python<'_>,
+    points: PyReadonlyArrayDyn<'_, f64>,
+    mesh: &Mesh,
+    initial: &Iso3,
+    mode: DeviationMode,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
+    loss: Option<RobustLoss>,
+    max_distance: Option<f64>,
+) -> PyResult<Iso3> {
+    // engeom's solver does the alignment unless one of the options only the in-house solver has
+    // is asked for
+    if dof.is_none() && frame.is_none() && loss.is_none() && max_distance.is_none() {
+        let points = array_to_points3(&points.as_array())?;
+        let (target, initial) = (mesh.get_inner(), initial.get_inner());
+        let transform = py
+            .allow_threads(|| {
+                engeom::geom3::align3::points_to_mesh(&points, target, initial, mode.into())
+                    .map(|align| *align.transform())
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(PyValueError::new_err)?;
+        return Ok(Iso3::from_inner(transform));
+    }
+
+    let result = fit_points_to_mesh(
+        py,
+        points,
+        mesh,
+        initial,
+        mode,
+        100,
+        1e-10,
+        None,
+        dof,
+        frame,
+        loss,
+        max_distance,
+    )?;
+    Ok(Iso3::from_inner(result.transform))
+}
+
+/// Expand each measured point into one pair per active axis, where the target side of the pair
+/// is the nominal point with the axis as its normal.
+fn rps_pairs(
+    measured: &[Point3],
+    references: &[SurfacePoint3],
+    axes: &[String],
+) -> PyResult<Vec<(Point3, engeom::SurfacePoint3)>> {
+    if measured.len() != references.len() || measured.len() != axes.len() {
+        return Err(PyValueError::new_err(format!(
+            "There are {} measured points, {} references and {} axis specifications, but they \
+             must all be the same length",
+            measured.len(),
+            references.len(),
+            axes.len()
+        )));
+    }
+
+    let mut pairs = Vec::new();
+    for ((m, r), active) in measured.iter().zip(references).zip(axes) {
+        let r = r.get_inner();
+        if active.is_empty() {
+            return Err(PyValueError::new_err(
+                "Every point must have at least one active axis",
+            ));
+        }
+        for c in active.chars() {
+            let direction = match c.to_ascii_lowercase() {
+                'x' => Vector3::x_axis(),
+                'y' => Vector3::y_axis(),
+                'z' => Vector3::z_axis(),
+                'n' => r.normal,
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown axis '{c}', axes must be 'x', 'y', 'z' or 'n'"
+                    )));
+                }
+            };
+            pairs.push((*m, engeom::SurfacePoint3::new(r.point, direction)));
+        }
+    }
+    Ok(pairs)
+}
+
+#[pyfunction]
+#[pyo3(signature=(measured, references, axes, initial, max_iterations = 100, tolerance = 1e-10, dof = None, frame = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn points_to_rps(
+    measured: PyReadonlyArrayDyn<'_, f64>,
+    references: Vec<SurfacePoint3>,
+    axes: Vec<String>,
+    initial: &Iso3,
+    max_iterations: usize,
+    tolerance: f64,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
+) -> PyResult<AlignmentResult> {
+    let measured = array_to_points3(&measured.as_array())?;
+    let pairs = rps_pairs(&measured, &references, &axes)?;
+
+    let initial = initial.get_inner();
+    let options = solver_options(
+        &measured,
+        initial,
+        DeviationMode::Plane,
+        max_iterations,
+        tolerance,
+        dof,
+        frame,
+    );
+
+    // There are only a handful of pairs, so this isn't worth handing off to worker threads
+    let fixed = Fixed { pairs: &pairs };
+    let solution = solver::solve(&fixed, initial, &options).map_err(PyValueError::new_err)?;
+
+    let rows = axes.iter().map(|a| a.chars().count()).collect::<Vec<_>>();
+    Ok(AlignmentResult::from_solution(solution).per_point(&rows))
+}
+
+#[pyfunction]
+#[pyo3(signature=(points, mesh, all_axes = false, threads = None))]
+pub fn coarse_points_to_mesh(
+    py: Python<'_>,
     points: PyReadonlyArrayDyn<'_, f64>,
     mesh: &Mesh,
+    all_axes: bool,
+    threads: Option<usize>,
+) -> PyResult<Vec<Iso3>> {
+    let points = array_to_points3(&points.as_array())?;
+    if points.is_empty() {
+        return Err(PyValueError::new_err("No points were provided to align"));
+    }
+
+    let target = mesh.get_inner();
+    let candidates = parallel::run(py, threads, || {
+        let frame = coarse::mesh_principal_frame(target);
+        coarse::candidates(&points, target, &frame, all_axes)
+    })?;
+
+    Ok(candidates
+        .into_iter()
+        .map(|(iso, _)| Iso3::from_inner(iso))
+        .collect())
+}
+
+#[pyfunction]
+#[pyo3(signature=(points, mesh, mode, all_axes = false, max_iterations = 100, tolerance = 1e-10, threads = None, loss = None, max_distance = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn global_points_to_mesh(
+    py: Python<'_>,
+    points: PyReadonlyArrayDyn<'_, f64>,
+    mesh: &Mesh,
+    mode: DeviationMode,
+    all_axes: bool,
+    max_iterations: usize,
+    tolerance: f64,
+    threads: Option<usize>,
+    loss: Option<RobustLoss>,
+    max_distance: Option<f64>,
+) -> PyResult<AlignmentResult> {
+    let points = array_to_points3(&points.as_array())?;
+    if points.is_empty() {
+        return Err(PyValueError::new_err("No points were provided to align"));
+    }
+
+    let robust = Robust {
+        loss: loss.map_or(Loss::Squared, Loss::from),
+        max_distance,
+    };
+    robust.validate().map_err(PyValueError::new_err)?;
+
+    let target = mesh.get_inner();
+    let best = parallel::run(py, threads, || {
+        let frame = coarse::mesh_principal_frame(target);
+        let pairs = PointsToTarget {
+            points: &points,
+            target,
+        };
+
+        // Every candidate is refined, since the best coarse fit is not always the one which
+        // converges to the best final fit
+        let mut best: Option<Solution> = None;
+        for (initial, _) in coarse::candidates(&points, target, &frame, all_axes) {
+            let mut options = solver_options(
+                &points,
+                &initial,
+                mode,
+                max_iterations,
+                tolerance,
+                None,
+                None,
+            );
+            options.robust = robust;
+
+            // A candidate which is far enough off to fail is simply not a contender
+            let Ok(solution) = solver::solve(&pairs, &initial, &options) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|b| solution.cost < b.cost) {
+                best = Some(solution);
+            }
+        }
+        best.ok_or_else(|| "No candidate alignments were found".to_string())
+    })?
+    .map_err(PyValueError::new_err)?;
+
+    Ok(AlignmentResult::from_solution(best))
+}
+
+#[pyfunction]
+#[pyo3(signature=(moving, target, initial, mode, spacing, max_iterations = 100, tolerance = 1e-10, threads = None, dof = None, frame = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn mesh_to_mesh(
+    py: Python<'_>,
+    moving: &Mesh,
+    target: &Mesh,
     initial: &Iso3,
     mode: DeviationMode,
+    spacing: f64,
+    max_iterations: usize,
+    tolerance: f64,
+    threads: Option<usize>,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
 ) -> PyResult<Iso3> {
+    if spacing <= 0.0 {
+        return Err(PyValueError::new_err("spacing must be greater than zero"));
+    }
+
+    let initial = initial.get_inner();
+    let moving = moving.get_inner();
+    let target = target.get_inner();
+
+    let solution = parallel::run(py, threads, || {
+        let moving_samples = moving
+            .sample_poisson(spacing)
+            .iter()
+            .map(|s| s.point)
+            .collect::<Vec<_>>();
+        let target_samples = target.sample_poisson(spacing);
+
+        let options = solver_options(
+            &moving_samples,
+            initial,
+            mode,
+            max_iterations,
+            tolerance,
+            dof,
+            frame,
+        );
+        let pairs = Symmetric {
+            moving,
+            moving_samples: &moving_samples,
+            target,
+            target_samples: &target_samples,
+        };
+        solver::solve(&pairs, initial, &options)
+    })?
+    .map_err(PyValueError::new_err)?;
+
+    Ok(Iso3::from_inner(solution.transform))
+}
+
+#[pyfunction]
+#[pyo3(signature=(points, targets, weights = None))]
+pub fn points_to_points<'py>(
+    points: PyReadonlyArrayDyn<'py, f64>,
+    targets: PyReadonlyArrayDyn<'py, f64>,
+    weights: Option<PyReadonlyArray1<'py, f64>>,
+) -> PyResult<Iso3> {
+    let points = array_to_points3(&points.as_array())?;
+    let targets = array_to_points3(&targets.as_array())?;
+    let weights = weights.map(|w| w.as_array().to_vec());
+
+    let (iso, _) =
+        kabsch::fit(&points, &targets, weights.as_deref(), false).map_err(PyValueError::new_err)?;
+    Ok(Iso3::from_inner(iso))
+}
+
+#[pyfunction]
+#[pyo3(signature=(points, targets, weights = None))]
+pub fn points_to_points_scaled<'py>(
+    points: PyReadonlyArrayDyn<'py, f64>,
+    targets: PyReadonlyArrayDyn<'py, f64>,
+    weights: Option<PyReadonlyArray1<'py, f64>>,
+) -> PyResult<(Iso3, f64)> {
     let points = array_to_points3(&points.as_array())?;
+    let targets = array_to_points3(&targets.as_array())?;
+    let weights = weights.map(|w| w.as_array().to_vec());
+
+    let (iso, scale) =
+        kabsch::fit(&points, &targets, weights.as_deref(), true).map_err(PyValueError::new_err)?;
+    Ok((Iso3::from_inner(iso), scale))
+}
 
-    let result = engeom::geom3::align3::points_to_mesh(
+#[pyfunction]
+#[pyo3(signature=(points, targets, initial, max_iterations = 100, tolerance = 1e-10, threads = None, dof = None, frame = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn points_to_points_icp(
+    py: Python<'_>,
+    points: PyReadonlyArrayDyn<'_, f64>,
+    targets: PyReadonlyArrayDyn<'_, f64>,
+    initial: &Iso3,
+    max_iterations: usize,
+    tolerance: f64,
+    threads: Option<usize>,
+    dof: Option<Dof3>,
+    frame: Option<Iso3>,
+) -> PyResult<Iso3> {
+    let points = array_to_points3(&points.as_array())?;
+    let targets = array_to_points3(&targets.as_array())?;
+    let initial = initial.get_inner();
+    let options = solver_options(
         &points,
-        mesh.get_inner(),
-        initial.get_inner(),
-        mode.into(),
+        initial,
+        DeviationMode::Point,
+        max_iterations,
+        tolerance,
+        dof,
+        frame,
     );
 
-    match result {
-        Ok(align) => Ok(Iso3::from_inner(*align.transform())),
-        Err(e) => Err(PyValueError::new_err(e.to_string())),
+    let solution = parallel::run(py, threads, || {
+        let tree = KdTree3::new(&targets);
+        let pairs = PointsToTarget {
+            points: &points,
+            target: &tree,
+        };
+        solver::solve(&pairs, initial, &options)
+    })?
+    .map_err(PyValueError::new_err)?;
+
+    Ok(Iso3::from_inner(solution.transform))
+}
+
+#[pyfunction]
+#[pyo3(signature=(moving, target, initial, mode, max_iterations = 100, tolerance = 1e-10, dof = None, frame = None, loss = None, max_distance = None))]
+#[allow(clippy::too_many_arguments)]
+pub fn curve_to_curve(
+    py: Python<'_>,
+    moving: CurveOrPoints2<'_>,
+    target: &Curve2,
+    initial: &Iso2,
+    mode: DeviationMode,
+    max_iterations: usize,
+    tolerance: f64,
+    dof: Option<Dof2>,
+    frame: Option<Iso2>,
+    loss: Option<RobustLoss>,
+    max_distance: Option<f64>,
+) -> PyResult<Iso2> {
+    let points = match moving {
+        CurveOrPoints2::Curve(curve) => curve.get_inner().points().to_vec(),
+        CurveOrPoints2::Points(array) => array_to_points2(&array.as_array())?,
+    };
+    let initial = initial.get_inner();
+
+    let mut options = SolverOptions2::centered(&points, initial, mode);
+    options.max_iterations = max_iterations;
+    options.tolerance = tolerance;
+    options.robust = Robust {
+        loss: loss.map_or(Loss::Squared, Loss::from),
+        max_distance,
+    };
+    if let Some(dof) = dof {
+        options.free = dof.free;
+    }
+    if let Some(frame) = frame {
+        options.frame = *frame.get_inner();
     }
+
+    let target = target.get_inner();
+    let transform = parallel::run(py, None, || {
+        solver2::solve(&points, target, initial, &options)
+    })?
+    .map_err(PyValueError::new_err)?;
+
+    Ok(Iso2::from_inner(transform))
 }
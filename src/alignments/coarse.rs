@@ -0,0 +1,139 @@
+//! Coarse registration for when there is no initial guess at all, done by matching the principal
+//! axes of the moving points with those of the target. The principal axes only define the frame
+//! up to the sign of each axis (and their order, if two of the variances are similar), so every
+//! proper rotation which maps axes onto axes is tried and the candidates are ranked by how well
+//! they fit.
+
+use super::solver::Target;
+use engeom::{Iso3, Point3, SvdBasis3, Vector3};
+use parry3d_f64::na::{Matrix3, Rotation3, Translation3, UnitQuaternion};
+use rayon::prelude::*;
+
+/// The most points used to score each candidate
+const SCORE_POINTS: usize = 1000;
+
+/// A frame at the weighted centroid of the points, with its x axis along the direction of largest
+/// variance and its z axis along the direction of smallest.
+pub fn principal_frame(points: &[Point3], weights: Option<&[f64]>) -> Iso3 {
+    let weight = |i: usize| weights.map_or(1.0, |w| w[i]);
+    let total = (0..points.len())
+        .map(weight)
+        .sum::<f64>()
+        .max(f64::MIN_POSITIVE);
+    let center = points
+        .iter()
+        .enumerate()
+        .fold(Vector3::zeros(), |acc, (i, p)| acc + p.coords * weight(i))
+        / total;
+
+    let basis = SvdBasis3::from_points(points, weights);
+    let x = basis.largest().normalize();
+    let y = basis.smallest().cross(&x).normalize();
+    let z = x.cross(&y);
+
+    Iso3::from_parts(
+        Translation3::from(center),
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(
+            Matrix3::from_columns(&[x, y, z]),
+        )),
+    )
+}
+
+/// The principal frame of the surface of a mesh, found from the area weighted centroids of its
+/// faces.
+pub fn mesh_principal_frame(mesh: &engeom::Mesh) -> Iso3 {
+    let vertices = mesh.vertices();
+    let (centers, areas): (Vec<_>, Vec<_>) = mesh
+        .faces()
+        .iter()
+        .map(|f| {
+            let [a, b, c] = f.map(|i| vertices[i as usize]);
+            let center = Point3::from((a.coords + b.coords + c.coords) / 3.0);
+            (center, (b - a).cross(&(c - a)).norm() * 0.5)
+        })
+        .unzip();
+
+    principal_frame(&centers, Some(&areas))
+}
+
+/// The proper rotations which map each axis onto an axis, either only the four which keep every
+/// axis on its own line, or all twenty-four.
+fn axis_rotations(all_axes: bool) -> Vec<Matrix3<f64>> {
+    let orders: &[[usize; 3]] = if all_axes {
+        &[
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ]
+    } else {
+        &[[0, 1, 2]]
+    };
+
+    let mut rotations = Vec::new();
+    for order in orders {
+        for signs in 0..8 {
+            let m = Matrix3::from_fn(|i, j| {
+                if order[j] != i {
+                    0.0
+                } else if signs & (1 << j) == 0 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            });
+            if m.determinant() > 0.0 {
+                rotations.push(m);
+            }
+        }
+    }
+    rotations
+}
+
+/// Candidate transforms which move the points onto the target by matching the moving frame with
+/// the target frame, ordered from the best fitting to the worst.
+pub fn candidates(
+    points: &[Point3],
+    target: &dyn Target,
+    target_frame: &Iso3,
+    all_axes: bool,
+) -> Vec<(Iso3, f64)> {
+    let moving_frame = principal_frame(points, None);
+    let stride = points.len().div_ceil(SCORE_POINTS).max(1);
+    let sample = points.iter().step_by(stride).collect::<Vec<_>>();
+
+    let mut scored = axis_rotations(all_axes)
+        .into_iter()
+        .map(|m| {
+            let flip = Iso3::from_parts(
+                Translation3::identity(),
+                UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(m)),
+            );
+            let candidate = target_frame * flip * moving_frame.inverse();
+            let score = rms_distance(&sample, target, &candidate);
+            (candidate, score)
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    scored
+}
+
+/// The root mean square distance from the transformed points to the target.
+fn rms_distance(points: &[&Point3], target: &dyn Target, transform: &Iso3) -> f64 {
+    let distances = points
+        .par_iter()
+        .filter_map(|p| {
+            let q = transform * *p;
+            target.closest(&q).map(|c| (q - c.point).norm_squared())
+        })
+        .collect::<Vec<_>>();
+
+    if distances.is_empty() {
+        f64::INFINITY
+    } else {
+        (distances.iter().sum::<f64>() / distances.len() as f64).sqrt()
+    }
+}
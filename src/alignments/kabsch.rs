@@ -0,0 +1,91 @@
+//! Closed form rigid and similarity transforms between sets of corresponding points, following
+//! Umeyama's method ("Least-squares estimation of transformation parameters between two point
+//! patterns", 1991) with per-point weights.
+
+use engeom::{Iso3, Point3, Vector3};
+use parry3d_f64::na::{Matrix3, Rotation3, Translation3, UnitQuaternion};
+
+/// The transform `target ≈ iso * (scale * point)` which minimizes the weighted sum of squared
+/// distances between corresponding points. If `with_scale` is false the scale is fixed at 1.
+pub fn fit(
+    points: &[Point3],
+    targets: &[Point3],
+    weights: Option<&[f64]>,
+    with_scale: bool,
+) -> Result<(Iso3, f64), String> {
+    if points.len() != targets.len() {
+        return Err(format!(
+            "There are {} points but {} targets, they must correspond one to one",
+            points.len(),
+            targets.len()
+        ));
+    }
+    if points.len() < 3 {
+        return Err("At least three corresponding points are needed".to_string());
+    }
+    if let Some(w) = weights {
+        if w.len() != points.len() {
+            return Err(format!(
+                "There are {} weights for {} points",
+                w.len(),
+                points.len()
+            ));
+        }
+        if w.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err("Weights must be finite and non-negative".to_string());
+        }
+    }
+
+    let weight = |i: usize| weights.map_or(1.0, |w| w[i]);
+    let total: f64 = (0..points.len()).map(weight).sum();
+    if total <= 0.0 {
+        return Err("The weights must not all be zero".to_string());
+    }
+
+    let mut mean_p = Vector3::zeros();
+    let mut mean_q = Vector3::zeros();
+    for (i, (p, q)) in points.iter().zip(targets).enumerate() {
+        mean_p += p.coords * weight(i);
+        mean_q += q.coords * weight(i);
+    }
+    mean_p /= total;
+    mean_q /= total;
+
+    let mut cross = Matrix3::zeros();
+    let mut spread = 0.0;
+    for (i, (p, q)) in points.iter().zip(targets).enumerate() {
+        let dp = p.coords - mean_p;
+        let dq = q.coords - mean_q;
+        cross += (dq * dp.transpose()) * weight(i);
+        spread += dp.norm_squared() * weight(i);
+    }
+
+    let svd = cross.svd(true, true);
+    let (u, v_t) = match (svd.u, svd.v_t) {
+        (Some(u), Some(v_t)) => (u, v_t),
+        _ => return Err("Failed to decompose the point covariance".to_string()),
+    };
+
+    // If the best orthogonal matrix is a reflection, the direction of least agreement is flipped
+    let mut signs = Vector3::repeat(1.0);
+    if (u * v_t).determinant() < 0.0 {
+        signs[svd.singular_values.imin()] = -1.0;
+    }
+
+    let rotation = Rotation3::from_matrix_unchecked(u * Matrix3::from_diagonal(&signs) * v_t);
+    let scale = if with_scale {
+        if spread <= 0.0 {
+            return Err("The points are all coincident, so the scale is undefined".to_string());
+        }
+        svd.singular_values.dot(&signs) / spread
+    } else {
+        1.0
+    };
+
+    let translation = mean_q - rotation * mean_p * scale;
+    let iso = Iso3::from_parts(
+        Translation3::from(translation),
+        UnitQuaternion::from_rotation_matrix(&rotation),
+    );
+    Ok((iso, scale))
+}
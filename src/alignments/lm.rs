@@ -0,0 +1,144 @@
+//! The Levenberg-Marquardt iteration shared by the 2D and 3D alignment solvers. It is generic over
+//! the number of transform parameters `N`, and only needs each solver to linearize its residuals
+//! into the normal equations at a given parameter vector.
+
+use parry3d_f64::na::{SMatrix, SVector};
+
+/// The weighted normal equations `H = Σ w JᵀJ` and `g = Σ w Jᵀr` of the residuals at one
+/// parameter vector, along with the total cost and the number of residual rows with weight.
+pub struct Normal<const N: usize> {
+    pub h: SMatrix<f64, N, N>,
+    pub g: SVector<f64, N>,
+    pub cost: f64,
+    pub rows: usize,
+}
+
+impl<const N: usize> Normal<N> {
+    pub fn zeros() -> Self {
+        Self {
+            h: SMatrix::zeros(),
+            g: SVector::zeros(),
+            cost: 0.0,
+            rows: 0,
+        }
+    }
+
+    /// Add a single residual row with its jacobian and weight.
+    pub fn add(&mut self, jacobian: &SVector<f64, N>, value: f64, weight: f64) {
+        self.h += jacobian * jacobian.transpose() * weight;
+        self.g += jacobian * value * weight;
+    }
+}
+
+/// The result of evaluating a solver's model, which may carry more than the normal equations.
+pub trait Evaluated<const N: usize> {
+    fn normal(&self) -> &Normal<N>;
+}
+
+impl<const N: usize> Evaluated<N> for Normal<N> {
+    fn normal(&self) -> &Normal<N> {
+        self
+    }
+}
+
+pub struct Minimum<const N: usize, E> {
+    pub x: SVector<f64, N>,
+
+    /// The evaluation of the model at `x`
+    pub evaluation: E,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Minimize the cost of a model from a parameter vector of zeros, changing only the parameters
+/// marked as free.
+pub fn minimize<const N: usize, E: Evaluated<N>>(
+    evaluate: impl Fn(&SVector<f64, N>) -> E,
+    free: &[bool; N],
+    max_iterations: usize,
+    tolerance: f64,
+) -> Result<Minimum<N, E>, String> {
+    let n_free = free.iter().filter(|f| **f).count();
+    if n_free == 0 {
+        return Err("At least one degree of freedom must be free to align".to_string());
+    }
+
+    let mut x = SVector::zeros();
+    let mut current = evaluate(&x);
+    if current.normal().rows < n_free {
+        return Err("Too few points have a correspondence to solve the alignment".to_string());
+    }
+
+    let mut lambda = 1e-3;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < max_iterations {
+        iterations += 1;
+
+        let Some(step) = damped_step(current.normal(), lambda, free) else {
+            break;
+        };
+
+        let trial_x = x + step;
+        let trial = evaluate(&trial_x);
+        let (cost, trial_cost) = (current.normal().cost, trial.normal().cost);
+
+        if trial.normal().rows >= n_free && trial_cost <= cost {
+            x = trial_x;
+            current = trial;
+            lambda = (lambda * 0.1).max(1e-12);
+
+            if cost - trial_cost <= tolerance * trial_cost.max(f64::MIN_POSITIVE)
+                || step.norm() <= tolerance
+            {
+                converged = true;
+                break;
+            }
+        } else {
+            lambda *= 10.0;
+            if lambda > 1e12 {
+                // No step in any direction reduces the cost, so this is a minimum
+                converged = true;
+                break;
+            }
+        }
+    }
+
+    Ok(Minimum {
+        x,
+        evaluation: current,
+        iterations,
+        converged,
+    })
+}
+
+/// Solve the damped normal equations `(H + λ diag(H)) δ = -g` for the parameter step. Locked
+/// parameters are decoupled from the system so that their step is always zero.
+fn damped_step<const N: usize>(
+    normal: &Normal<N>,
+    lambda: f64,
+    free: &[bool; N],
+) -> Option<SVector<f64, N>> {
+    let (mut a, g) = masked(normal, free);
+    for i in 0..N {
+        a[(i, i)] += lambda * a[(i, i)].max(1e-12);
+    }
+
+    a.cholesky().map(|c| -c.solve(&g))
+}
+
+/// Remove the locked parameters from the normal equations by replacing their rows and columns
+/// with those of the identity.
+pub fn masked<const N: usize>(
+    normal: &Normal<N>,
+    free: &[bool; N],
+) -> (SMatrix<f64, N, N>, SVector<f64, N>) {
+    let a = SMatrix::from_fn(|i, j| match (free[i], free[j]) {
+        (true, true) => normal.h[(i, j)],
+        _ if i == j => 1.0,
+        _ => 0.0,
+    });
+    let g = SVector::from_fn(|i, _| if free[i] { normal.g[i] } else { 0.0 });
+    (a, g)
+}
@@ -0,0 +1,137 @@
+//! Robust loss functions for the alignment solver, which keep a few bad correspondences from
+//! dragging the whole alignment off. They are applied by iteratively reweighted least squares, so
+//! each loss supplies both its cost and the weight of a residual in the normal equations.
+//!
+//! All the costs are scaled to match the plain squared residual `r²` near zero.
+
+#[derive(Clone, Copy, Debug)]
+pub enum Loss {
+    /// Ordinary least squares
+    Squared,
+
+    /// Squared below the threshold `k`, linear above it
+    Huber(f64),
+
+    /// Tukey's biweight, where residuals beyond `c` have no influence at all
+    Tukey(f64),
+
+    /// The Cauchy (Lorentzian) loss with scale `c`, which grows only logarithmically
+    Cauchy(f64),
+
+    /// Least squares on all but the given fraction of correspondences with the largest residuals
+    Trimmed(f64),
+}
+
+#[derive(Clone, Copy)]
+pub struct Robust {
+    pub loss: Loss,
+
+    /// Correspondences further apart than this are ignored, regardless of the loss
+    pub max_distance: Option<f64>,
+}
+
+impl Default for Robust {
+    fn default() -> Self {
+        Self {
+            loss: Loss::Squared,
+            max_distance: None,
+        }
+    }
+}
+
+impl Robust {
+    /// Check that the loss parameters are usable.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.loss {
+            Loss::Squared => {}
+            Loss::Huber(v) | Loss::Tukey(v) | Loss::Cauchy(v) => {
+                if !(v > 0.0 && v.is_finite()) {
+                    return Err("The scale of a robust loss must be greater than zero".to_string());
+                }
+            }
+            Loss::Trimmed(f) => {
+                if !(0.0..1.0).contains(&f) {
+                    return Err(
+                        "The trimmed fraction must be at least 0 and less than 1".to_string()
+                    );
+                }
+            }
+        }
+
+        if let Some(d) = self.max_distance
+            && (d.is_nan() || d <= 0.0)
+        {
+            return Err(
+                "The maximum correspondence distance must be greater than zero".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The cost and weight of each correspondence, given as the magnitude of its residual and the
+    /// distance between the matched points. Entries without a correspondence get no weight.
+    pub fn apply(&self, matches: &[Option<(f64, f64)>]) -> Vec<(f64, f64)> {
+        // Correspondences out of range cost the same as one at the limit, so that the total cost
+        // doesn't jump when one moves out of range
+        let rejected = self
+            .max_distance
+            .map_or(0.0, |d| self.loss.cost_weight(d).0);
+        let in_range = |distance: f64| self.max_distance.is_none_or(|d| distance <= d);
+
+        // The magnitude above which correspondences are trimmed away
+        let cutoff = match self.loss {
+            Loss::Trimmed(fraction) => {
+                let mut kept = matches
+                    .iter()
+                    .flatten()
+                    .filter(|(_, distance)| in_range(*distance))
+                    .map(|(r, _)| *r)
+                    .collect::<Vec<_>>();
+                kept.sort_by(f64::total_cmp);
+                let count = ((1.0 - fraction) * kept.len() as f64).ceil() as usize;
+                count.checked_sub(1).map_or(f64::NEG_INFINITY, |i| kept[i])
+            }
+            _ => f64::INFINITY,
+        };
+
+        matches
+            .iter()
+            .map(|m| match m {
+                None => (0.0, 0.0),
+                Some((_, distance)) if !in_range(*distance) => (rejected, 0.0),
+                Some((r, _)) if *r > cutoff => (0.0, 0.0),
+                Some((r, _)) => self.loss.cost_weight(*r),
+            })
+            .collect()
+    }
+}
+
+impl Loss {
+    /// The cost `ρ(r)` and the reweighting factor `ρ'(r) / 2r` for a residual magnitude `r`.
+    fn cost_weight(&self, r: f64) -> (f64, f64) {
+        let r = r.abs();
+        match *self {
+            Loss::Squared | Loss::Trimmed(_) => (r * r, 1.0),
+            Loss::Huber(k) => {
+                if r <= k {
+                    (r * r, 1.0)
+                } else {
+                    (2.0 * k * r - k * k, k / r)
+                }
+            }
+            Loss::Tukey(c) => {
+                if r <= c {
+                    let u = 1.0 - (r / c).powi(2);
+                    (c * c / 3.0 * (1.0 - u.powi(3)), u * u)
+                } else {
+                    (c * c / 3.0, 0.0)
+                }
+            }
+            Loss::Cauchy(c) => {
+                let u = (r / c).powi(2);
+                (c * c * u.ln_1p(), 1.0 / (1.0 + u))
+            }
+        }
+    }
+}
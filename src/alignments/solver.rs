@@ -0,0 +1,406 @@
+//! A Levenberg-Marquardt solver for rigid alignments in 3D, in the iterative closest point style
+//! where the correspondences between the moving points and the target are found again each time
+//! the transform is evaluated.
+//!
+//! The transform is parameterized as `frame * motion(x) * frame⁻¹ * initial`, where `motion` is a
+//! translation `[x0, x1, x2]` followed by the Euler rotation `[x3, x4, x5]` about the x, y and z
+//! axes. Working in a reference frame keeps the translation and rotation parameters from being
+//! strongly coupled, and is what the covariance of the result is expressed in.
+
+use super::lm::{self, Evaluated, Normal};
+use super::robust::Robust;
+use crate::common::DeviationMode;
+use crate::kdtree::KdTree3;
+use engeom::{Iso3, Point3, SurfacePoint3, UnitVec3, Vector3};
+use parry3d_f64::na::{Matrix3, Matrix6, Translation3, UnitQuaternion, Vector6};
+use rayon::prelude::*;
+
+/// Something which the moving points can be aligned to.
+pub trait Target: Sync {
+    /// Find the point on the target which corresponds with a test point, or `None` if there is
+    /// no usable correspondence.
+    fn closest(&self, point: &Point3) -> Option<SurfacePoint3>;
+}
+
+impl Target for engeom::Mesh {
+    fn closest(&self, point: &Point3) -> Option<SurfacePoint3> {
+        Some(self.surf_closest_to(point))
+    }
+}
+
+impl Target for KdTree3 {
+    /// Points have no surface direction, so the normal of the match faces the test point
+    fn closest(&self, point: &Point3) -> Option<SurfacePoint3> {
+        let (i, _) = self.nearest(point)?;
+        let c = self.points()[i];
+        let normal = UnitVec3::try_new(point - c, 1e-12).unwrap_or(Vector3::x_axis());
+        Some(SurfacePoint3::new(c, normal))
+    }
+}
+
+/// A set of matched pairs between the moving entity and the target, which are found again for
+/// every transform the solver tries.
+pub trait Correspondences: Sync {
+    /// The number of pairs, some of which may have no match for a given transform
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// For the pair at `index` and the current transform of the moving entity, the point on the
+    /// moving entity in its own coordinates and the point on the target it is matched with in
+    /// world coordinates.
+    fn pair(&self, index: usize, transform: &Iso3) -> Option<(Point3, SurfacePoint3)>;
+}
+
+/// Each moving point is matched with the point on the target closest to it.
+pub struct PointsToTarget<'a> {
+    pub points: &'a [Point3],
+    pub target: &'a dyn Target,
+}
+
+impl Correspondences for PointsToTarget<'_> {
+    fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    fn pair(&self, index: usize, transform: &Iso3) -> Option<(Point3, SurfacePoint3)> {
+        let p = self.points[index];
+        self.target.closest(&(transform * p)).map(|c| (p, c))
+    }
+}
+
+/// Pairs which are known ahead of time and don't depend on the transform.
+pub struct Fixed<'a> {
+    pub pairs: &'a [(Point3, SurfacePoint3)],
+}
+
+impl Correspondences for Fixed<'_> {
+    fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    fn pair(&self, index: usize, _transform: &Iso3) -> Option<(Point3, SurfacePoint3)> {
+        Some(self.pairs[index])
+    }
+}
+
+/// Samples on both entities are matched with the closest point on the other, so that neither one
+/// is favored when they only partially overlap. The moving samples come first in the residuals.
+pub struct Symmetric<'a> {
+    pub moving: &'a dyn Target,
+    pub moving_samples: &'a [Point3],
+    pub target: &'a dyn Target,
+    pub target_samples: &'a [SurfacePoint3],
+}
+
+impl Correspondences for Symmetric<'_> {
+    fn len(&self) -> usize {
+        self.moving_samples.len() + self.target_samples.len()
+    }
+
+    fn pair(&self, index: usize, transform: &Iso3) -> Option<(Point3, SurfacePoint3)> {
+        match self.moving_samples.get(index) {
+            Some(p) => self.target.closest(&(transform * p)).map(|c| (*p, c)),
+            None => {
+                let s = &self.target_samples[index - self.moving_samples.len()];
+                let c = self.moving.closest(&(transform.inverse() * s.point))?;
+                Some((c.point, *s))
+            }
+        }
+    }
+}
+
+pub struct SolverOptions {
+    pub mode: DeviationMode,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub frame: Iso3,
+
+    /// Which of `[tx, ty, tz, rx, ry, rz]` the solver may change, the others stay at zero
+    pub free: [bool; 6],
+
+    pub robust: Robust,
+}
+
+impl SolverOptions {
+    /// Default options with the reference frame placed at the centroid of the points once the
+    /// initial transform has been applied.
+    pub fn centered(points: &[Point3], initial: &Iso3, mode: DeviationMode) -> Self {
+        let n = points.len().max(1) as f64;
+        let sum = points.iter().fold(engeom::Vector3::zeros(), |acc, p| {
+            acc + (initial * p).coords
+        });
+
+        Self {
+            mode,
+            max_iterations: 100,
+            tolerance: 1e-10,
+            frame: Iso3::translation(sum.x / n, sum.y / n, sum.z / n),
+            free: [true; 6],
+            robust: Robust::default(),
+        }
+    }
+}
+
+pub struct Solution {
+    pub transform: Iso3,
+    pub iterations: usize,
+    pub converged: bool,
+
+    /// The final value of the cost function, the sum of squared residuals if there is no robust
+    /// loss
+    pub cost: f64,
+
+    /// The signed distance from each transformed point to its correspondence, or NaN for points
+    /// which had none
+    pub residuals: Vec<f64>,
+
+    /// The weight each correspondence had in the final iteration, zero for those which were
+    /// rejected by the robust loss or had no correspondence
+    pub weights: Vec<f64>,
+
+    /// The covariance of `[tx, ty, tz, rx, ry, rz]` in the reference frame, with zeros for the
+    /// locked parameters
+    pub covariance: Matrix6<f64>,
+}
+
+impl Solution {
+    pub fn rms(&self) -> f64 {
+        let (sum, count) = self
+            .residuals
+            .iter()
+            .filter(|r| r.is_finite())
+            .fold((0.0, 0usize), |(s, c), r| (s + r * r, c + 1));
+        if count == 0 {
+            f64::NAN
+        } else {
+            (sum / count as f64).sqrt()
+        }
+    }
+
+    pub fn max_residual(&self) -> f64 {
+        self.residuals
+            .iter()
+            .filter(|r| r.is_finite())
+            .fold(f64::NAN, |m, r| m.max(r.abs()))
+    }
+}
+
+/// The linearized residual of a single matched pair, with one row in plane mode and three in
+/// point mode.
+struct Term {
+    rows: usize,
+    jacobian: [Vector6<f64>; 3],
+    values: [f64; 3],
+
+    /// The residual the loss is applied to and the distance between the matched points
+    magnitude: f64,
+    distance: f64,
+
+    /// The signed residual which is reported back
+    residual: f64,
+}
+
+struct Evaluation {
+    normal: Normal<6>,
+
+    /// The weighted sum of squared residuals, for estimating the residual variance
+    weighted_sq: f64,
+    residuals: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl Evaluated<6> for Evaluation {
+    fn normal(&self) -> &Normal<6> {
+        &self.normal
+    }
+}
+
+struct Model<'a> {
+    pairs: &'a dyn Correspondences,
+    mode: DeviationMode,
+    robust: &'a Robust,
+    frame: Iso3,
+    base: Iso3,
+}
+
+impl Model<'_> {
+    fn transform(&self, x: &Vector6<f64>) -> Iso3 {
+        self.frame * motion(x) * self.base
+    }
+
+    fn evaluate(&self, x: &Vector6<f64>) -> Evaluation {
+        let transform = self.transform(x);
+        let frame_rot = self.frame.rotation.to_rotation_matrix().into_inner();
+        let d_rot = rotation_derivatives(x);
+
+        let terms = (0..self.pairs.len())
+            .into_par_iter()
+            .map(|i| {
+                let (p, c) = self.pairs.pair(i, &transform)?;
+                let q = transform * p;
+
+                // The point in the reference frame before the motion is applied, and the
+                // derivatives of the world position with respect to each parameter
+                let local = self.base * p;
+                let mut dq = [engeom::Vector3::zeros(); 6];
+                for (k, d) in dq.iter_mut().take(3).enumerate() {
+                    *d = frame_rot.column(k).into_owned();
+                }
+                for (k, d) in d_rot.iter().enumerate() {
+                    dq[3 + k] = frame_rot * (d * local.coords);
+                }
+
+                let delta = q - c.point;
+                let normal_dev = c.normal.dot(&delta);
+                let distance = delta.norm();
+
+                let term = match self.mode {
+                    DeviationMode::Plane => Term {
+                        rows: 1,
+                        jacobian: [
+                            Vector6::from_fn(|k, _| c.normal.dot(&dq[k])),
+                            Vector6::zeros(),
+                            Vector6::zeros(),
+                        ],
+                        values: [normal_dev, 0.0, 0.0],
+                        magnitude: normal_dev.abs(),
+                        distance,
+                        residual: normal_dev,
+                    },
+                    DeviationMode::Point => Term {
+                        rows: 3,
+                        jacobian: std::array::from_fn(|axis| Vector6::from_fn(|k, _| dq[k][axis])),
+                        values: delta.into(),
+                        magnitude: distance,
+                        distance,
+                        residual: distance * normal_dev.signum(),
+                    },
+                };
+                Some(term)
+            })
+            .collect::<Vec<_>>();
+
+        let matches = terms
+            .iter()
+            .map(|t| t.as_ref().map(|t| (t.magnitude, t.distance)))
+            .collect::<Vec<_>>();
+        let robust = self.robust.apply(&matches);
+
+        let mut result = Evaluation {
+            normal: Normal::zeros(),
+            weighted_sq: 0.0,
+            residuals: Vec::with_capacity(terms.len()),
+            weights: Vec::with_capacity(terms.len()),
+        };
+
+        for (term, (cost, weight)) in terms.iter().zip(robust) {
+            result.normal.cost += cost;
+            result.weights.push(weight);
+            let Some(term) = term else {
+                result.residuals.push(f64::NAN);
+                continue;
+            };
+
+            result.residuals.push(term.residual);
+            if weight > 0.0 {
+                for (j, v) in term.jacobian.iter().zip(term.values).take(term.rows) {
+                    result.normal.add(j, v, weight);
+                    result.weighted_sq += v * v * weight;
+                }
+                result.normal.rows += term.rows;
+            }
+        }
+
+        result
+    }
+}
+
+/// Find the transform which best aligns the moving side of a set of correspondences with the
+/// target side, starting from an initial transform.
+pub fn solve(
+    pairs: &dyn Correspondences,
+    initial: &Iso3,
+    options: &SolverOptions,
+) -> Result<Solution, String> {
+    if pairs.is_empty() {
+        return Err("No points were provided to align".to_string());
+    }
+    options.robust.validate()?;
+
+    let model = Model {
+        pairs,
+        mode: options.mode,
+        robust: &options.robust,
+        frame: options.frame,
+        base: options.frame.inverse() * initial,
+    };
+
+    let minimum = lm::minimize(
+        |x| model.evaluate(x),
+        &options.free,
+        options.max_iterations,
+        options.tolerance,
+    )?;
+    let current = minimum.evaluation;
+
+    Ok(Solution {
+        transform: model.transform(&minimum.x),
+        iterations: minimum.iterations,
+        converged: minimum.converged,
+        cost: current.normal.cost,
+        covariance: covariance(&current, &options.free),
+        residuals: current.residuals,
+        weights: current.weights,
+    })
+}
+
+/// The rigid motion for a parameter vector, a translation after an x-y-z Euler rotation.
+fn motion(x: &Vector6<f64>) -> Iso3 {
+    Iso3::from_parts(
+        Translation3::new(x[0], x[1], x[2]),
+        UnitQuaternion::from_euler_angles(x[3], x[4], x[5]),
+    )
+}
+
+/// The derivatives of the rotation matrix `Rz * Ry * Rx` with respect to the three angles.
+fn rotation_derivatives(x: &Vector6<f64>) -> [Matrix3<f64>; 3] {
+    let (sa, ca) = x[3].sin_cos();
+    let (sb, cb) = x[4].sin_cos();
+    let (sc, cc) = x[5].sin_cos();
+
+    let rx = Matrix3::new(1.0, 0.0, 0.0, 0.0, ca, -sa, 0.0, sa, ca);
+    let ry = Matrix3::new(cb, 0.0, sb, 0.0, 1.0, 0.0, -sb, 0.0, cb);
+    let rz = Matrix3::new(cc, -sc, 0.0, sc, cc, 0.0, 0.0, 0.0, 1.0);
+
+    let drx = Matrix3::new(0.0, 0.0, 0.0, 0.0, -sa, -ca, 0.0, ca, -sa);
+    let dry = Matrix3::new(-sb, 0.0, cb, 0.0, 0.0, 0.0, -cb, 0.0, -sb);
+    let drz = Matrix3::new(-sc, -cc, 0.0, cc, -sc, 0.0, 0.0, 0.0, 0.0);
+
+    [rz * ry * drx, rz * dry * rx, drz * ry * rx]
+}
+
+/// Estimate the covariance of the free parameters as `σ² H⁻¹`, with `σ²` taken from the
+/// residuals. If the points don't constrain the free parameters their covariance is infinite.
+fn covariance(eval: &Evaluation, free: &[bool; 6]) -> Matrix6<f64> {
+    let n_free = free.iter().filter(|f| **f).count();
+    let dof = eval.normal.rows.saturating_sub(n_free).max(1) as f64;
+    let sigma2 = eval.weighted_sq / dof;
+
+    let (a, _) = lm::masked(&eval.normal, free);
+    let inverse = a
+        .cholesky()
+        .map_or(Matrix6::from_element(f64::INFINITY), |c| {
+            c.inverse() * sigma2
+        });
+
+    Matrix6::from_fn(|i, j| {
+        if free[i] && free[j] {
+            inverse[(i, j)]
+        } else {
+            0.0
+        }
+    })
+}
@@ -0,0 +1,174 @@
+//! The 2D counterpart of the alignment solver, for fitting points or a curve onto a nominal
+//! `Curve2`. The transform is parameterized as `frame * motion(x) * frame⁻¹ * initial`, where
+//! `motion` is a rotation by `x2` followed by the translation `[x0, x1]`.
+
+use super::lm::{self, Normal};
+use super::robust::Robust;
+use crate::common::DeviationMode;
+use engeom::{Curve2, Iso2, Point2, Vector2};
+use parry3d_f64::na::{Matrix2, Vector3};
+use rayon::prelude::*;
+
+pub struct SolverOptions2 {
+    pub mode: DeviationMode,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub frame: Iso2,
+
+    /// Which of `[tx, ty, r]` the solver may change, the others stay at zero
+    pub free: [bool; 3],
+    pub robust: Robust,
+}
+
+impl SolverOptions2 {
+    /// Default options with the reference frame placed at the centroid of the points once the
+    /// initial transform has been applied.
+    pub fn centered(points: &[Point2], initial: &Iso2, mode: DeviationMode) -> Self {
+        let n = points.len().max(1) as f64;
+        let sum = points
+            .iter()
+            .fold(Vector2::zeros(), |acc, p| acc + (initial * p).coords);
+
+        Self {
+            mode,
+            max_iterations: 100,
+            tolerance: 1e-10,
+            frame: Iso2::translation(sum.x / n, sum.y / n),
+            free: [true; 3],
+            robust: Robust::default(),
+        }
+    }
+}
+
+struct Term {
+    rows: usize,
+    jacobian: [Vector3<f64>; 2],
+    values: [f64; 2],
+    magnitude: f64,
+    distance: f64,
+}
+
+struct Model<'a> {
+    points: &'a [Point2],
+    target: &'a Curve2,
+    options: &'a SolverOptions2,
+    base: Iso2,
+}
+
+impl Model<'_> {
+    fn transform(&self, x: &Vector3<f64>) -> Iso2 {
+        self.options.frame * Iso2::new(Vector2::new(x[0], x[1]), x[2]) * self.base
+    }
+
+    /// Match a transformed point with the target. On an open target, points whose closest point
+    /// is one of the ends hang past it and have no correspondence, so that a moving curve which
+    /// only covers part of the target is not pulled towards the ends.
+    fn closest(&self, q: &Point2) -> Option<(Point2, Vector2)> {
+        let station = self.target.at_closest_to_point(q);
+        let length = station.length_along();
+        let tol = self.target.tol();
+        if !self.target.is_closed() && (length <= tol || length >= self.target.length() - tol) {
+            return None;
+        }
+        Some((station.point(), station.normal().into_inner()))
+    }
+
+    fn evaluate(&self, x: &Vector3<f64>) -> Normal<3> {
+        let transform = self.transform(x);
+        let frame_rot = self
+            .options
+            .frame
+            .rotation
+            .to_rotation_matrix()
+            .into_inner();
+        let (s, c) = x[2].sin_cos();
+        let d_rot = Matrix2::new(-s, -c, c, -s);
+
+        let terms = self
+            .points
+            .par_iter()
+            .map(|p| {
+                let q = transform * p;
+                let (cp, normal) = self.closest(&q)?;
+
+                let local = self.base * p;
+                let dq = [
+                    frame_rot.column(0).into_owned(),
+                    frame_rot.column(1).into_owned(),
+                    frame_rot * (d_rot * local.coords),
+                ];
+
+                let delta = q - cp;
+                let term = match self.options.mode {
+                    DeviationMode::Plane => Term {
+                        rows: 1,
+                        jacobian: [
+                            Vector3::from_fn(|k, _| normal.dot(&dq[k])),
+                            Vector3::zeros(),
+                        ],
+                        values: [normal.dot(&delta), 0.0],
+                        magnitude: normal.dot(&delta).abs(),
+                        distance: delta.norm(),
+                    },
+                    DeviationMode::Point => Term {
+                        rows: 2,
+                        jacobian: std::array::from_fn(|axis| Vector3::from_fn(|k, _| dq[k][axis])),
+                        values: delta.into(),
+                        magnitude: delta.norm(),
+                        distance: delta.norm(),
+                    },
+                };
+                Some(term)
+            })
+            .collect::<Vec<_>>();
+
+        let matches = terms
+            .iter()
+            .map(|t| t.as_ref().map(|t| (t.magnitude, t.distance)))
+            .collect::<Vec<_>>();
+        let robust = self.options.robust.apply(&matches);
+
+        let mut result = Normal::zeros();
+        for (term, (cost, weight)) in terms.iter().zip(robust) {
+            result.cost += cost;
+            if let Some(term) = term
+                && weight > 0.0
+            {
+                for (j, v) in term.jacobian.iter().zip(term.values).take(term.rows) {
+                    result.add(j, v, weight);
+                }
+                result.rows += term.rows;
+            }
+        }
+        result
+    }
+}
+
+/// Align a set of points to a target curve, starting from an initial transform.
+pub fn solve(
+    points: &[Point2],
+    target: &Curve2,
+    initial: &Iso2,
+    options: &SolverOptions2,
+) -> Result<Iso2, String> {
+    if points.is_empty() {
+        return Err("No points were provided to align".to_string());
+    }
+    options.robust.validate()?;
+
+    let model = Model {
+        points,
+        target,
+        options,
+        base: options.frame.inverse() * initial,
+    };
+
+    let minimum = lm::minimize(
+        |x| model.evaluate(x),
+        &options.free,
+        options.max_iterations,
+        options.tolerance,
+    )?;
+
+    Ok(model.transform(&minimum.x))
+}
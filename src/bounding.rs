@@ -1,13 +1,15 @@
 use crate::geom2::{Point2, Vector2};
 use crate::geom3::{Point3, Vector3};
+use crate::serialization::{serial_methods, Serial};
 use numpy::{PyReadonlyArray2, PyReadonlyArrayDyn};
 use pyo3::exceptions::PyValueError;
-use pyo3::{pyclass, pymethods, PyResult};
+use pyo3::{pyclass, pymethods, PyResult, Python};
+use serde::{Deserialize, Serialize};
 // ================================================================================================
 // Aabb2
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Aabb2 {
     inner: engeom::geom2::Aabb2,
@@ -23,6 +25,33 @@ impl Aabb2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Aabb2State {
+    mins: [f64; 2],
+    maxs: [f64; 2],
+}
+
+impl Serial for Aabb2 {
+    const KIND: &'static str = "Aabb2";
+    type State = Aabb2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Aabb2State {
+            mins: self.inner.mins.coords.into(),
+            maxs: self.inner.maxs.coords.into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::geom2::Aabb2::new(
+            state.mins.into(),
+            state.maxs.into(),
+        )))
+    }
+}
+
+serial_methods!(Aabb2);
+
 #[pymethods]
 impl Aabb2 {
     #[new]
@@ -145,7 +174,7 @@ impl Aabb2 {
 // Aabb3
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct Aabb3 {
     inner: engeom::geom3::Aabb3,
@@ -161,6 +190,33 @@ impl Aabb3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Aabb3State {
+    mins: [f64; 3],
+    maxs: [f64; 3],
+}
+
+impl Serial for Aabb3 {
+    const KIND: &'static str = "Aabb3";
+    type State = Aabb3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Aabb3State {
+            mins: self.inner.mins.coords.into(),
+            maxs: self.inner.maxs.coords.into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::geom3::Aabb3::new(
+            state.mins.into(),
+            state.maxs.into(),
+        )))
+    }
+}
+
+serial_methods!(Aabb3);
+
 #[pymethods]
 impl Aabb3 {
     #[new]
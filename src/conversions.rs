@@ -44,6 +44,36 @@ pub fn faces_to_array(faces: &[[u32; 3]]) -> ArrayD<u32> {
     array
 }
 
+pub fn colors_to_array(colors: &[[u8; 4]]) -> ArrayD<u8> {
+    let mut array = ArrayD::zeros(vec![colors.len(), 4]);
+    for (i, color) in colors.iter().enumerate() {
+        for j in 0..4 {
+            array[[i, j]] = color[j];
+        }
+    }
+    array
+}
+
+pub fn array_to_colors(array: &ArrayViewD<'_, u8>) -> PyResult<Vec<[u8; 4]>> {
+    let shape = array.shape();
+    if shape.len() != 2 || (shape[1] != 3 && shape[1] != 4) {
+        return Err(PyValueError::new_err("Expected Nx3 or Nx4 array of colors"));
+    }
+
+    Ok(array
+        .rows()
+        .into_iter()
+        .map(|row| {
+            [
+                row[0],
+                row[1],
+                row[2],
+                if row.len() == 4 { row[3] } else { 255 },
+            ]
+        })
+        .collect())
+}
+
 pub fn array_to_faces(array: &ArrayViewD<'_, u32>) -> PyResult<Vec<[u32; 3]>> {
     let shape = array.shape();
     if shape.len() != 2 || shape[1] != 3 {
@@ -4,6 +4,7 @@ use crate::conversions::{
     array_to_points2, array_to_vectors2, array2_to_points2, points_to_array2,
 };
 use crate::geom3::Point3;
+use crate::serialization::{serial_methods, Serial};
 use engeom::airfoil::OpenEdge;
 use engeom::geom2::{HasBounds2, Line2};
 use engeom::{BestFit, To3D};
@@ -15,6 +16,7 @@ use pyo3::{
     Bound, FromPyObject, IntoPyObject, IntoPyObjectExt, Py, PyAny, PyResult, Python, pyclass,
     pymethods,
 };
+use serde::{Deserialize, Serialize};
 
 #[derive(FromPyObject)]
 enum Vector2OrPoint2 {
@@ -26,7 +28,7 @@ enum Vector2OrPoint2 {
 // Vectors
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Vector2 {
     inner: engeom::Vector2,
@@ -42,6 +44,21 @@ impl Vector2 {
     }
 }
 
+impl Serial for Vector2 {
+    const KIND: &'static str = "Vector2";
+    type State = [f64; 2];
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        [self.inner.x, self.inner.y]
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::Vector2::new(state[0], state[1])))
+    }
+}
+
+serial_methods!(Vector2);
+
 #[pymethods]
 impl Vector2 {
     #[new]
@@ -157,7 +174,7 @@ impl Vector2 {
 // Points
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Point2 {
     inner: engeom::Point2,
@@ -173,6 +190,21 @@ impl Point2 {
     }
 }
 
+impl Serial for Point2 {
+    const KIND: &'static str = "Point2";
+    type State = [f64; 2];
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        [self.inner.x, self.inner.y]
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::Point2::new(state[0], state[1])))
+    }
+}
+
+serial_methods!(Point2);
+
 #[pymethods]
 impl Point2 {
     #[new]
@@ -278,7 +310,7 @@ impl Point2 {
 // ================================================================================================
 // Surface Point
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct SurfacePoint2 {
     pub inner: engeom::SurfacePoint2,
@@ -294,6 +326,33 @@ impl SurfacePoint2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SurfacePoint2State {
+    point: [f64; 2],
+    normal: [f64; 2],
+}
+
+impl Serial for SurfacePoint2 {
+    const KIND: &'static str = "SurfacePoint2";
+    type State = SurfacePoint2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        SurfacePoint2State {
+            point: self.inner.point.coords.into(),
+            normal: self.inner.normal.into_inner().into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::SurfacePoint2::new_normalize(
+            state.point.into(),
+            state.normal.into(),
+        )))
+    }
+}
+
+serial_methods!(SurfacePoint2);
+
 #[pymethods]
 impl SurfacePoint2 {
     #[new]
@@ -391,7 +450,7 @@ impl SurfacePoint2 {
 // ================================================================================================
 // Circle
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Circle2 {
     inner: engeom::Circle2,
@@ -407,6 +466,34 @@ impl Circle2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Circle2State {
+    center: [f64; 2],
+    radius: f64,
+}
+
+impl Serial for Circle2 {
+    const KIND: &'static str = "Circle2";
+    type State = Circle2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Circle2State {
+            center: [self.inner.x(), self.inner.y()],
+            radius: self.inner.r(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::Circle2::new(
+            state.center[0],
+            state.center[1],
+            state.radius,
+        )))
+    }
+}
+
+serial_methods!(Circle2);
+
 #[pymethods]
 impl Circle2 {
     #[new]
@@ -500,7 +587,7 @@ impl Circle2 {
 // ================================================================================================
 // TODO: Type hints for this
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Segment2 {
     inner: engeom::geom2::Segment2,
@@ -516,6 +603,32 @@ impl Segment2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Segment2State {
+    a: [f64; 2],
+    b: [f64; 2],
+}
+
+impl Serial for Segment2 {
+    const KIND: &'static str = "Segment2";
+    type State = Segment2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Segment2State {
+            a: self.inner.a.coords.into(),
+            b: self.inner.b.coords.into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let inner = engeom::geom2::Segment2::try_new(state.a.into(), state.b.into())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner(inner))
+    }
+}
+
+serial_methods!(Segment2);
+
 #[pymethods]
 impl Segment2 {
     #[new]
@@ -554,7 +667,7 @@ impl Segment2 {
 // ================================================================================================
 // Arc
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Arc2 {
     inner: engeom::Arc2,
@@ -570,6 +683,39 @@ impl Arc2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Arc2State {
+    center: [f64; 2],
+    radius: f64,
+    angle0: f64,
+    angle: f64,
+}
+
+impl Serial for Arc2 {
+    const KIND: &'static str = "Arc2";
+    type State = Arc2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Arc2State {
+            center: self.inner.center().coords.into(),
+            radius: self.inner.radius(),
+            angle0: self.inner.angle0,
+            angle: self.inner.angle,
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::Arc2::circle_angles(
+            state.center.into(),
+            state.radius,
+            state.angle0,
+            state.angle,
+        )))
+    }
+}
+
+serial_methods!(Arc2);
+
 #[pymethods]
 impl Arc2 {
     fn __repr__(&self) -> String {
@@ -645,7 +791,7 @@ impl Arc2 {
 // Curve
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct CurveStation2 {
     i_point: engeom::Point2,
@@ -676,6 +822,45 @@ impl CurveStation2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CurveStation2State {
+    point: [f64; 2],
+    direction: [f64; 2],
+    index: usize,
+    fraction: f64,
+    length_along: f64,
+    normal: [f64; 2],
+}
+
+impl Serial for CurveStation2 {
+    const KIND: &'static str = "CurveStation2";
+    type State = CurveStation2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        CurveStation2State {
+            point: self.i_point.coords.into(),
+            direction: self.i_direction.into(),
+            index: self.i_index,
+            fraction: self.i_fraction,
+            length_along: self.i_length_along,
+            normal: self.i_normal.into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::new(
+            state.point.into(),
+            state.direction.into(),
+            state.index,
+            state.fraction,
+            state.length_along,
+            state.normal.into(),
+        ))
+    }
+}
+
+serial_methods!(CurveStation2);
+
 #[pymethods]
 impl CurveStation2 {
     #[getter]
@@ -738,7 +923,7 @@ impl From<engeom::CurveStation2<'_>> for CurveStation2 {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 pub struct Curve2 {
     inner: engeom::Curve2,
     points: Option<Py<PyArrayDyn<f64>>>,
@@ -766,6 +951,44 @@ impl Clone for Curve2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Curve2State {
+    points: Vec<[f64; 2]>,
+    tol: f64,
+    closed: bool,
+}
+
+impl Serial for Curve2 {
+    const KIND: &'static str = "Curve2";
+    type State = Curve2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Curve2State {
+            points: self
+                .inner
+                .points()
+                .iter()
+                .map(|p| p.coords.into())
+                .collect(),
+            tol: self.inner.tol(),
+            closed: self.inner.is_closed(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let points = state
+            .points
+            .into_iter()
+            .map(engeom::Point2::from)
+            .collect::<Vec<_>>();
+        let inner = engeom::Curve2::from_points(&points, state.tol, state.closed)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner(inner))
+    }
+}
+
+serial_methods!(Curve2);
+
 #[pymethods]
 impl Curve2 {
     #[getter]
@@ -987,7 +1210,7 @@ enum Transformable2 {
     Sp(SurfacePoint2),
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 #[derive(Clone, Debug)]
 pub struct Iso2 {
     inner: engeom::Iso2,
@@ -1003,6 +1226,33 @@ impl Iso2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Iso2State {
+    translation: [f64; 2],
+    angle: f64,
+}
+
+impl Serial for Iso2 {
+    const KIND: &'static str = "Iso2";
+    type State = Iso2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Iso2State {
+            translation: self.inner.translation.vector.into(),
+            angle: self.inner.rotation.angle(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::Iso2::new(
+            state.translation.into(),
+            state.angle,
+        )))
+    }
+}
+
+serial_methods!(Iso2);
+
 #[pymethods]
 impl Iso2 {
     #[new]
@@ -1,15 +1,17 @@
 use crate::common::Resample;
 use crate::conversions::{array_to_points3, array_to_vectors3, points_to_array3};
+use crate::serialization::{serial_methods, Serial};
 use engeom::geom3::IsoExtensions3;
 use numpy::ndarray::{Array1, ArrayD};
 use numpy::{IntoPyArray, PyArray1, PyArrayDyn, PyReadonlyArrayDyn, PyUntypedArrayMethods};
-use parry3d_f64::na::{Translation3, UnitQuaternion};
+use parry3d_f64::na::{Quaternion, Translation3, UnitQuaternion};
 use pyo3::exceptions::PyValueError;
 use pyo3::types::PyIterator;
 use pyo3::{
     pyclass, pymethods, Bound, FromPyObject, IntoPyObject, IntoPyObjectExt, Py, PyAny, PyResult,
     Python,
 };
+use serde::{Deserialize, Serialize};
 
 #[derive(FromPyObject)]
 enum Vector3OrPoint3 {
@@ -21,7 +23,7 @@ enum Vector3OrPoint3 {
 // Vectors
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct Vector3 {
     inner: engeom::Vector3,
@@ -37,6 +39,21 @@ impl Vector3 {
     }
 }
 
+impl Serial for Vector3 {
+    const KIND: &'static str = "Vector3";
+    type State = [f64; 3];
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        self.inner.into()
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(state.into()))
+    }
+}
+
+serial_methods!(Vector3);
+
 #[pymethods]
 impl Vector3 {
     #[new]
@@ -163,7 +180,7 @@ impl Vector3 {
 // Points
 // ================================================================================================
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct Point3 {
     inner: engeom::Point3,
@@ -179,6 +196,21 @@ impl Point3 {
     }
 }
 
+impl Serial for Point3 {
+    const KIND: &'static str = "Point3";
+    type State = [f64; 3];
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        self.inner.coords.into()
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(state.into()))
+    }
+}
+
+serial_methods!(Point3);
+
 #[pymethods]
 impl Point3 {
     #[new]
@@ -297,7 +329,7 @@ impl Point3 {
 // ================================================================================================
 // Surface Point
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct SurfacePoint3 {
     pub inner: engeom::SurfacePoint3,
@@ -313,6 +345,33 @@ impl SurfacePoint3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SurfacePoint3State {
+    point: [f64; 3],
+    normal: [f64; 3],
+}
+
+impl Serial for SurfacePoint3 {
+    const KIND: &'static str = "SurfacePoint3";
+    type State = SurfacePoint3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        SurfacePoint3State {
+            point: self.inner.point.coords.into(),
+            normal: self.inner.normal.into_inner().into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::from_inner(engeom::SurfacePoint3::new_normalize(
+            state.point.into(),
+            state.normal.into(),
+        )))
+    }
+}
+
+serial_methods!(SurfacePoint3);
+
 #[pymethods]
 impl SurfacePoint3 {
     #[new]
@@ -411,7 +470,7 @@ impl SurfacePoint3 {
 // ================================================================================================
 // Plane
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct Plane3 {
     pub inner: engeom::Plane3,
@@ -427,6 +486,31 @@ impl Plane3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Plane3State {
+    normal: [f64; 3],
+    d: f64,
+}
+
+impl Serial for Plane3 {
+    const KIND: &'static str = "Plane3";
+    type State = Plane3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Plane3State {
+            normal: self.inner.normal.into_inner().into(),
+            d: self.inner.d,
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let normal = engeom::UnitVec3::new_normalize(state.normal.into());
+        Ok(Self::from_inner(engeom::Plane3::new(normal, state.d)))
+    }
+}
+
+serial_methods!(Plane3);
+
 #[pymethods]
 impl Plane3 {
     #[new]
@@ -467,7 +551,7 @@ impl Plane3 {
 // ================================================================================================
 // Curve
 // ================================================================================================
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct CurveStation3 {
     i_point: engeom::Point3,
@@ -495,6 +579,42 @@ impl CurveStation3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CurveStation3State {
+    point: [f64; 3],
+    direction: [f64; 3],
+    index: usize,
+    fraction: f64,
+    length_along: f64,
+}
+
+impl Serial for CurveStation3 {
+    const KIND: &'static str = "CurveStation3";
+    type State = CurveStation3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        CurveStation3State {
+            point: self.i_point.coords.into(),
+            direction: self.i_direction.into(),
+            index: self.i_index,
+            fraction: self.i_fraction,
+            length_along: self.i_length_along,
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        Ok(Self::new(
+            state.point.into(),
+            state.direction.into(),
+            state.index,
+            state.fraction,
+            state.length_along,
+        ))
+    }
+}
+
+serial_methods!(CurveStation3);
+
 #[pymethods]
 impl CurveStation3 {
     #[getter]
@@ -531,7 +651,7 @@ impl CurveStation3 {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 pub struct Curve3 {
     inner: engeom::Curve3,
     points: Option<Py<PyArrayDyn<f64>>>,
@@ -559,6 +679,42 @@ impl Curve3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Curve3State {
+    points: Vec<[f64; 3]>,
+    tol: f64,
+}
+
+impl Serial for Curve3 {
+    const KIND: &'static str = "Curve3";
+    type State = Curve3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Curve3State {
+            points: self
+                .inner
+                .vertices()
+                .iter()
+                .map(|p| p.coords.into())
+                .collect(),
+            tol: self.inner.tol(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let points = state
+            .points
+            .into_iter()
+            .map(engeom::Point3::from)
+            .collect::<Vec<_>>();
+        let inner = engeom::Curve3::from_points(&points, state.tol)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner(inner))
+    }
+}
+
+serial_methods!(Curve3);
+
 #[pymethods]
 impl Curve3 {
     #[new]
@@ -675,7 +831,7 @@ enum Transformable3 {
     Sp(SurfacePoint3),
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 #[derive(Clone, Debug)]
 pub struct Iso3 {
     inner: engeom::Iso3,
@@ -691,6 +847,38 @@ impl Iso3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Iso3State {
+    translation: [f64; 3],
+    /// The rotation as a unit quaternion in `[i, j, k, w]` order
+    rotation: [f64; 4],
+}
+
+impl Serial for Iso3 {
+    const KIND: &'static str = "Iso3";
+    type State = Iso3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        let r = &self.inner.rotation;
+        Iso3State {
+            translation: self.inner.translation.vector.into(),
+            rotation: [r.i, r.j, r.k, r.w],
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let [i, j, k, w] = state.rotation;
+        let rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k));
+        let translation = Translation3::from(engeom::Vector3::from(state.translation));
+        Ok(Self::from_inner(engeom::Iso3::from_parts(
+            translation,
+            rotation,
+        )))
+    }
+}
+
+serial_methods!(Iso3);
+
 #[pymethods]
 impl Iso3 {
     fn __repr__(&self) -> String {
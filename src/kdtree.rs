@@ -0,0 +1,73 @@
+//! A kd-tree over a set of 3D points, used for the nearest neighbor searches behind point cloud
+//! alignment and processing. The index itself is kiddo's immutable tree, the same one engeom uses,
+//! and this only adapts it to engeom's point type and to returning indices with true distances.
+
+use engeom::Point3;
+use kiddo::immutable::float::kdtree::ImmutableKdTree;
+use kiddo::SquaredEuclidean;
+use std::num::NonZero;
+
+pub struct KdTree3 {
+    points: Vec<Point3>,
+
+    /// The index over `points`, or `None` if there are none, which kiddo can't build a tree from
+    tree: Option<ImmutableKdTree<f64, u64, 3, 32>>,
+}
+
+impl KdTree3 {
+    pub fn new(points: &[Point3]) -> Self {
+        let entries = points
+            .iter()
+            .map(|p| p.coords.into())
+            .collect::<Vec<[f64; 3]>>();
+        let tree = (!entries.is_empty()).then(|| ImmutableKdTree::new_from_slice(&entries));
+
+        Self {
+            points: points.to_vec(),
+            tree,
+        }
+    }
+
+    pub fn points(&self) -> &[Point3] {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The index of the point closest to `point` and the distance to it, or `None` if the tree
+    /// is empty.
+    pub fn nearest(&self, point: &Point3) -> Option<(usize, f64)> {
+        let tree = self.tree.as_ref()?;
+        let found = tree.nearest_one::<SquaredEuclidean>(&point.coords.into());
+        Some((found.item as usize, found.distance.sqrt()))
+    }
+
+    /// The indices of the `k` points closest to `point` and their distances, nearest first.
+    pub fn nearest_k(&self, point: &Point3, k: usize) -> Vec<(usize, f64)> {
+        let (Some(tree), Some(k)) = (&self.tree, NonZero::new(k)) else {
+            return Vec::new();
+        };
+        tree.nearest_n::<SquaredEuclidean>(&point.coords.into(), k)
+            .into_iter()
+            .map(|n| (n.item as usize, n.distance.sqrt()))
+            .collect()
+    }
+
+    /// The indices of all points no further than `radius` from `point` and their distances,
+    /// nearest first.
+    pub fn within(&self, point: &Point3, radius: f64) -> Vec<(usize, f64)> {
+        let Some(tree) = self.tree.as_ref().filter(|_| radius >= 0.0) else {
+            return Vec::new();
+        };
+        tree.within::<SquaredEuclidean>(&point.coords.into(), radius * radius)
+            .into_iter()
+            .map(|n| (n.item as usize, n.distance.sqrt()))
+            .collect()
+    }
+}
@@ -5,10 +5,14 @@ mod common;
 mod conversions;
 mod geom2;
 mod geom3;
+mod kdtree;
 mod mesh;
 mod metrology;
+mod parallel;
+mod point_cloud;
 mod raster;
 mod ray_casting;
+mod serialization;
 mod svd_basis;
 mod sensor;
 
@@ -23,6 +27,7 @@ fn register_geom2(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
     child.add_class::<geom2::Point2>()?;
     child.add_class::<geom2::SurfacePoint2>()?;
     child.add_class::<geom2::Circle2>()?;
+    child.add_class::<geom2::Arc2>()?;
 
     // Curves and other complex geometries
     child.add_class::<geom2::Curve2>()?;
@@ -50,6 +55,11 @@ fn register_geom3(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
     child.add_class::<mesh::Mesh>()?;
     child.add_class::<mesh::MeshCollisionSet>()?;
     child.add_class::<mesh::FaceFilterHandle>()?;
+    child.add_class::<mesh::HoleFill>()?;
+    child.add_class::<mesh::MeshRepairReport>()?;
+    child.add_class::<mesh::MeshCheckReport>()?;
+    child.add_class::<mesh::MeshCurvature>()?;
+    child.add_class::<point_cloud::PointCloud>()?;
     child.add_class::<geom3::Curve3>()?;
     child.add_class::<geom3::CurveStation3>()?;
 
@@ -65,7 +75,23 @@ fn register_geom3(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
 
 fn register_align_module(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
     let child = PyModule::new(parent_module.py(), "_align")?;
+    child.add_class::<alignments::Dof2>()?;
+    child.add_class::<alignments::Dof3>()?;
+    child.add_class::<alignments::RobustLoss>()?;
+    child.add_class::<alignments::AlignmentResult>()?;
     child.add_function(wrap_pyfunction!(alignments::points_to_mesh, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::fit_points_to_mesh, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::points_to_rps, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::coarse_points_to_mesh, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::global_points_to_mesh, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::mesh_to_mesh, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::curve_to_curve, &child)?)?;
+    child.add_function(wrap_pyfunction!(alignments::points_to_points, &child)?)?;
+    child.add_function(wrap_pyfunction!(
+        alignments::points_to_points_scaled,
+        &child
+    )?)?;
+    child.add_function(wrap_pyfunction!(alignments::points_to_points_icp, &child)?)?;
     parent_module.add_submodule(&child)
 }
 
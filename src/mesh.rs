@@ -1,25 +1,56 @@
+mod attributes;
+mod boolean;
+mod check;
+mod curvature;
+mod decimate;
+mod features;
+mod grid;
+mod holes;
+mod io;
+mod mass;
+mod remesh;
+mod repair;
+mod section;
+mod smooth;
+
 use crate::bounding::Aabb3;
 use crate::common::{DeviationMode, SelectOp};
 use crate::conversions::{
-    array_to_faces, array_to_points3, faces_to_array, points_to_array3, vectors_to_array3,
+    array_to_colors, array_to_faces, array_to_points3, colors_to_array, faces_to_array,
+    points_to_array3, vectors_to_array3,
 };
 use crate::geom3::{Curve3, Iso3, Plane3, Point3, SurfacePoint3, Vector3};
+use crate::kdtree::KdTree3;
 use crate::metrology::Distance3;
+use crate::parallel;
+use crate::serialization::{serial_methods, Serial};
 use engeom::common::points::dist;
 use engeom::common::{Selection, SplitResult};
-use numpy::ndarray::{Array1, ArrayD};
-use numpy::{IntoPyArray, PyArray1, PyArrayDyn, PyReadonlyArrayDyn};
-use pyo3::exceptions::{PyIOError, PyValueError};
+use numpy::ndarray::{Array1, Array2, ArrayD};
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayDyn, PyReadonlyArray1, PyReadonlyArrayDyn};
+use pyo3::exceptions::{PyIOError, PyKeyError, PyValueError};
 use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[pyclass]
+pub use attributes::VertexAttributes;
+use boolean::BooleanOp;
+use check::Topology;
+use holes::{FillMethod, FillOptions};
+use io::MeshData;
+use mass::MassProperties;
+use repair::MeshParts;
+
+#[pyclass(module = "engeom.geom3")]
 pub struct Mesh {
     inner: engeom::Mesh,
+    attributes: VertexAttributes,
     vertices: Option<Py<PyArrayDyn<f64>>>,
     faces: Option<Py<PyArrayDyn<u32>>>,
     face_normals: Option<Py<PyArrayDyn<f64>>>,
     vertex_normals: Option<Py<PyArrayDyn<f64>>>,
+    vertex_colors: Option<Py<PyArrayDyn<u8>>>,
 }
 
 impl Mesh {
@@ -28,29 +59,271 @@ impl Mesh {
         self.faces = None;
         self.face_normals = None;
         self.vertex_normals = None;
+        self.vertex_colors = None;
     }
 
     pub fn get_inner(&self) -> &engeom::Mesh {
         &self.inner
     }
 
+    pub fn get_attributes(&self) -> &VertexAttributes {
+        &self.attributes
+    }
+
     pub fn from_inner(inner: engeom::Mesh) -> Self {
+        Self::from_inner_with_attributes(inner, VertexAttributes::default())
+    }
+
+    pub fn from_inner_with_attributes(inner: engeom::Mesh, attributes: VertexAttributes) -> Self {
         Self {
             inner,
+            attributes,
             vertices: None,
             faces: None,
             face_normals: None,
             vertex_normals: None,
+            vertex_colors: None,
+        }
+    }
+
+    /// Build a mesh from data read from a file. Duplicate merging and degenerate face removal are
+    /// done here so that the vertex attributes stay aligned with the vertices, and the options are
+    /// then handed on to the engeom mesh so they still apply to later appends.
+    fn from_data(
+        mut data: MeshData,
+        merge_duplicates: bool,
+        delete_degenerate: bool,
+    ) -> PyResult<Self> {
+        if merge_duplicates {
+            data.merge_duplicates();
+        }
+        if delete_degenerate {
+            data.delete_degenerate();
+        }
+
+        let MeshData {
+            vertices,
+            faces,
+            attributes,
+        } = data;
+
+        let original = (!attributes.is_empty()).then(|| vertices.clone());
+        let inner = engeom::Mesh::new_with_options(
+            vertices,
+            faces,
+            false,
+            merge_duplicates,
+            delete_degenerate,
+            None,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        let attributes = match original {
+            Some(original) if original.len() != inner.vertices().len() => {
+                attributes.remapped(&original, inner.vertices())
+            }
+            _ => attributes,
+        };
+
+        Ok(Self::from_inner_with_attributes(inner, attributes))
+    }
+
+    /// Wrap a mesh derived from this one by an engeom operation which copies vertices without
+    /// changing them, carrying over the vertex attributes by position.
+    fn derived(&self, inner: engeom::Mesh) -> Self {
+        if self.attributes.is_empty() {
+            return Self::from_inner(inner);
+        }
+
+        let attributes = self
+            .attributes
+            .remapped(self.inner.vertices(), inner.vertices());
+        Self::from_inner_with_attributes(inner, attributes)
+    }
+
+    /// Which vertices a smoothing filter may move, from an optional per-vertex mask and an optional
+    /// selection of faces whose vertices may move.
+    fn movable(
+        &self,
+        mask: Option<PyReadonlyArray1<'_, bool>>,
+        region: Option<PyRef<'_, FaceFilterHandle>>,
+    ) -> PyResult<Vec<bool>> {
+        let n = self.inner.vertices().len();
+        let mut movable = match mask {
+            Some(mask) if mask.len()? != n => {
+                return Err(PyValueError::new_err(
+                    "mask must have one value for each vertex",
+                ));
+            }
+            Some(mask) => mask.as_array().to_vec(),
+            None => vec![true; n],
+        };
+
+        if let Some(region) = region {
+            let faces = self.inner.faces();
+            let mut selected = vec![false; n];
+            for &i in region.indices.iter() {
+                let f = faces.get(i).ok_or_else(|| {
+                    PyValueError::new_err("The face selection does not belong to this mesh")
+                })?;
+                for &v in f {
+                    selected[v as usize] = true;
+                }
+            }
+            for (m, s) in movable.iter_mut().zip(selected) {
+                *m &= s;
+            }
+        }
+
+        Ok(movable)
+    }
+
+    /// Build a mesh with the same faces and new vertex positions, keeping the vertex attributes
+    /// other than the normals, which no longer fit the surface.
+    fn smoothed(&self, vertices: Vec<engeom::Point3>) -> PyResult<Self> {
+        let mut attributes = self.attributes.clone();
+        attributes.normals = None;
+        let inner = engeom::Mesh::new_with_options(
+            vertices,
+            self.inner.faces().to_vec(),
+            false,
+            false,
+            false,
+            None,
+        )
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner_with_attributes(inner, attributes))
+    }
+
+    /// The volume properties of the mesh, optionally refusing a mesh which doesn't enclose a
+    /// volume. Vertices at the same position count as one when checking, the same as for booleans.
+    fn mass(&self, require_closed: bool) -> PyResult<MassProperties> {
+        let faces = repair::welded_faces(self.inner.vertices(), self.inner.faces());
+        if require_closed && !mass::is_closed(&faces) {
+            return Err(PyValueError::new_err(
+                "The mesh is not closed and consistently wound, so it does not enclose a volume",
+            ));
         }
+        Ok(mass::mass_properties(
+            self.inner.vertices(),
+            self.inner.faces(),
+        ))
+    }
+
+    /// The volume properties of a mesh which encloses some volume.
+    fn solid(&self, require_closed: bool) -> PyResult<MassProperties> {
+        let properties = self.mass(require_closed)?;
+        if properties.volume == 0.0 {
+            return Err(PyValueError::new_err("The mesh encloses no volume"));
+        }
+        Ok(properties)
+    }
+
+    /// Combine this solid with another one. Neither mesh keeps its vertex attributes, since the
+    /// result is made of new vertices along the cuts as well as vertices from both meshes.
+    fn boolean(&self, other: &Mesh, op: BooleanOp) -> PyResult<Option<Self>> {
+        let closed = |mesh: &Mesh, name: &str| {
+            boolean::solid(mesh.inner.vertices(), mesh.inner.faces())
+                .map_err(|e| PyValueError::new_err(format!("The {name} mesh is invalid: {e}")))
+        };
+        let (a, b) = (closed(self, "first")?, closed(other, "second")?);
+
+        let Some((vertices, faces)) =
+            boolean::boolean(&a, &b, op).map_err(PyValueError::new_err)?
+        else {
+            return Ok(None);
+        };
+
+        let inner = engeom::Mesh::new_with_options(vertices, faces, false, false, false, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Some(Self::from_inner(inner)))
+    }
+
+    /// Build a mesh from a repaired copy of this one, averaging the vertex attributes of the
+    /// vertices each new vertex came from.
+    fn repaired(&self, mut parts: MeshParts) -> PyResult<Self> {
+        parts.compact();
+        let attributes = self.attributes.averaged(&parts.sources);
+        let inner =
+            engeom::Mesh::new_with_options(parts.vertices, parts.faces, false, false, false, None)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner_with_attributes(inner, attributes))
     }
 }
 
 impl Clone for Mesh {
     fn clone(&self) -> Self {
-        Self::from_inner(self.inner.clone())
+        Self::from_inner_with_attributes(self.inner.clone(), self.attributes.clone())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MeshState {
+    vertices: Vec<[f64; 3]>,
+    faces: Vec<[u32; 3]>,
+    normals: Option<Vec<[f64; 3]>>,
+    colors: Option<Vec<[u8; 4]>>,
+    scalars: Vec<(String, Vec<f64>)>,
+    #[serde(default)]
+    is_solid: bool,
+}
+
+impl MeshState {
+    fn from_inner(inner: &engeom::Mesh) -> Self {
+        Self {
+            vertices: inner.vertices().iter().map(|p| p.coords.into()).collect(),
+            faces: inner.faces().to_vec(),
+            normals: None,
+            colors: None,
+            scalars: Vec::new(),
+            is_solid: inner.is_solid(),
+        }
+    }
+
+    fn to_inner(&self) -> PyResult<engeom::Mesh> {
+        let vertices = self.vertices.iter().map(|&v| v.into()).collect();
+        let faces = self.faces.clone();
+        engeom::Mesh::new_with_options(vertices, faces, self.is_solid, false, false, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 }
 
+impl Serial for Mesh {
+    const KIND: &'static str = "Mesh";
+    type State = MeshState;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        let attributes = &self.attributes;
+        MeshState {
+            normals: attributes
+                .normals
+                .as_ref()
+                .map(|n| n.iter().map(|&v| v.into()).collect()),
+            colors: attributes.colors.clone(),
+            scalars: attributes.scalars.clone(),
+            ..MeshState::from_inner(&self.inner)
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let inner = state.to_inner()?;
+        let attributes = VertexAttributes {
+            normals: state
+                .normals
+                .map(|n| n.into_iter().map(engeom::Vector3::from).collect()),
+            colors: state.colors,
+            scalars: state.scalars,
+        };
+        attributes
+            .check_len(inner.vertices().len())
+            .map_err(PyValueError::new_err)?;
+
+        Ok(Self::from_inner_with_attributes(inner, attributes))
+    }
+}
+
+serial_methods!(Mesh);
+
 #[pymethods]
 impl Mesh {
     #[new]
@@ -90,8 +363,71 @@ impl Mesh {
         Ok(Self::from_inner(mesh))
     }
 
+    #[staticmethod]
+    #[pyo3(signature=(path, merge_duplicates = false, delete_degenerate = false))]
+    fn load_ply(path: PathBuf, merge_duplicates: bool, delete_degenerate: bool) -> PyResult<Self> {
+        let data = io::read_ply(&path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        Self::from_data(data, merge_duplicates, delete_degenerate)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature=(path, merge_duplicates = false, delete_degenerate = false))]
+    fn load_obj(path: PathBuf, merge_duplicates: bool, delete_degenerate: bool) -> PyResult<Self> {
+        let data = io::read_obj(&path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        Self::from_data(data, merge_duplicates, delete_degenerate)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature=(path, merge_duplicates = false, delete_degenerate = false))]
+    fn load_3mf(path: PathBuf, merge_duplicates: bool, delete_degenerate: bool) -> PyResult<Self> {
+        let data = io::read_3mf(&path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        Self::from_data(data, merge_duplicates, delete_degenerate)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature=(points, max_edge_length = None, max_angle = None, viewpoint = None))]
+    fn from_organized_grid<'py>(
+        points: PyReadonlyArrayDyn<'py, f64>,
+        max_edge_length: Option<f64>,
+        max_angle: Option<f64>,
+        viewpoint: Option<Point3>,
+    ) -> PyResult<Self> {
+        let points = points.as_array();
+        let shape = points.shape();
+        if shape.len() != 3 || shape[2] != 3 {
+            return Err(PyValueError::new_err(
+                "Expected an array of shape (rows, cols, 3)",
+            ));
+        }
+        if max_angle.is_some() && viewpoint.is_none() {
+            return Err(PyValueError::new_err(
+                "A viewpoint is needed to use a max_angle",
+            ));
+        }
+
+        let grid = points
+            .into_dimensionality()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let options = grid::GridOptions {
+            max_edge_length,
+            max_angle,
+            viewpoint: viewpoint.map(|p| *p.get_inner()),
+        };
+        let (vertices, faces) = grid::triangulate(&grid, &options);
+        if faces.is_empty() {
+            return Err(PyValueError::new_err(
+                "No triangles could be made from the grid",
+            ));
+        }
+
+        let mesh = engeom::Mesh::new_with_options(vertices, faces, false, false, false, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self::from_inner(mesh))
+    }
+
     fn transform_by(&mut self, iso: &Iso3) {
         self.inner.transform(iso.get_inner());
+        self.attributes.transform(iso.get_inner());
 
         self.clear_cached()
     }
@@ -103,9 +439,28 @@ impl Mesh {
 
     fn append(&mut self, other: &Mesh) -> PyResult<()> {
         self.clear_cached();
+
+        let n_self = self.inner.vertices().len();
+        let n_other = other.inner.vertices().len();
+        let carry = !(self.attributes.is_empty() && other.attributes.is_empty());
+        let original = carry.then(|| [self.inner.vertices(), other.inner.vertices()].concat());
+
         self.inner
             .append(&other.inner)
-            .map_err(|e| PyValueError::new_err(e.to_string()))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        if let Some(original) = original {
+            let combined = self.attributes.extended(n_self, &other.attributes, n_other);
+
+            // If the append merged any vertices the attributes have to follow them by position
+            self.attributes = if self.inner.vertices().len() == original.len() {
+                combined
+            } else {
+                combined.remapped(&original, self.inner.vertices())
+            };
+        }
+
+        Ok(())
     }
 
     fn cloned(&self) -> Self {
@@ -117,6 +472,38 @@ impl Mesh {
             .map_err(|e| PyIOError::new_err(e.to_string()))
     }
 
+    #[pyo3(signature=(path, binary = true))]
+    fn write_ply(&self, path: PathBuf, binary: bool) -> PyResult<()> {
+        io::write_ply(
+            &path,
+            self.inner.vertices(),
+            self.inner.faces(),
+            &self.attributes,
+            binary,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    fn write_obj(&self, path: PathBuf) -> PyResult<()> {
+        io::write_obj(
+            &path,
+            self.inner.vertices(),
+            self.inner.faces(),
+            &self.attributes,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    fn write_3mf(&self, path: PathBuf) -> PyResult<()> {
+        io::write_3mf(
+            &path,
+            self.inner.vertices(),
+            self.inner.faces(),
+            &self.attributes,
+        )
+        .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
     #[getter]
     fn vertices<'py>(&mut self, py: Python<'py>) -> &Bound<'py, PyArrayDyn<f64>> {
         if self.vertices.is_none() {
@@ -129,14 +516,82 @@ impl Mesh {
     #[getter]
     fn vertex_normals<'py>(&mut self, py: Python<'py>) -> &Bound<'py, PyArrayDyn<f64>> {
         if self.vertex_normals.is_none() {
-            let normals = self.inner.get_vertex_normals();
-            let array = vectors_to_array3(&normals);
+            let array = match &self.attributes.normals {
+                Some(normals) => vectors_to_array3(normals),
+                None => vectors_to_array3(&self.inner.get_vertex_normals()),
+            };
             self.vertex_normals = Some(array.into_pyarray(py).unbind());
         }
 
         self.vertex_normals.as_ref().unwrap().bind(py)
     }
 
+    #[getter]
+    fn vertex_colors<'py>(&mut self, py: Python<'py>) -> Option<&Bound<'py, PyArrayDyn<u8>>> {
+        let colors = self.attributes.colors.as_ref()?;
+        if self.vertex_colors.is_none() {
+            let array = colors_to_array(colors);
+            self.vertex_colors = Some(array.into_pyarray(py).unbind());
+        }
+
+        self.vertex_colors.as_ref().map(|c| c.bind(py))
+    }
+
+    #[pyo3(signature=(colors = None))]
+    fn set_vertex_colors(&mut self, colors: Option<PyReadonlyArrayDyn<'_, u8>>) -> PyResult<()> {
+        let colors = colors.map(|c| array_to_colors(&c.as_array())).transpose()?;
+
+        if let Some(c) = &colors
+            && c.len() != self.inner.vertices().len()
+        {
+            return Err(PyValueError::new_err(
+                "There must be one color for each vertex",
+            ));
+        }
+
+        self.attributes.colors = colors;
+        self.vertex_colors = None;
+        Ok(())
+    }
+
+    #[getter]
+    fn scalar_field_names(&self) -> Vec<String> {
+        self.attributes
+            .scalars
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn get_scalar_field<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let values = self
+            .attributes
+            .scalar(name)
+            .ok_or_else(|| PyKeyError::new_err(format!("No scalar field named '{}'", name)))?;
+
+        Ok(Array1::from(values.to_vec()).into_pyarray(py))
+    }
+
+    fn set_scalar_field(&mut self, name: &str, values: PyReadonlyArray1<'_, f64>) -> PyResult<()> {
+        let values = values.as_array().to_vec();
+        if values.len() != self.inner.vertices().len() {
+            return Err(PyValueError::new_err(
+                "There must be one value for each vertex",
+            ));
+        }
+
+        self.attributes.set_scalar(name, values);
+        Ok(())
+    }
+
+    fn remove_scalar_field(&mut self, name: &str) -> bool {
+        self.attributes.remove_scalar(name)
+    }
+
     fn get_patch_boundaries(&self) -> PyResult<Vec<Curve3>> {
         let boundaries = self
             .inner
@@ -153,6 +608,44 @@ impl Mesh {
         Ok(result)
     }
 
+    #[pyo3(signature=(angle, boundaries = true, regions = None))]
+    fn feature_edges(
+        &self,
+        angle: Option<f64>,
+        boundaries: bool,
+        regions: Option<Vec<i64>>,
+    ) -> PyResult<Vec<Curve3>> {
+        if regions
+            .as_ref()
+            .is_some_and(|r| r.len() != self.inner.faces().len())
+        {
+            return Err(PyValueError::new_err(
+                "regions must have one label for each face",
+            ));
+        }
+
+        let options = features::FeatureOptions {
+            angle,
+            boundaries,
+            regions,
+        };
+        let vertices = self.inner.vertices();
+        let edges = features::feature_edges(vertices, self.inner.faces(), &options);
+
+        let mut result = Vec::new();
+        for chain in features::chain(&edges) {
+            let points = chain
+                .iter()
+                .map(|&i| vertices[i as usize])
+                .collect::<Vec<_>>();
+            let c = engeom::Curve3::from_points(&points, 1.0e-6)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            result.push(Curve3::from_inner(c))
+        }
+
+        Ok(result)
+    }
+
     #[pyo3(signature=(facing, max_edge_length, corner_angle = None))]
     fn visual_outline<'py>(
         &self,
@@ -224,27 +717,34 @@ impl Mesh {
         }
     }
 
+    #[pyo3(signature=(points, mode, threads = None))]
     fn deviation<'py>(
         &self,
         py: Python<'py>,
         points: PyReadonlyArrayDyn<'py, f64>,
         mode: DeviationMode,
+        threads: Option<usize>,
     ) -> PyResult<Bound<'py, PyArray1<f64>>> {
         let points = array_to_points3(&points.as_array())?;
-        let mut result = Array1::zeros(points.len());
-
-        for (i, point) in points.iter().enumerate() {
-            let closest = self.inner.surf_closest_to(point);
-            let normal_dev = closest.scalar_projection(point);
-
-            result[i] = match mode {
-                // Copy the sign of the normal deviation
-                DeviationMode::Point => dist(&closest.point, point) * normal_dev.signum(),
-                DeviationMode::Plane => normal_dev,
-            }
-        }
-
-        Ok(result.into_pyarray(py))
+        let mesh = &self.inner;
+
+        let result: Vec<f64> = parallel::run(py, threads, || {
+            points
+                .par_iter()
+                .map(|point| {
+                    let closest = mesh.surf_closest_to(point);
+                    let normal_dev = closest.scalar_projection(point);
+
+                    match mode {
+                        // Copy the sign of the normal deviation
+                        DeviationMode::Point => dist(&closest.point, point) * normal_dev.signum(),
+                        DeviationMode::Plane => normal_dev,
+                    }
+                })
+                .collect()
+        })?;
+
+        Ok(Array1::from(result).into_pyarray(py))
     }
 
     fn measure_point_deviation(
@@ -280,8 +780,15 @@ impl Mesh {
         Ok(result.into_pyarray(py))
     }
 
-    fn sample_poisson<'py>(&self, py: Python<'py>, radius: f64) -> Bound<'py, PyArrayDyn<f64>> {
-        let sps = self.inner.sample_poisson(radius);
+    #[pyo3(signature=(radius, threads = None))]
+    fn sample_poisson<'py>(
+        &self,
+        py: Python<'py>,
+        radius: f64,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArrayDyn<f64>>> {
+        let mesh = &self.inner;
+        let sps = parallel::run(py, threads, || mesh.sample_poisson(radius))?;
         let mut result = ArrayD::zeros(vec![sps.len(), 6]);
         for (i, sp) in sps.iter().enumerate() {
             result[[i, 0]] = sp.point.x;
@@ -291,17 +798,30 @@ impl Mesh {
             result[[i, 4]] = sp.normal.y;
             result[[i, 5]] = sp.normal.z;
         }
-        result.into_pyarray(py)
+        Ok(result.into_pyarray(py))
     }
 
-    #[pyo3(signature=(plane, tol = None))]
-    fn section(&self, plane: Plane3, tol: Option<f64>) -> PyResult<Vec<Curve3>> {
-        let results = self
-            .inner
-            .section(plane.get_inner(), tol)
-            .map_err(|e| PyValueError::new_err(e.to_string()))?;
-
-        Ok(results.into_iter().map(Curve3::from_inner).collect())
+    #[pyo3(signature=(plane, tol = None, threads = None))]
+    fn section(
+        &self,
+        py: Python<'_>,
+        plane: Plane3,
+        tol: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<Curve3>> {
+        let mesh = &self.inner;
+        let chains = parallel::run(py, threads, || {
+            section::section(mesh.vertices(), mesh.faces(), plane.get_inner())
+        })?;
+
+        // A piece of the section shorter than the tolerance collapses to a single point, which
+        // isn't a curve
+        let tol = tol.unwrap_or(1.0e-6);
+        Ok(chains
+            .iter()
+            .filter_map(|points| engeom::Curve3::from_points(points, tol).ok())
+            .map(Curve3::from_inner)
+            .collect())
     }
 
     fn face_select_all<'py>(
@@ -329,7 +849,7 @@ impl Mesh {
     }
 
     fn create_from_indices(&self, indices: Vec<usize>) -> Self {
-        Self::from_inner(self.inner.create_from_indices(&indices))
+        self.derived(self.inner.create_from_indices(&indices))
     }
 
     fn separate_patches(&self) -> Vec<Self> {
@@ -344,6 +864,351 @@ impl Mesh {
         Self::from_inner(self.inner.convex_hull())
     }
 
+    #[pyo3(signature=(target_faces = None, ratio = None, max_error = None, preserve_boundary = false, feature_angle = None, threads = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn decimate(
+        &self,
+        py: Python<'_>,
+        target_faces: Option<usize>,
+        ratio: Option<f64>,
+        max_error: Option<f64>,
+        preserve_boundary: bool,
+        feature_angle: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<(Self, f64)> {
+        let n = self.inner.faces().len();
+        let target_faces = match (target_faces, ratio) {
+            (Some(t), None) => t,
+            (None, Some(r)) if r > 0.0 && r <= 1.0 => (r * n as f64).round() as usize,
+            (None, Some(_)) => return Err(PyValueError::new_err("ratio must be in (0, 1]")),
+            (None, None) if max_error.is_some() => 0,
+            (None, None) => {
+                return Err(PyValueError::new_err(
+                    "One of target_faces, ratio or max_error must be given",
+                ));
+            }
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "Only one of target_faces or ratio can be given",
+                ));
+            }
+        };
+        if max_error.is_some_and(|e| e.is_nan() || e < 0.0) {
+            return Err(PyValueError::new_err("max_error must not be negative"));
+        }
+
+        let options = decimate::DecimateOptions {
+            target_faces,
+            max_error,
+            preserve_boundary,
+            feature_angle,
+        };
+        let original = &self.inner;
+        let (mesh, kept, error) = parallel::run(py, threads, || {
+            let (vertices, faces, kept) =
+                decimate::decimate(original.vertices(), original.faces(), &options);
+            let mesh = engeom::Mesh::new_with_options(vertices, faces, false, false, false, None)
+                .map_err(|e| e.to_string())?;
+
+            // The Hausdorff distance, measured from points spread over each surface to the other
+            let to_mesh = |from: &engeom::Mesh, to: &engeom::Mesh| {
+                decimate::surface_samples(from.vertices(), from.faces())
+                    .par_iter()
+                    .map(|p| dist(p, &to.point_closest_to(p)))
+                    .max_by(|a, b| a.total_cmp(b))
+                    .unwrap_or(0.0)
+            };
+            let error = to_mesh(original, &mesh).max(to_mesh(&mesh, original));
+            Ok::<_, String>((mesh, kept, error))
+        })?
+        .map_err(PyValueError::new_err)?;
+
+        let indices = kept.into_iter().map(Some).collect::<Vec<_>>();
+        let attributes = self.attributes.gather(&indices);
+        Ok((Self::from_inner_with_attributes(mesh, attributes), error))
+    }
+
+    #[pyo3(signature=(target_length, iterations = 10, feature_angle = None, threads = None))]
+    fn remesh(
+        &self,
+        py: Python<'_>,
+        target_length: f64,
+        iterations: usize,
+        feature_angle: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        if !(target_length > 0.0 && target_length.is_finite()) {
+            return Err(PyValueError::new_err(
+                "target_length must be greater than zero",
+            ));
+        }
+
+        let options = remesh::RemeshOptions {
+            target_length,
+            iterations,
+            feature_angle,
+        };
+        let original = &self.inner;
+        let carry = !self.attributes.is_empty();
+        let project = |p: &engeom::Point3| original.point_closest_to(p);
+        let (mesh, nearest) = parallel::run(py, threads, || {
+            let (vertices, faces) =
+                remesh::remesh(original.vertices(), original.faces(), &options, &project);
+
+            // The vertices are all new, so each one takes the attributes of the closest vertex
+            // of the original mesh
+            let nearest = if carry {
+                let tree = KdTree3::new(original.vertices());
+                vertices
+                    .par_iter()
+                    .map(|p| tree.nearest(p).map(|(i, _)| i))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let mesh = engeom::Mesh::new_with_options(vertices, faces, false, false, false, None)
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>((mesh, nearest))
+        })?
+        .map_err(PyValueError::new_err)?;
+
+        let attributes = self.attributes.gather(&nearest);
+        Ok(Self::from_inner_with_attributes(mesh, attributes))
+    }
+
+    #[pyo3(signature=(iterations = 10, factor = 0.5, mask = None, region = None, threads = None))]
+    fn smooth_laplacian(
+        &self,
+        py: Python<'_>,
+        iterations: usize,
+        factor: f64,
+        mask: Option<PyReadonlyArray1<'_, bool>>,
+        region: Option<PyRef<'_, FaceFilterHandle>>,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        let movable = self.movable(mask, region)?;
+        let mesh = &self.inner;
+        let vertices = parallel::run(py, threads, || {
+            smooth::laplacian(mesh.vertices(), mesh.faces(), &movable, iterations, factor)
+        })?;
+        self.smoothed(vertices)
+    }
+
+    #[pyo3(signature=(iterations = 10, shrink = 0.5, inflate = -0.53, mask = None, region = None, threads = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn smooth_taubin(
+        &self,
+        py: Python<'_>,
+        iterations: usize,
+        shrink: f64,
+        inflate: f64,
+        mask: Option<PyReadonlyArray1<'_, bool>>,
+        region: Option<PyRef<'_, FaceFilterHandle>>,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        if !(shrink > 0.0 && inflate < -shrink) {
+            return Err(PyValueError::new_err(
+                "shrink must be positive and inflate must be negative with a larger magnitude",
+            ));
+        }
+
+        let movable = self.movable(mask, region)?;
+        let mesh = &self.inner;
+        let vertices = parallel::run(py, threads, || {
+            smooth::taubin(
+                mesh.vertices(),
+                mesh.faces(),
+                &movable,
+                iterations,
+                shrink,
+                inflate,
+            )
+        })?;
+        self.smoothed(vertices)
+    }
+
+    #[pyo3(signature=(normal_iterations = 10, vertex_iterations = 10, sigma = 0.35, mask = None, region = None, threads = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn smooth_bilateral(
+        &self,
+        py: Python<'_>,
+        normal_iterations: usize,
+        vertex_iterations: usize,
+        sigma: f64,
+        mask: Option<PyReadonlyArray1<'_, bool>>,
+        region: Option<PyRef<'_, FaceFilterHandle>>,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return Err(PyValueError::new_err("sigma must be greater than zero"));
+        }
+
+        let movable = self.movable(mask, region)?;
+        let mesh = &self.inner;
+        let vertices = parallel::run(py, threads, || {
+            smooth::bilateral(
+                mesh.vertices(),
+                mesh.faces(),
+                &movable,
+                normal_iterations,
+                vertex_iterations,
+                sigma,
+            )
+        })?;
+        self.smoothed(vertices)
+    }
+
+    #[pyo3(signature=(max_edges, method = HoleFill::MinimumArea, max_perimeter = None))]
+    fn fill_holes(
+        &self,
+        max_edges: Option<usize>,
+        method: HoleFill,
+        max_perimeter: Option<f64>,
+    ) -> PyResult<(Self, MeshRepairReport)> {
+        let options = FillOptions {
+            method: method.into(),
+            max_perimeter,
+            max_edges,
+        };
+        let mut parts = MeshParts::new(self.inner.vertices(), self.inner.faces());
+        let counts = parts.fill_holes(&options);
+
+        let report = MeshRepairReport {
+            holes_filled: counts.filled,
+            holes_skipped: counts.skipped,
+            faces_added: counts.faces_added,
+            vertices_added: counts.vertices_added,
+            ..Default::default()
+        };
+        Ok((self.repaired(parts)?, report))
+    }
+
+    #[pyo3(signature=(duplicates = true, non_manifold = true, orient = true, min_island_faces = None))]
+    fn repair(
+        &self,
+        duplicates: bool,
+        non_manifold: bool,
+        orient: bool,
+        min_island_faces: Option<usize>,
+    ) -> PyResult<(Self, MeshRepairReport)> {
+        let mut parts = MeshParts::new(self.inner.vertices(), self.inner.faces());
+        let mut report = MeshRepairReport {
+            degenerate_faces_removed: parts.remove_degenerate_faces(),
+            ..Default::default()
+        };
+
+        if duplicates {
+            report.duplicate_faces_removed = parts.remove_duplicate_faces();
+        }
+        if non_manifold {
+            report.non_manifold_faces_removed = parts.fix_non_manifold_edges();
+            report.non_manifold_vertices_split = parts.fix_non_manifold_vertices();
+        }
+        if orient {
+            report.faces_flipped = parts.orient_faces();
+        }
+        if let Some(min_faces) = min_island_faces {
+            (report.islands_removed, report.island_faces_removed) = parts.remove_islands(min_faces);
+        }
+
+        Ok((self.repaired(parts)?, report))
+    }
+
+    #[pyo3(signature=(self_intersections = true, threads = None))]
+    fn check(
+        &self,
+        py: Python<'_>,
+        self_intersections: bool,
+        threads: Option<usize>,
+    ) -> PyResult<MeshCheckReport> {
+        let mesh = &self.inner;
+        let topology = parallel::run(py, threads, || {
+            check::check(mesh.vertices(), mesh.faces(), self_intersections)
+        })?;
+        Ok(MeshCheckReport { topology })
+    }
+
+    #[pyo3(signature=(radius = None, threads = None))]
+    fn curvature(
+        &self,
+        py: Python<'_>,
+        radius: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<MeshCurvature> {
+        if radius.is_some_and(|r| !(r > 0.0 && r.is_finite())) {
+            return Err(PyValueError::new_err("radius must be greater than zero"));
+        }
+
+        let mesh = &self.inner;
+        let (inner, mean, gaussian) = parallel::run(py, threads, || {
+            let inner = curvature::curvature(mesh.vertices(), mesh.faces(), radius);
+            let (mean, gaussian) = (inner.mean(), inner.gaussian());
+            (inner, mean, gaussian)
+        })?;
+
+        Ok(MeshCurvature {
+            face_mean: curvature::face_average(&mean, mesh.faces()),
+            face_gaussian: curvature::face_average(&gaussian, mesh.faces()),
+            inner,
+            mean,
+            gaussian,
+        })
+    }
+
+    fn surface_area(&self) -> f64 {
+        mass::surface(self.inner.vertices(), self.inner.faces()).0
+    }
+
+    fn surface_centroid(&self) -> PyResult<Point3> {
+        let (area, centroid) = mass::surface(self.inner.vertices(), self.inner.faces());
+        if area == 0.0 {
+            return Err(PyValueError::new_err("The mesh has no area"));
+        }
+        Ok(Point3::from_inner(centroid))
+    }
+
+    #[pyo3(signature=(require_closed = true))]
+    fn volume(&self, require_closed: bool) -> PyResult<f64> {
+        Ok(self.mass(require_closed)?.volume)
+    }
+
+    #[pyo3(signature=(require_closed = true))]
+    fn centroid(&self, require_closed: bool) -> PyResult<Point3> {
+        Ok(Point3::from_inner(self.solid(require_closed)?.centroid))
+    }
+
+    #[pyo3(signature=(density = 1.0, require_closed = true))]
+    fn inertia_tensor<'py>(
+        &self,
+        py: Python<'py>,
+        density: f64,
+        require_closed: bool,
+    ) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        let inertia = self.solid(require_closed)?.inertia(density);
+        Ok(Array2::from_shape_fn((3, 3), |(i, j)| inertia[(i, j)]).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(require_closed = true))]
+    fn principal_axes(&self, require_closed: bool) -> PyResult<Iso3> {
+        Ok(Iso3::from_inner(
+            self.solid(require_closed)?.principal_axes(),
+        ))
+    }
+
+    fn union(&self, other: &Mesh) -> PyResult<Self> {
+        self.boolean(other, BooleanOp::Union)?
+            .ok_or_else(|| PyValueError::new_err("The union of the meshes is empty"))
+    }
+
+    fn difference(&self, other: &Mesh) -> PyResult<Option<Self>> {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    fn intersection(&self, other: &Mesh) -> PyResult<Option<Self>> {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
     #[staticmethod]
     fn create_box(length: f64, width: f64, height: f64) -> Self {
         let mesh = engeom::Mesh::create_box(length, width, height, true);
@@ -409,12 +1274,340 @@ impl Mesh {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3", eq, eq_int)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HoleFill {
+    Flat,
+    MinimumArea,
+    Curvature,
+}
+
+impl From<HoleFill> for FillMethod {
+    fn from(val: HoleFill) -> Self {
+        match val {
+            HoleFill::Flat => FillMethod::Flat,
+            HoleFill::MinimumArea => FillMethod::MinimumArea,
+            HoleFill::Curvature => FillMethod::Curvature,
+        }
+    }
+}
+
+#[pyclass(module = "engeom.geom3")]
+#[derive(Default, Clone)]
+pub struct MeshRepairReport {
+    holes_filled: usize,
+    holes_skipped: usize,
+    faces_added: usize,
+    vertices_added: usize,
+    degenerate_faces_removed: usize,
+    duplicate_faces_removed: usize,
+    non_manifold_faces_removed: usize,
+    non_manifold_vertices_split: usize,
+    faces_flipped: usize,
+    islands_removed: usize,
+    island_faces_removed: usize,
+}
+
+impl MeshRepairReport {
+    fn entries(&self) -> [(&'static str, usize); 11] {
+        [
+            ("holes_filled", self.holes_filled),
+            ("holes_skipped", self.holes_skipped),
+            ("faces_added", self.faces_added),
+            ("vertices_added", self.vertices_added),
+            ("degenerate_faces_removed", self.degenerate_faces_removed),
+            ("duplicate_faces_removed", self.duplicate_faces_removed),
+            (
+                "non_manifold_faces_removed",
+                self.non_manifold_faces_removed,
+            ),
+            (
+                "non_manifold_vertices_split",
+                self.non_manifold_vertices_split,
+            ),
+            ("faces_flipped", self.faces_flipped),
+            ("islands_removed", self.islands_removed),
+            ("island_faces_removed", self.island_faces_removed),
+        ]
+    }
+}
+
+#[pymethods]
+impl MeshRepairReport {
+    #[getter]
+    fn holes_filled(&self) -> usize {
+        self.holes_filled
+    }
+
+    #[getter]
+    fn holes_skipped(&self) -> usize {
+        self.holes_skipped
+    }
+
+    #[getter]
+    fn faces_added(&self) -> usize {
+        self.faces_added
+    }
+
+    #[getter]
+    fn vertices_added(&self) -> usize {
+        self.vertices_added
+    }
+
+    #[getter]
+    fn degenerate_faces_removed(&self) -> usize {
+        self.degenerate_faces_removed
+    }
+
+    #[getter]
+    fn duplicate_faces_removed(&self) -> usize {
+        self.duplicate_faces_removed
+    }
+
+    #[getter]
+    fn non_manifold_faces_removed(&self) -> usize {
+        self.non_manifold_faces_removed
+    }
+
+    #[getter]
+    fn non_manifold_vertices_split(&self) -> usize {
+        self.non_manifold_vertices_split
+    }
+
+    #[getter]
+    fn faces_flipped(&self) -> usize {
+        self.faces_flipped
+    }
+
+    #[getter]
+    fn islands_removed(&self) -> usize {
+        self.islands_removed
+    }
+
+    #[getter]
+    fn island_faces_removed(&self) -> usize {
+        self.island_faces_removed
+    }
+
+    /// Whether the repair changed anything
+    fn __bool__(&self) -> bool {
+        self.entries()
+            .iter()
+            .any(|(name, n)| *n > 0 && *name != "holes_skipped")
+    }
+
+    fn __repr__(&self) -> String {
+        let changes = self
+            .entries()
+            .iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(name, n)| format!("{}={}", name, n))
+            .collect::<Vec<_>>();
+        format!("<MeshRepairReport {}>", changes.join(", "))
+    }
+}
+
+#[pyclass(module = "engeom.geom3")]
+pub struct MeshCurvature {
+    inner: curvature::Curvature,
+    mean: Vec<f64>,
+    gaussian: Vec<f64>,
+    face_mean: Vec<f64>,
+    face_gaussian: Vec<f64>,
+}
+
+#[pymethods]
+impl MeshCurvature {
+    #[getter]
+    fn k1<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.inner.k1.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn k2<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.inner.k2.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn d1<'py>(&self, py: Python<'py>) -> Bound<'py, PyArrayDyn<f64>> {
+        vectors_to_array3(&self.inner.d1).into_pyarray(py)
+    }
+
+    #[getter]
+    fn d2<'py>(&self, py: Python<'py>) -> Bound<'py, PyArrayDyn<f64>> {
+        vectors_to_array3(&self.inner.d2).into_pyarray(py)
+    }
+
+    #[getter]
+    fn mean<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.mean.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn gaussian<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.gaussian.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn face_mean<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.face_mean.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn face_gaussian<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        Array1::from(self.face_gaussian.clone()).into_pyarray(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<MeshCurvature n={}>", self.mean.len())
+    }
+}
+
+#[pyclass(module = "engeom.geom3")]
+pub struct MeshCheckReport {
+    topology: Topology,
+}
+
+fn edges_to_array(edges: &[[u32; 2]]) -> Array2<u32> {
+    Array2::from(edges.to_vec())
+}
+
+#[pymethods]
+impl MeshCheckReport {
+    #[getter]
+    fn is_watertight(&self) -> bool {
+        self.topology.boundary_edges.is_empty() && self.topology.non_manifold_edges.is_empty()
+    }
+
+    #[getter]
+    fn is_manifold(&self) -> bool {
+        self.topology.is_manifold()
+    }
+
+    #[getter]
+    fn is_oriented(&self) -> bool {
+        self.topology.inconsistent_edges.is_empty()
+    }
+
+    /// Whether the mesh is manifold, consistently wound, and free of degenerate faces and
+    /// self-intersections. A sound mesh may still have boundaries.
+    #[getter]
+    fn is_sound(&self) -> bool {
+        self.is_manifold()
+            && self.is_oriented()
+            && self.topology.degenerate_faces.is_empty()
+            && self.topology.self_intersections.is_empty()
+    }
+
+    #[getter]
+    fn boundary_loops(&self) -> usize {
+        self.topology.boundary_loops
+    }
+
+    #[getter]
+    fn components(&self) -> usize {
+        self.topology.components
+    }
+
+    #[getter]
+    fn euler_characteristic(&self) -> i64 {
+        self.topology.euler_characteristic
+    }
+
+    #[getter]
+    fn genus(&self) -> Option<i64> {
+        self.topology.genus()
+    }
+
+    #[getter]
+    fn boundary_edges<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        edges_to_array(&self.topology.boundary_edges).into_pyarray(py)
+    }
+
+    #[getter]
+    fn non_manifold_edges<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        edges_to_array(&self.topology.non_manifold_edges).into_pyarray(py)
+    }
+
+    #[getter]
+    fn non_manifold_vertices<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<u32>> {
+        Array1::from(self.topology.non_manifold_vertices.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn inconsistent_edges<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        edges_to_array(&self.topology.inconsistent_edges).into_pyarray(py)
+    }
+
+    #[getter]
+    fn degenerate_faces<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<usize>> {
+        Array1::from(self.topology.degenerate_faces.clone()).into_pyarray(py)
+    }
+
+    #[getter]
+    fn self_intersections<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<usize>> {
+        Array2::from(self.topology.self_intersections.clone()).into_pyarray(py)
+    }
+
+    fn __bool__(&self) -> bool {
+        self.is_sound()
+    }
+
+    fn __repr__(&self) -> String {
+        let t = &self.topology;
+        let genus = t.genus().map_or("None".to_string(), |g| g.to_string());
+        format!(
+            "<MeshCheckReport components={} boundary_loops={} euler_characteristic={} genus={} \
+            boundary_edges={} non_manifold_edges={} non_manifold_vertices={} \
+            inconsistent_edges={} degenerate_faces={} self_intersections={}>",
+            t.components,
+            t.boundary_loops,
+            t.euler_characteristic,
+            genus,
+            t.boundary_edges.len(),
+            t.non_manifold_edges.len(),
+            t.non_manifold_vertices.len(),
+            t.inconsistent_edges.len(),
+            t.degenerate_faces.len(),
+            t.self_intersections.len(),
+        )
+    }
+}
+
+#[pyclass(module = "engeom.geom3")]
 pub struct FaceFilterHandle {
     mesh: Py<Mesh>,
     indices: Vec<usize>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct FaceFilterHandleState {
+    mesh: MeshState,
+    indices: Vec<usize>,
+}
+
+impl Serial for FaceFilterHandle {
+    const KIND: &'static str = "FaceFilterHandle";
+    type State = FaceFilterHandleState;
+
+    fn to_state(&self, py: Python<'_>) -> Self::State {
+        FaceFilterHandleState {
+            mesh: self.mesh.bind(py).borrow().to_state(py),
+            indices: self.indices.clone(),
+        }
+    }
+
+    fn from_state(py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let mesh = Mesh::from_state(py, state.mesh)?;
+        Ok(Self {
+            mesh: Py::new(py, mesh)?,
+            indices: state.indices,
+        })
+    }
+}
+
+serial_methods!(FaceFilterHandle);
+
 #[pymethods]
 impl FaceFilterHandle {
     fn __repr__(&self) -> String {
@@ -483,9 +1676,19 @@ impl FaceFilterHandle {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 pub struct MeshCollisionSet {
     inner: engeom::geom3::MeshCollisionSet,
+
+    // The collision set can't be read back out of engeom, so the calls made to build it are
+    // recorded here and replayed when it is deserialized
+    entries: Vec<CollisionEntry>,
+}
+
+enum CollisionEntry {
+    Stationary(engeom::Mesh),
+    Moving(engeom::Mesh),
+    Exception(usize, usize),
 }
 
 impl MeshCollisionSet {
@@ -493,30 +1696,80 @@ impl MeshCollisionSet {
         &self.inner
     }
 
-    pub fn from_inner(inner: engeom::geom3::MeshCollisionSet) -> Self {
-        Self { inner }
+    fn add_entry(&mut self, entry: CollisionEntry) -> usize {
+        let id = match &entry {
+            CollisionEntry::Stationary(mesh) => self.inner.add_stationary(mesh.clone()),
+            CollisionEntry::Moving(mesh) => self.inner.add_moving(mesh.clone()),
+            CollisionEntry::Exception(id1, id2) => {
+                self.inner.add_exception(*id1, *id2);
+                0
+            }
+        };
+        self.entries.push(entry);
+        id
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub enum CollisionEntryState {
+    Stationary(MeshState),
+    Moving(MeshState),
+    Exception(usize, usize),
+}
+
+impl Serial for MeshCollisionSet {
+    const KIND: &'static str = "MeshCollisionSet";
+    type State = Vec<CollisionEntryState>;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                CollisionEntry::Stationary(m) => {
+                    CollisionEntryState::Stationary(MeshState::from_inner(m))
+                }
+                CollisionEntry::Moving(m) => CollisionEntryState::Moving(MeshState::from_inner(m)),
+                CollisionEntry::Exception(a, b) => CollisionEntryState::Exception(*a, *b),
+            })
+            .collect()
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let mut result = Self::new();
+        for entry in state {
+            let entry = match entry {
+                CollisionEntryState::Stationary(m) => CollisionEntry::Stationary(m.to_inner()?),
+                CollisionEntryState::Moving(m) => CollisionEntry::Moving(m.to_inner()?),
+                CollisionEntryState::Exception(a, b) => CollisionEntry::Exception(a, b),
+            };
+            result.add_entry(entry);
+        }
+        Ok(result)
+    }
+}
+
+serial_methods!(MeshCollisionSet);
+
 #[pymethods]
 impl MeshCollisionSet {
     #[new]
     fn new() -> Self {
-        Self::from_inner(engeom::geom3::MeshCollisionSet::new())
+        Self {
+            inner: engeom::geom3::MeshCollisionSet::new(),
+            entries: Vec::new(),
+        }
     }
 
     fn add_stationary(&mut self, mesh: &Mesh) -> usize {
-        let inner = mesh.inner.clone();
-        self.inner.add_stationary(inner)
+        self.add_entry(CollisionEntry::Stationary(mesh.inner.clone()))
     }
 
     fn add_moving(&mut self, mesh: &Mesh) -> usize {
-        let inner = mesh.inner.clone();
-        self.inner.add_moving(inner)
+        self.add_entry(CollisionEntry::Moving(mesh.inner.clone()))
     }
 
     fn add_exception(&mut self, id1: usize, id2: usize) {
-        self.inner.add_exception(id1, id2);
+        self.add_entry(CollisionEntry::Exception(id1, id2));
     }
 
     fn check_all(
@@ -0,0 +1,251 @@
+//! Optional per-vertex data that can be carried alongside the vertices of a mesh, such as the
+//! normals, colors, and scalar fields found in PLY, OBJ, and 3MF files.
+
+use engeom::{Iso3, Point3, Vector3};
+use std::collections::HashMap;
+
+/// The color used to fill in vertices which did not originally have a color, for example after
+/// appending a mesh without colors to one which has them.
+pub const DEFAULT_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+#[derive(Clone, Debug, Default)]
+pub struct VertexAttributes {
+    /// Explicit vertex normals, if the source provided them
+    pub normals: Option<Vec<Vector3>>,
+
+    /// RGBA vertex colors
+    pub colors: Option<Vec<[u8; 4]>>,
+
+    /// Named scalar fields, kept in the order they were added
+    pub scalars: Vec<(String, Vec<f64>)>,
+}
+
+impl VertexAttributes {
+    pub fn is_empty(&self) -> bool {
+        self.normals.is_none() && self.colors.is_none() && self.scalars.is_empty()
+    }
+
+    pub fn scalar(&self, name: &str) -> Option<&[f64]> {
+        self.scalars
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Set a scalar field, replacing any existing field with the same name.
+    pub fn set_scalar(&mut self, name: &str, values: Vec<f64>) {
+        if let Some((_, v)) = self.scalars.iter_mut().find(|(n, _)| n == name) {
+            *v = values;
+        } else {
+            self.scalars.push((name.to_string(), values));
+        }
+    }
+
+    pub fn remove_scalar(&mut self, name: &str) -> bool {
+        let count = self.scalars.len();
+        self.scalars.retain(|(n, _)| n != name);
+        count != self.scalars.len()
+    }
+
+    /// Check that every attribute present has exactly one entry per vertex.
+    pub fn check_len(&self, n: usize) -> Result<(), String> {
+        if let Some(normals) = &self.normals
+            && normals.len() != n
+        {
+            return Err(format!(
+                "Expected {} vertex normals, got {}",
+                n,
+                normals.len()
+            ));
+        }
+
+        if let Some(colors) = &self.colors
+            && colors.len() != n
+        {
+            return Err(format!(
+                "Expected {} vertex colors, got {}",
+                n,
+                colors.len()
+            ));
+        }
+
+        for (name, values) in self.scalars.iter() {
+            if values.len() != n {
+                return Err(format!(
+                    "Expected {} values in scalar field '{}', got {}",
+                    n,
+                    name,
+                    values.len()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rotate the explicit normals (if any) by the rotational part of the isometry.
+    pub fn transform(&mut self, iso: &Iso3) {
+        if let Some(normals) = &mut self.normals {
+            for n in normals.iter_mut() {
+                *n = iso * *n;
+            }
+        }
+    }
+
+    /// Build a new set of attributes by pulling entries from this one. An index of `None` produces
+    /// a default value: a zero normal, the default color, and NaN for scalar fields.
+    pub fn gather(&self, indices: &[Option<usize>]) -> Self {
+        let normals = self.normals.as_ref().map(|normals| {
+            indices
+                .iter()
+                .map(|i| i.map_or(Vector3::zeros(), |i| normals[i]))
+                .collect()
+        });
+
+        let colors = self.colors.as_ref().map(|colors| {
+            indices
+                .iter()
+                .map(|i| i.map_or(DEFAULT_COLOR, |i| colors[i]))
+                .collect()
+        });
+
+        let scalars = self
+            .scalars
+            .iter()
+            .map(|(name, values)| {
+                let v = indices
+                    .iter()
+                    .map(|i| i.map_or(f64::NAN, |i| values[i]))
+                    .collect();
+                (name.clone(), v)
+            })
+            .collect();
+
+        Self {
+            normals,
+            colors,
+            scalars,
+        }
+    }
+
+    /// Build a new set of attributes with one entry for each group of entries in this one, found
+    /// by averaging the group. Averaged normals are normalized and averaged colors are rounded.
+    pub fn averaged(&self, groups: &[Vec<usize>]) -> Self {
+        let normals = self.normals.as_ref().map(|normals| {
+            groups
+                .iter()
+                .map(|g| {
+                    let sum = g.iter().fold(Vector3::zeros(), |acc, &i| acc + normals[i]);
+                    sum.try_normalize(f64::EPSILON).unwrap_or(sum)
+                })
+                .collect()
+        });
+
+        let colors = self.colors.as_ref().map(|colors| {
+            groups
+                .iter()
+                .map(|g| {
+                    std::array::from_fn(|c| {
+                        let sum = g.iter().map(|&i| colors[i][c] as f64).sum::<f64>();
+                        (sum / g.len() as f64).round() as u8
+                    })
+                })
+                .collect()
+        });
+
+        let scalars = self
+            .scalars
+            .iter()
+            .map(|(name, values)| {
+                let v = groups
+                    .iter()
+                    .map(|g| g.iter().map(|&i| values[i]).sum::<f64>() / g.len() as f64)
+                    .collect();
+                (name.clone(), v)
+            })
+            .collect();
+
+        Self {
+            normals,
+            colors,
+            scalars,
+        }
+    }
+
+    /// Produce the attributes for the concatenation of a mesh with `n_self` vertices and another
+    /// with `n_other` vertices. Attributes present on only one side are padded on the other with
+    /// the same default values used by `gather`.
+    pub fn extended(&self, n_self: usize, other: &Self, n_other: usize) -> Self {
+        let normals = match (&self.normals, &other.normals) {
+            (None, None) => None,
+            (a, b) => {
+                let mut v = a.clone().unwrap_or_else(|| vec![Vector3::zeros(); n_self]);
+                v.extend(b.clone().unwrap_or_else(|| vec![Vector3::zeros(); n_other]));
+                Some(v)
+            }
+        };
+
+        let colors = match (&self.colors, &other.colors) {
+            (None, None) => None,
+            (a, b) => {
+                let mut v = a.clone().unwrap_or_else(|| vec![DEFAULT_COLOR; n_self]);
+                v.extend(b.clone().unwrap_or_else(|| vec![DEFAULT_COLOR; n_other]));
+                Some(v)
+            }
+        };
+
+        let mut names = self
+            .scalars
+            .iter()
+            .map(|(n, _)| n.clone())
+            .collect::<Vec<_>>();
+        for (name, _) in other.scalars.iter() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+
+        let scalars = names
+            .into_iter()
+            .map(|name| {
+                let mut v = self
+                    .scalar(&name)
+                    .map_or_else(|| vec![f64::NAN; n_self], |s| s.to_vec());
+                match other.scalar(&name) {
+                    Some(s) => v.extend_from_slice(s),
+                    None => v.extend(std::iter::repeat_n(f64::NAN, n_other)),
+                }
+                (name, v)
+            })
+            .collect();
+
+        Self {
+            normals,
+            colors,
+            scalars,
+        }
+    }
+
+    /// Carry the attributes from one list of vertices over to another list which was derived from
+    /// it by an operation that may have reordered, merged, or dropped vertices. Vertices are
+    /// matched by exact position, and any new vertex which did not exist before receives default
+    /// values.
+    pub fn remapped(&self, old: &[Point3], new: &[Point3]) -> Self {
+        let mut lookup = HashMap::with_capacity(old.len());
+        for (i, p) in old.iter().enumerate() {
+            lookup.entry(position_key(p)).or_insert(i);
+        }
+
+        let indices = new
+            .iter()
+            .map(|p| lookup.get(&position_key(p)).copied())
+            .collect::<Vec<_>>();
+
+        self.gather(&indices)
+    }
+}
+
+/// A hashable key for the exact position of a point
+pub fn position_key(p: &Point3) -> [u64; 3] {
+    [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+}
@@ -0,0 +1,913 @@
+//! Boolean operations between closed meshes. Every face of both meshes is split along the curves
+//! where it meets the other mesh, each piece is sorted by whether it lies inside, outside, or on
+//! the surface of the other mesh, and the pieces each operation keeps are joined into the result.
+//!
+//! Where two faces meet is decided with exact orientation predicates on the original vertices,
+//! and every vertex added along a cut is known by the elements of the two meshes it lies on
+//! rather than by its computed position. The faces on either side of an edge therefore always
+//! agree on where it is split, which keeps the result closed. Faces of the two meshes which lie in
+//! the same plane, as where solids touch or sit flush against each other, are split along the
+//! outline of their overlap like any other cut, and the overlapping pieces are kept once or not at
+//! all depending on the operation and on whether the two faces point the same way.
+
+use super::attributes::position_key;
+use super::mass::is_closed;
+use super::repair::{directed_edges, edge_key, welded_faces};
+use engeom::{Point3, Vector3};
+use rayon::prelude::*;
+use robust::{Coord, Coord3D};
+use spade::handles::{FixedFaceHandle, FixedVertexHandle, InnerTag};
+use spade::{ConstrainedDelaunayTriangulation, HasPosition, Point2, Triangulation};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f64::consts::PI;
+
+/// The vertices and faces of a mesh
+type Parts = (Vec<Point3>, Vec<[u32; 3]>);
+
+/// An edge, as its two vertices in ascending order
+type Edge = (u32, u32);
+
+#[derive(Clone, Copy)]
+pub enum BooleanOp {
+    Union,
+    Difference,
+    Intersection,
+}
+
+/// A vertex of the split faces. Vertices along the cuts are named by the elements of the two
+/// meshes they lie on, so every face which finds one finds the same vertex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Node {
+    /// A vertex of either mesh
+    Vertex(u32),
+
+    /// Where an edge of one mesh passes through the inside of a face of the other
+    EdgeFace(Edge, usize),
+
+    /// Where an edge of each mesh crosses the other, with the lesser edge first
+    EdgeEdge(Edge, Edge),
+}
+
+/// Where a point lies in the plane of a face. Edge `k` runs from corner `k` to the next corner,
+/// and doesn't include them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Location {
+    Outside,
+    Inside,
+    Edge(usize),
+    Corner(usize),
+}
+
+/// Where a piece of one mesh lies relative to the other mesh
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Outside,
+    Inside,
+
+    /// On a face of the other mesh which points the same way
+    Same,
+
+    /// On a face of the other mesh which points the opposite way
+    Opposite,
+}
+
+/// A point or a segment where two faces meet, with where each of its ends lies in each face
+type Contact = Vec<(Node, [Location; 2])>;
+
+/// How two faces meet
+struct Meeting {
+    faces: [usize; 2],
+    coplanar: bool,
+    contacts: Vec<Contact>,
+}
+
+/// A triangle of a split face
+struct Tile {
+    face: usize,
+    nodes: [Node; 3],
+}
+
+/// The cuts found on the faces, gathered from every pair of faces which meet
+#[derive(Default)]
+struct Cuts {
+    /// The cut vertices inside each face, not on its edges
+    inner: HashMap<usize, HashSet<Node>>,
+
+    /// The cut vertices along each edge, keyed by whether the edge is of the first mesh
+    edges: HashMap<(bool, Edge), HashSet<Node>>,
+
+    /// The segments where each face is cut, with where their ends lie in the face
+    segments: HashMap<usize, HashSet<[(Node, Location); 2]>>,
+
+    /// The faces of the other mesh which lie in the same plane as each face and overlap it
+    coplanar: HashMap<usize, Vec<usize>>,
+}
+
+impl Cuts {
+    fn add(&mut self, pair: &Pair, f: usize, contact: &[(Node, Location)]) {
+        for &(node, at) in contact {
+            match at {
+                Location::Inside => {
+                    self.inner.entry(f).or_default().insert(node);
+                }
+                Location::Edge(k) => {
+                    let key = (pair.is_first(f), pair.edge(f, k));
+                    self.edges.entry(key).or_default().insert(node);
+                }
+                _ => {}
+            }
+        }
+
+        if let [a, b] = contact
+            && a.0 != b.0
+        {
+            let segment = if a.0 < b.0 { [*a, *b] } else { [*b, *a] };
+            self.segments.entry(f).or_default().insert(segment);
+        }
+    }
+
+    fn nodes(&self) -> HashSet<Node> {
+        let mut nodes = HashSet::new();
+        nodes.extend(self.inner.values().flatten());
+        nodes.extend(self.edges.values().flatten());
+        nodes.extend(self.segments.values().flatten().flatten().map(|(n, _)| *n));
+        nodes
+    }
+}
+
+/// A vertex of the triangulation of a split face, in the coordinates of its projection
+struct Spot {
+    position: Point2<f64>,
+    node: Node,
+}
+
+impl HasPosition for Spot {
+    type Scalar = f64;
+
+    fn position(&self) -> Point2<f64> {
+        self.position
+    }
+}
+
+fn sign(value: f64) -> i8 {
+    if value > 0.0 {
+        1
+    } else if value < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+fn coord3(p: &Point3) -> Coord3D<f64> {
+    Coord3D {
+        x: p.x,
+        y: p.y,
+        z: p.z,
+    }
+}
+
+/// The exact sign of the orientation of `d` relative to the plane through `a`, `b` and `c`
+fn orient3(a: &Point3, b: &Point3, c: &Point3, d: &Point3) -> i8 {
+    sign(robust::orient3d(coord3(a), coord3(b), coord3(c), coord3(d)))
+}
+
+/// The exact orientation of three points projected onto the plane of two coordinate axes
+fn orient2(axes: [usize; 2], a: &Point3, b: &Point3, c: &Point3) -> f64 {
+    let coord = |p: &Point3| Coord {
+        x: p[axes[0]],
+        y: p[axes[1]],
+    };
+    robust::orient2d(coord(a), coord(b), coord(c))
+}
+
+/// The pairs of coordinate axes a face can be projected onto
+const PROJECTIONS: [[usize; 2]; 3] = [[1, 2], [2, 0], [0, 1]];
+
+/// Where a point lies relative to a triangle, from the signs of its orientation relative to each
+/// edge of the triangle. The point is inside when all of them agree, and on an edge or a corner
+/// where one or two of them are zero.
+fn place(signs: [i8; 3]) -> Location {
+    if signs.contains(&1) && signs.contains(&-1) {
+        return Location::Outside;
+    }
+
+    match signs.map(|s| s == 0) {
+        [false, false, false] => Location::Inside,
+        [true, false, false] => Location::Edge(0),
+        [false, true, false] => Location::Edge(1),
+        [false, false, true] => Location::Edge(2),
+        [true, true, false] => Location::Corner(1),
+        [false, true, true] => Location::Corner(2),
+        [true, false, true] => Location::Corner(0),
+        [true, true, true] => Location::Outside,
+    }
+}
+
+fn edge_edge(a: Edge, b: Edge) -> Node {
+    Node::EdgeEdge(a.min(b), a.max(b))
+}
+
+/// Whether `c`, which is on the line through `a` and `b`, lies strictly between them
+fn between(a: &Point3, b: &Point3, c: &Point3) -> bool {
+    let axis = (b - a).iamax();
+    (a[axis] < c[axis] && c[axis] < b[axis]) || (b[axis] < c[axis] && c[axis] < a[axis])
+}
+
+/// Keep the first and last of the points of a contact along a direction, which only matters if
+/// more than two were found where the faces are very nearly degenerate.
+fn extremes(
+    mut found: Vec<(Node, [Location; 2])>,
+    position: impl Fn(Node) -> Point3,
+    direction: &Vector3,
+) -> Option<Contact> {
+    found.sort_by_key(|(n, _)| *n);
+    found.dedup_by_key(|(n, _)| *n);
+    if found.len() > 2 {
+        let along = |n: Node| position(n).coords.dot(direction);
+        found.sort_by(|a, b| along(a.0).total_cmp(&along(b.0)));
+        found.drain(1..found.len() - 1);
+    }
+
+    (!found.is_empty()).then_some(found)
+}
+
+/// Both solids of a boolean operation, with their vertices in one list. Vertices at the same
+/// position are the same vertex, whichever mesh they came from, and the faces of the first mesh
+/// come before those of the second.
+struct Pair {
+    points: Vec<Point3>,
+    faces: Vec<[u32; 3]>,
+    first: usize,
+
+    /// The coordinate axes each face is projected onto, where its projection has the most area
+    axes: Vec<[usize; 2]>,
+}
+
+impl Pair {
+    fn new(a: &Parts, b: &Parts) -> Self {
+        let mut index = HashMap::new();
+        let mut points = Vec::new();
+        let mut faces = Vec::with_capacity(a.1.len() + b.1.len());
+        for (vertices, mesh_faces) in [a, b] {
+            // Adding zero turns negative zeros into positive ones, so they are the same position
+            let map = vertices
+                .iter()
+                .map(|p| {
+                    let p = Point3::new(p.x + 0.0, p.y + 0.0, p.z + 0.0);
+                    *index.entry(position_key(&p)).or_insert_with(|| {
+                        points.push(p);
+                        (points.len() - 1) as u32
+                    })
+                })
+                .collect::<Vec<_>>();
+            faces.extend(mesh_faces.iter().map(|f| f.map(|i| map[i as usize])));
+        }
+
+        let axes = faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.map(|i| points[i as usize]);
+                PROJECTIONS
+                    .into_iter()
+                    .max_by(|&i, &j| {
+                        orient2(i, &a, &b, &c)
+                            .abs()
+                            .total_cmp(&orient2(j, &a, &b, &c).abs())
+                    })
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            points,
+            faces,
+            first: a.1.len(),
+            axes,
+        }
+    }
+
+    fn is_first(&self, f: usize) -> bool {
+        f < self.first
+    }
+
+    fn corners(&self, f: usize) -> [Point3; 3] {
+        self.faces[f].map(|i| self.points[i as usize])
+    }
+
+    fn normal(&self, f: usize) -> Vector3 {
+        let [a, b, c] = self.corners(f);
+        (b - a).cross(&(c - a))
+    }
+
+    fn edge(&self, f: usize, k: usize) -> Edge {
+        let face = self.faces[f];
+        edge_key(face[k], face[(k + 1) % 3])
+    }
+
+    /// Where a point in the plane of face `f` lies in it
+    fn locate(&self, f: usize, p: &Point3) -> Location {
+        let c = self.corners(f);
+        let axes = self.axes[f];
+        place([0, 1, 2].map(|k| sign(orient2(axes, &c[k], &c[(k + 1) % 3], p))))
+    }
+
+    /// Where the segment from vertex `a` to vertex `b`, whose ends are on opposite sides of the
+    /// plane of face `f`, crosses that plane, relative to the face
+    fn locate_crossing(&self, f: usize, a: u32, b: u32) -> Location {
+        let c = self.corners(f);
+        let (a, b) = (&self.points[a as usize], &self.points[b as usize]);
+        place([0, 1, 2].map(|k| orient3(a, b, &c[k], &c[(k + 1) % 3])))
+    }
+
+    /// The position of a vertex of the split faces
+    fn position(&self, node: Node) -> Point3 {
+        let point = |i: u32| self.points[i as usize];
+        match node {
+            Node::Vertex(v) => point(v),
+            Node::EdgeFace((a, b), f) => {
+                let (pa, pb) = (point(a), point(b));
+                let [c, ..] = self.corners(f);
+                let normal = self.normal(f);
+                let (da, db) = (normal.dot(&(pa - c)), normal.dot(&(pb - c)));
+                pa + (pb - pa) * (da / (da - db)).clamp(0.0, 1.0)
+            }
+            Node::EdgeEdge((a, b), (c, d)) => {
+                // The middle of the closest points of the two lines
+                let (pa, pc) = (point(a), point(c));
+                let (u, v, w) = (point(b) - pa, point(d) - pc, pa - pc);
+                let (uu, uv, vv, uw, vw) = (u.dot(&u), u.dot(&v), v.dot(&v), u.dot(&w), v.dot(&w));
+                let denom = uu * vv - uv * uv;
+                let s = ((uv * vw - vv * uw) / denom).clamp(0.0, 1.0);
+                let t = ((uu * vw - uv * uw) / denom).clamp(0.0, 1.0);
+                Point3::from(((pa + u * s).coords + (pc + v * t).coords) / 2.0)
+            }
+        }
+    }
+
+    /// The pairs of faces, one from each mesh, whose bounds overlap
+    fn candidates(&self) -> Vec<[usize; 2]> {
+        let bounds = self
+            .faces
+            .iter()
+            .enumerate()
+            .map(|(f, _)| {
+                let [a, b, c] = self.corners(f);
+                (
+                    a.coords.inf(&b.coords).inf(&c.coords),
+                    a.coords.sup(&b.coords).sup(&c.coords),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Sweep along x, only testing faces whose bounds overlap
+        let mut order = (0..self.faces.len()).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| bounds[i].0.x.total_cmp(&bounds[j].0.x));
+
+        let (order, bounds) = (&order, &bounds);
+        (0..order.len())
+            .into_par_iter()
+            .flat_map_iter(|k| {
+                let i = order[k];
+                let (lower, upper) = bounds[i];
+                order[k + 1..]
+                    .iter()
+                    .copied()
+                    .take_while(move |&j| bounds[j].0.x <= upper.x)
+                    .filter(move |&j| self.is_first(i) != self.is_first(j))
+                    .filter(move |&j| {
+                        let (l, u) = bounds[j];
+                        l.y <= upper.y && u.y >= lower.y && l.z <= upper.z && u.z >= lower.z
+                    })
+                    .map(move |j| if self.is_first(i) { [i, j] } else { [j, i] })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Find the ends of the segment where face `g` meets the plane of face `f` which lie within
+    /// `f`, given the side of the plane each corner of `g` is on. The locations are in `f` and
+    /// then in `g`.
+    fn plane_ends(
+        &self,
+        f: usize,
+        g: usize,
+        sides: [i8; 3],
+        found: &mut Vec<(Node, [Location; 2])>,
+    ) {
+        let face = self.faces[g];
+        for k in 0..3 {
+            let (a, b) = (face[k], face[(k + 1) % 3]);
+            if sides[k] == 0 {
+                let here = self.locate(f, &self.points[a as usize]);
+                if here != Location::Outside {
+                    found.push((Node::Vertex(a), [here, Location::Corner(k)]));
+                }
+            } else if sides[k] * sides[(k + 1) % 3] < 0 {
+                let here = self.locate_crossing(f, a, b);
+                let node = match here {
+                    Location::Outside => continue,
+                    Location::Inside => Node::EdgeFace(edge_key(a, b), f),
+                    Location::Edge(m) => edge_edge(self.edge(f, m), edge_key(a, b)),
+                    Location::Corner(m) => Node::Vertex(self.faces[f][m]),
+                };
+                found.push((node, [here, Location::Edge(k)]));
+            }
+        }
+    }
+
+    /// The part of edge `k` of face `g` which lies within face `f`, when the two faces are in the
+    /// same plane. The locations are in `f` and then in `g`.
+    fn clip(&self, f: usize, g: usize, k: usize) -> Option<Contact> {
+        let axes = self.axes[f];
+        let face = self.faces[g];
+        let (a, b) = (face[k], face[(k + 1) % 3]);
+        let (pa, pb) = (self.points[a as usize], self.points[b as usize]);
+
+        let mut found = Vec::new();
+        for (v, at) in [(a, Location::Corner(k)), (b, Location::Corner((k + 1) % 3))] {
+            let here = self.locate(f, &self.points[v as usize]);
+            if here != Location::Outside {
+                found.push((Node::Vertex(v), [here, at]));
+            }
+        }
+
+        let c = self.corners(f);
+        for m in 0..3 {
+            let (pc, pd) = (&c[m], &c[(m + 1) % 3]);
+            let (oc, od) = (
+                sign(orient2(axes, &pa, &pb, pc)),
+                sign(orient2(axes, &pa, &pb, pd)),
+            );
+            let (oa, ob) = (
+                sign(orient2(axes, pc, pd, &pa)),
+                sign(orient2(axes, pc, pd, &pb)),
+            );
+            if oc * od < 0 && oa * ob < 0 {
+                let node = edge_edge(self.edge(f, m), edge_key(a, b));
+                found.push((node, [Location::Edge(m), Location::Edge(k)]));
+            } else if oc == 0 && between(&pa, &pb, pc) {
+                let node = Node::Vertex(self.faces[f][m]);
+                found.push((node, [Location::Corner(m), Location::Edge(k)]));
+            }
+        }
+
+        extremes(found, |n| self.position(n), &(pb - pa))
+    }
+
+    /// How face `fa` of the first mesh and face `fb` of the second meet, if they do
+    fn meet(&self, fa: usize, fb: usize) -> Option<Meeting> {
+        let side = |f: usize, v: u32| {
+            let [a, b, c] = self.corners(f);
+            orient3(&a, &b, &c, &self.points[v as usize])
+        };
+        let sides_b = self.faces[fb].map(|v| side(fa, v));
+        let apart = |s: [i8; 3]| s.iter().all(|&x| x > 0) || s.iter().all(|&x| x < 0);
+        if apart(sides_b) {
+            return None;
+        }
+
+        let swap = |(n, [x, y]): (Node, [Location; 2])| (n, [y, x]);
+        let mut contacts = Vec::new();
+        let coplanar = sides_b == [0, 0, 0];
+        if coplanar {
+            for k in 0..3 {
+                contacts.extend(self.clip(fa, fb, k));
+                contacts.extend(
+                    self.clip(fb, fa, k)
+                        .map(|c| c.into_iter().map(swap).collect()),
+                );
+            }
+        } else {
+            let sides_a = self.faces[fa].map(|v| side(fb, v));
+            if apart(sides_a) {
+                return None;
+            }
+
+            let mut found = Vec::new();
+            self.plane_ends(fa, fb, sides_b, &mut found);
+            let mut other = Vec::new();
+            self.plane_ends(fb, fa, sides_a, &mut other);
+            found.extend(other.into_iter().map(swap));
+
+            let direction = self.normal(fa).cross(&self.normal(fb));
+            contacts.extend(extremes(found, |n| self.position(n), &direction));
+        }
+
+        (coplanar || !contacts.is_empty()).then_some(Meeting {
+            faces: [fa, fb],
+            coplanar,
+            contacts,
+        })
+    }
+
+    /// Split a face along its cuts, returning its triangles wound the same way as the face.
+    fn split(
+        &self,
+        f: usize,
+        cuts: &Cuts,
+        positions: &HashMap<Node, Point3>,
+    ) -> Result<Vec<[Node; 3]>, String> {
+        let face = self.faces[f];
+        let corners = face.map(Node::Vertex);
+        let on_edge = |k: usize| cuts.edges.get(&(self.is_first(f), self.edge(f, k)));
+        let inner = cuts.inner.get(&f);
+        let segments = cuts.segments.get(&f);
+        if inner.is_none() && segments.is_none() && (0..3).all(|k| on_edge(k).is_none()) {
+            return Ok(vec![corners]);
+        }
+
+        // The outline of the face, with the cut vertices along each edge in order
+        let c = self.corners(f);
+        let mut ring = Vec::new();
+        for k in 0..3 {
+            ring.push(corners[k]);
+            let mut along = on_edge(k)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+            let (start, direction) = (c[k], c[(k + 1) % 3] - c[k]);
+            let t = |n: &Node| (positions[n] - start).dot(&direction);
+            along.sort_by(|a, b| t(a).total_cmp(&t(b)));
+            ring.extend(along);
+        }
+
+        let axes = self.axes[f];
+        let spot = |node: Node| {
+            let p = positions[&node];
+            Spot {
+                position: Point2::new(p[axes[0]], p[axes[1]]),
+                node,
+            }
+        };
+        let mut cdt = ConstrainedDelaunayTriangulation::<Spot>::new();
+        let mut handles = HashMap::new();
+        for &node in ring.iter().chain(inner.into_iter().flatten()) {
+            let handle = cdt.insert(spot(node)).map_err(|e| format!("{e:?}"))?;
+            handles.insert(node, handle);
+        }
+
+        let crossed = || {
+            "The cuts through a face cross each other, which can happen where the meshes meet at a \
+             very shallow angle"
+                .to_string()
+        };
+        let mut constrain = |a: FixedVertexHandle, b: FixedVertexHandle| {
+            if a != b && !cdt.can_add_constraint(a, b) {
+                return Err(crossed());
+            }
+            cdt.add_constraint(a, b);
+            Ok(())
+        };
+        for i in 0..ring.len() {
+            constrain(handles[&ring[i]], handles[&ring[(i + 1) % ring.len()]])?;
+        }
+
+        // Segments along an edge of the face are already part of its outline
+        let along_edge = |at: Location, k: usize| match at {
+            Location::Edge(m) => m == k,
+            Location::Corner(m) => m == k || m == (k + 1) % 3,
+            _ => false,
+        };
+        for [(a, at_a), (b, at_b)] in segments.into_iter().flatten() {
+            if !(0..3).any(|k| along_edge(*at_a, k) && along_edge(*at_b, k)) {
+                constrain(handles[a], handles[b])?;
+            }
+        }
+
+        // Everything outside the outline is reached from the convex hull without crossing it
+        let mut outside: HashSet<FixedFaceHandle<InnerTag>> = HashSet::new();
+        let mut queue = VecDeque::new();
+        let enter = |edge: spade::handles::DirectedEdgeHandle<_, _, _, _>,
+                     outside: &mut HashSet<_>,
+                     queue: &mut VecDeque<_>| {
+            if cdt.is_constraint_edge(edge.as_undirected().fix()) {
+                return;
+            }
+            if let Some(next) = edge.rev().face().as_inner()
+                && outside.insert(next.fix())
+            {
+                queue.push_back(next.fix());
+            }
+        };
+        for edge in cdt.convex_hull() {
+            enter(edge, &mut outside, &mut queue);
+            enter(edge.rev(), &mut outside, &mut queue);
+        }
+        while let Some(h) = queue.pop_front() {
+            for edge in cdt.face(h).adjacent_edges() {
+                enter(edge, &mut outside, &mut queue);
+            }
+        }
+
+        // The triangulation winds its triangles anticlockwise in the projection
+        let turn = orient2(axes, &c[0], &c[1], &c[2]) < 0.0;
+        let mut tiles = cdt
+            .inner_faces()
+            .filter(|t| !outside.contains(&t.fix()))
+            .map(|t| {
+                let [a, b, c] = t.vertices().map(|v| v.data().node);
+                if turn {
+                    [a, c, b]
+                } else {
+                    [a, b, c]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // A cut vertex inside the face can be rounded onto an edge of the outline, which the
+        // triangulation then splits there. The sliver between them is put back as a fan, so that
+        // the face still meets its neighbour along the whole edge.
+        let edges = tiles
+            .iter()
+            .flat_map(|t| (0..3).map(move |k| (t[k], t[(k + 1) % 3])))
+            .collect::<HashSet<_>>();
+        let next = edges
+            .iter()
+            .filter(|(a, b)| !edges.contains(&(*b, *a)))
+            .copied()
+            .collect::<HashMap<_, _>>();
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            if a == b || edges.contains(&(a, b)) {
+                continue;
+            }
+            let mut path = vec![a];
+            while let Some(&n) = next.get(path.last().unwrap()) {
+                if n == b || path.len() > next.len() {
+                    break;
+                }
+                path.push(n);
+            }
+            let last = *path.last().unwrap();
+            if last == a || next.get(&last) != Some(&b) {
+                return Err(crossed());
+            }
+            tiles.push([a, b, last]);
+            tiles.extend(path[1..].windows(2).map(|w| [a, w[1], w[0]]));
+        }
+
+        Ok(tiles)
+    }
+
+    /// The winding number of the faces of one mesh around a point, which is one inside a closed
+    /// mesh and zero outside it (Van Oosterom and Strackee, 1983)
+    fn winding(&self, first: bool, p: &Point3) -> f64 {
+        let range = if first {
+            0..self.first
+        } else {
+            self.first..self.faces.len()
+        };
+        let angle = range
+            .map(|f| {
+                let [a, b, c] = self.corners(f).map(|q| q - p);
+                let (la, lb, lc) = (a.norm(), b.norm(), c.norm());
+                let det = a.dot(&b.cross(&c));
+                let div = la * lb * lc + a.dot(&b) * lc + a.dot(&c) * lb + b.dot(&c) * la;
+                2.0 * det.atan2(div)
+            })
+            .sum::<f64>();
+        angle / (4.0 * PI)
+    }
+}
+
+/// Check that a mesh can be one side of a boolean operation, returning its faces with vertices at
+/// the same position merged, so that meshes with split vertices along their sharp edges still
+/// count as closed. Faces with no area are left out.
+pub fn solid(vertices: &[Point3], faces: &[[u32; 3]]) -> Result<Parts, String> {
+    let faces = welded_faces(vertices, faces);
+    if !is_closed(&faces) {
+        return Err("The mesh is not closed and consistently wound".to_string());
+    }
+
+    let faces = faces
+        .into_iter()
+        .filter(|f| {
+            let [a, b, c] = f.map(|i| vertices[i as usize]);
+            PROJECTIONS
+                .iter()
+                .any(|&axes| orient2(axes, &a, &b, &c) != 0.0)
+        })
+        .collect();
+    Ok((vertices.to_vec(), faces))
+}
+
+/// Whether a piece lying where it does relative to the other mesh is part of the result, and if
+/// so whether it's turned over.
+fn keep(op: BooleanOp, first: bool, side: Side) -> Option<bool> {
+    match (op, first, side) {
+        (BooleanOp::Union, _, Side::Outside) => Some(false),
+        (BooleanOp::Intersection, _, Side::Inside) => Some(false),
+        (BooleanOp::Union | BooleanOp::Intersection, true, Side::Same) => Some(false),
+        (BooleanOp::Difference, true, Side::Outside | Side::Opposite) => Some(false),
+        (BooleanOp::Difference, false, Side::Inside) => Some(true),
+        _ => None,
+    }
+}
+
+/// Combine two solids, as returned by `solid`. Returns `None` if nothing is left, such as for the
+/// intersection of two solids which don't overlap.
+pub fn boolean(a: &Parts, b: &Parts, op: BooleanOp) -> Result<Option<Parts>, String> {
+    let pair = Pair::new(a, b);
+
+    let meetings = pair
+        .candidates()
+        .into_par_iter()
+        .filter_map(|[fa, fb]| pair.meet(fa, fb))
+        .collect::<Vec<_>>();
+
+    let mut cuts = Cuts::default();
+    for meeting in meetings.iter() {
+        let [fa, fb] = meeting.faces;
+        if meeting.coplanar {
+            cuts.coplanar.entry(fa).or_default().push(fb);
+            cuts.coplanar.entry(fb).or_default().push(fa);
+        }
+        for contact in meeting.contacts.iter() {
+            for (side, f) in [fa, fb].into_iter().enumerate() {
+                let at = contact
+                    .iter()
+                    .map(|(n, l)| (*n, l[side]))
+                    .collect::<Vec<_>>();
+                cuts.add(&pair, f, &at);
+            }
+        }
+    }
+
+    let positions = cuts
+        .nodes()
+        .into_iter()
+        .chain((0..pair.points.len() as u32).map(Node::Vertex))
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|n| (n, pair.position(n)))
+        .collect::<HashMap<_, _>>();
+
+    let tiles = (0..pair.faces.len())
+        .into_par_iter()
+        .map(|f| {
+            let split = pair.split(f, &cuts, &positions)?;
+            Ok(split.into_iter().map(move |nodes| Tile { face: f, nodes }))
+        })
+        .collect::<Result<Vec<_>, String>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let sides = classify(&pair, &cuts, &tiles, &positions);
+
+    let mut index = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+    for (tile, side) in tiles.iter().zip(sides) {
+        let Some(flip) = keep(op, pair.is_first(tile.face), side) else {
+            continue;
+        };
+
+        let [a, b, c] = tile.nodes.map(|n| {
+            let p = positions[&n];
+            *index.entry(position_key(&p)).or_insert_with(|| {
+                vertices.push(p);
+                (vertices.len() - 1) as u32
+            })
+        });
+        if a != b && b != c && c != a {
+            faces.push(if flip { [a, c, b] } else { [a, b, c] });
+        }
+    }
+
+    if faces.is_empty() {
+        return Ok(None);
+    }
+    if !is_balanced(&faces) {
+        return Err("The result is not closed".to_string());
+    }
+    Ok(Some((vertices, faces)))
+}
+
+/// Whether every edge is used as many times in one direction as in the other. This is looser than
+/// a closed manifold, since the result of solids which only touch along an edge has four faces
+/// around it.
+fn is_balanced(faces: &[[u32; 3]]) -> bool {
+    let mut count = HashMap::new();
+    for f in faces.iter() {
+        for edge in directed_edges(f) {
+            *count.entry(edge).or_insert(0) += 1;
+        }
+    }
+    count
+        .iter()
+        .all(|((a, b), n)| count.get(&(*b, *a)) == Some(n))
+}
+
+/// Find where each tile lies relative to the other mesh. Tiles lying on a face of the other mesh
+/// are found from the faces which share their plane. The rest are grouped into patches which
+/// meet without crossing the other mesh, which is wherever the tiles of the two meshes share an
+/// edge, and each patch is tested once with the winding number of the other mesh around its
+/// largest tile.
+fn classify(
+    pair: &Pair,
+    cuts: &Cuts,
+    tiles: &[Tile],
+    positions: &HashMap<Node, Point3>,
+) -> Vec<Side> {
+    let centroid = |t: &Tile| {
+        let [a, b, c] = t.nodes.map(|n| positions[&n].coords);
+        Point3::from((a + b + c) / 3.0)
+    };
+
+    let mut sides = tiles
+        .par_iter()
+        .map(|t| {
+            let p = centroid(t);
+            let axes = pair.axes[t.face];
+            let on = cuts.coplanar.get(&t.face)?.iter().find(|&&g| {
+                let c = pair.corners(g);
+                let signs = [0, 1, 2].map(|k| sign(orient2(axes, &c[k], &c[(k + 1) % 3], &p)));
+                place(signs) != Location::Outside
+            })?;
+            Some(if pair.normal(t.face).dot(&pair.normal(*on)) > 0.0 {
+                Side::Same
+            } else {
+                Side::Opposite
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let key = |t: &Tile, k: usize| {
+        let (a, b) = (t.nodes[k], t.nodes[(k + 1) % 3]);
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    };
+    let mut edges: HashMap<(Node, Node), Vec<usize>> = HashMap::new();
+    let mut meshes: HashMap<(Node, Node), [bool; 2]> = HashMap::new();
+    for (i, t) in tiles.iter().enumerate() {
+        for k in 0..3 {
+            edges.entry(key(t, k)).or_default().push(i);
+            meshes.entry(key(t, k)).or_default()[pair.is_first(t.face) as usize] = true;
+        }
+    }
+
+    let mut patch = vec![usize::MAX; tiles.len()];
+    let mut patches = Vec::new();
+    for seed in 0..tiles.len() {
+        if sides[seed].is_some() || patch[seed] != usize::MAX {
+            continue;
+        }
+
+        let id = patches.len();
+        let first = pair.is_first(tiles[seed].face);
+        let mut members = vec![seed];
+        patch[seed] = id;
+        let mut i = 0;
+        while i < members.len() {
+            let t = &tiles[members[i]];
+            i += 1;
+            for k in 0..3 {
+                let edge = key(t, k);
+                if meshes[&edge] == [true, true] {
+                    continue;
+                }
+                for &n in edges[&edge].iter() {
+                    if patch[n] == usize::MAX
+                        && sides[n].is_none()
+                        && pair.is_first(tiles[n].face) == first
+                    {
+                        patch[n] = id;
+                        members.push(n);
+                    }
+                }
+            }
+        }
+
+        let area = |i: &usize| {
+            let [a, b, c] = tiles[*i].nodes.map(|n| positions[&n]);
+            (b - a).cross(&(c - a)).norm()
+        };
+        let largest = *members
+            .iter()
+            .max_by(|i, j| area(i).total_cmp(&area(j)))
+            .unwrap();
+        patches.push((first, centroid(&tiles[largest])));
+    }
+
+    let inside = patches
+        .par_iter()
+        .map(|(first, p)| pair.winding(!first, p).abs() > 0.5)
+        .collect::<Vec<_>>();
+    for (i, side) in sides.iter_mut().enumerate() {
+        if side.is_none() {
+            *side = Some(if inside[patch[i]] {
+                Side::Inside
+            } else {
+                Side::Outside
+            });
+        }
+    }
+
+    sides.into_iter().map(|s| s.unwrap()).collect()
+}
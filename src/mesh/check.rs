@@ -0,0 +1,245 @@
+//! Inspection of the topology of a mesh, finding the elements which would trip up algorithms that
+//! expect a clean, closed, consistently wound surface.
+
+use super::repair::{directed_edges, edge_key, find, union, vertex_fans, welded_faces};
+use engeom::Point3;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Faces with an area smaller than this fraction of their longest edge squared are degenerate,
+/// which is to say their area is zero to within rounding.
+const DEGENERATE_RATIO: f64 = 1e-12;
+
+pub struct Topology {
+    /// Edges used by only one face
+    pub boundary_edges: Vec<[u32; 2]>,
+
+    /// The number of separate loops the boundary edges form
+    pub boundary_loops: usize,
+
+    /// Edges used by more than two faces
+    pub non_manifold_edges: Vec<[u32; 2]>,
+
+    /// Vertices where separate fans of faces meet
+    pub non_manifold_vertices: Vec<u32>,
+
+    /// Edges shared by two faces which both run the edge in the same direction
+    pub inconsistent_edges: Vec<[u32; 2]>,
+
+    /// Faces which use a vertex twice or have no area
+    pub degenerate_faces: Vec<usize>,
+
+    /// Pairs of faces, sharing no vertices, which cut through each other
+    pub self_intersections: Vec<[usize; 2]>,
+
+    /// Groups of faces connected through shared vertices
+    pub components: usize,
+
+    /// The number of vertices used by faces, less the number of edges, plus the number of faces
+    pub euler_characteristic: i64,
+}
+
+impl Topology {
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty() && self.non_manifold_vertices.is_empty()
+    }
+
+    /// The number of handles, which is only meaningful for a manifold, consistently wound mesh
+    pub fn genus(&self) -> Option<i64> {
+        if !self.is_manifold() || !self.inconsistent_edges.is_empty() {
+            return None;
+        }
+
+        // Each component contributes 2 - 2g - b to the Euler characteristic
+        let handles =
+            2 * self.components as i64 - self.boundary_loops as i64 - self.euler_characteristic;
+        Some(handles / 2)
+    }
+}
+
+/// Inspect the topology of a mesh. The search for self-intersections is the expensive part, and
+/// can be left out. Vertices at the same position are welded first, so the seams of a mesh with
+/// split vertices aren't reported as boundaries, and the elements found use the first vertex at
+/// each position.
+pub fn check(vertices: &[Point3], faces: &[[u32; 3]], intersections: bool) -> Topology {
+    let faces = &welded_faces(vertices, faces);
+    let mut edges: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (fi, f) in faces.iter().enumerate() {
+        for (a, b) in directed_edges(f) {
+            if a != b {
+                edges.entry(edge_key(a, b)).or_default().push(fi);
+            }
+        }
+    }
+
+    let mut boundary_edges = Vec::new();
+    let mut non_manifold_edges = Vec::new();
+    let mut inconsistent_edges = Vec::new();
+    for (&(a, b), around) in edges.iter() {
+        match around.len() {
+            1 => boundary_edges.push([a, b]),
+            2 => {
+                let runs = |f: usize| directed_edges(&faces[f]).contains(&(a, b));
+                if runs(around[0]) == runs(around[1]) {
+                    inconsistent_edges.push([a, b]);
+                }
+            }
+            _ => non_manifold_edges.push([a, b]),
+        }
+    }
+    boundary_edges.sort_unstable();
+    non_manifold_edges.sort_unstable();
+    inconsistent_edges.sort_unstable();
+
+    let mut vertex_faces = vec![Vec::new(); vertices.len()];
+    for (fi, f) in faces.iter().enumerate() {
+        for &v in f {
+            if !vertex_faces[v as usize].contains(&fi) {
+                vertex_faces[v as usize].push(fi);
+            }
+        }
+    }
+
+    let non_manifold_vertices = vertex_faces
+        .iter()
+        .enumerate()
+        .filter(|(v, around)| {
+            let fans = vertex_fans(faces, *v as u32, around);
+            fans.iter().any(|&root| root != fans[0])
+        })
+        .map(|(v, _)| v as u32)
+        .collect::<Vec<_>>();
+
+    let degenerate_faces = faces
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| is_degenerate(vertices, f))
+        .map(|(fi, _)| fi)
+        .collect::<Vec<_>>();
+
+    // Boundary loops are counted as the connected groups of boundary edges
+    let mut parents = (0..vertices.len()).collect::<Vec<_>>();
+    for &[a, b] in boundary_edges.iter() {
+        union(&mut parents, a as usize, b as usize);
+    }
+    let mut loop_roots = boundary_edges
+        .iter()
+        .map(|e| find(&mut parents, e[0] as usize))
+        .collect::<Vec<_>>();
+    loop_roots.sort_unstable();
+    loop_roots.dedup();
+
+    let mut parents = (0..vertices.len()).collect::<Vec<_>>();
+    for f in faces.iter() {
+        union(&mut parents, f[0] as usize, f[1] as usize);
+        union(&mut parents, f[0] as usize, f[2] as usize);
+    }
+    let mut roots = faces
+        .iter()
+        .map(|f| find(&mut parents, f[0] as usize))
+        .collect::<Vec<_>>();
+    roots.sort_unstable();
+    roots.dedup();
+
+    let used = vertex_faces.iter().filter(|f| !f.is_empty()).count();
+    let euler_characteristic = used as i64 - edges.len() as i64 + faces.len() as i64;
+
+    let self_intersections = if intersections {
+        self_intersections(vertices, faces)
+    } else {
+        Vec::new()
+    };
+
+    Topology {
+        boundary_edges,
+        boundary_loops: loop_roots.len(),
+        non_manifold_edges,
+        non_manifold_vertices,
+        inconsistent_edges,
+        degenerate_faces,
+        self_intersections,
+        components: roots.len(),
+        euler_characteristic,
+    }
+}
+
+fn is_degenerate(vertices: &[Point3], f: &[u32; 3]) -> bool {
+    if f[0] == f[1] || f[1] == f[2] || f[2] == f[0] {
+        return true;
+    }
+
+    let [a, b, c] = f.map(|i| vertices[i as usize]);
+    let longest = [a - b, b - c, c - a]
+        .iter()
+        .map(|e| e.norm_squared())
+        .fold(0.0, f64::max);
+    (b - a).cross(&(c - a)).norm() / 2.0 <= DEGENERATE_RATIO * longest
+}
+
+/// Find the pairs of faces which cut through each other. Faces sharing a vertex, and faces which
+/// only touch or which lie in the same plane, are not counted.
+fn self_intersections(vertices: &[Point3], faces: &[[u32; 3]]) -> Vec<[usize; 2]> {
+    let triangles = faces
+        .iter()
+        .map(|f| f.map(|i| vertices[i as usize]))
+        .collect::<Vec<_>>();
+    let bounds = triangles
+        .iter()
+        .map(|t| {
+            let lower = t[0].coords.inf(&t[1].coords).inf(&t[2].coords);
+            let upper = t[0].coords.sup(&t[1].coords).sup(&t[2].coords);
+            (lower, upper)
+        })
+        .collect::<Vec<_>>();
+
+    // Sweep along x, only testing faces whose bounds overlap
+    let mut order = (0..faces.len()).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| bounds[i].0.x.total_cmp(&bounds[j].0.x));
+
+    let (order, bounds, triangles) = (&order, &bounds, &triangles);
+    let mut found = (0..order.len())
+        .into_par_iter()
+        .flat_map_iter(|k| {
+            let i = order[k];
+            let (lower, upper) = bounds[i];
+            order[k + 1..]
+                .iter()
+                .copied()
+                .take_while(move |&j| bounds[j].0.x <= upper.x)
+                .filter(move |&j| {
+                    let (l, u) = bounds[j];
+                    l.y <= upper.y && u.y >= lower.y && l.z <= upper.z && u.z >= lower.z
+                })
+                .filter(|&j| !faces[i].iter().any(|v| faces[j].contains(v)))
+                .filter(|&j| cuts(&triangles[i], &triangles[j]))
+                .map(move |j| [i.min(j), i.max(j)])
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    found.sort_unstable();
+    found
+}
+
+/// Whether two triangles cut through each other, which happens when an edge of one passes through
+/// the inside of the other.
+fn cuts(t0: &[Point3; 3], t1: &[Point3; 3]) -> bool {
+    (0..3).any(|k| crosses(&t0[k], &t0[(k + 1) % 3], t1))
+        || (0..3).any(|k| crosses(&t1[k], &t1[(k + 1) % 3], t0))
+}
+
+/// Whether the segment from `p` to `q` passes strictly through the inside of a triangle.
+fn crosses(p: &Point3, q: &Point3, t: &[Point3; 3]) -> bool {
+    let normal = (t[1] - t[0]).cross(&(t[2] - t[0]));
+    let dp = normal.dot(&(p - t[0]));
+    let dq = normal.dot(&(q - t[0]));
+    if dp * dq >= 0.0 {
+        return false;
+    }
+
+    let x = p + (q - p) * (dp / (dp - dq));
+    (0..3).all(|k| {
+        let edge = t[(k + 1) % 3] - t[k];
+        edge.cross(&(x - t[k])).dot(&normal) > 0.0
+    })
+}
@@ -0,0 +1,224 @@
+//! Discrete curvature of a mesh, estimated at each vertex by fitting a quadratic height field to
+//! the vertices around it, measured in a frame aligned with the vertex normal.
+
+use crate::kdtree::KdTree3;
+use engeom::{Point3, Vector3};
+use parry3d_f64::na::{Matrix2, SMatrix, SVector};
+use rayon::prelude::*;
+
+/// The principal curvatures and directions at each vertex. Curvature is positive where the
+/// surface bends away from its normal, as it does everywhere on the outside of a sphere.
+pub struct Curvature {
+    /// The larger principal curvature
+    pub k1: Vec<f64>,
+
+    /// The smaller principal curvature
+    pub k2: Vec<f64>,
+
+    /// The direction of the larger principal curvature
+    pub d1: Vec<Vector3>,
+
+    /// The direction of the smaller principal curvature
+    pub d2: Vec<Vector3>,
+}
+
+impl Curvature {
+    pub fn mean(&self) -> Vec<f64> {
+        self.k1
+            .iter()
+            .zip(&self.k2)
+            .map(|(a, b)| (a + b) / 2.0)
+            .collect()
+    }
+
+    pub fn gaussian(&self) -> Vec<f64> {
+        self.k1.iter().zip(&self.k2).map(|(a, b)| a * b).collect()
+    }
+}
+
+/// Average per-vertex values over the three vertices of each face.
+pub fn face_average(values: &[f64], faces: &[[u32; 3]]) -> Vec<f64> {
+    faces
+        .iter()
+        .map(|f| f.iter().map(|&i| values[i as usize]).sum::<f64>() / 3.0)
+        .collect()
+}
+
+/// Vertex normals weighted by the angle each face makes at the vertex, which keeps the normal
+/// from leaning towards the side of the vertex with more, smaller faces.
+fn angle_weighted_normals(vertices: &[Point3], faces: &[[u32; 3]]) -> Vec<Vector3> {
+    let mut normals = vec![Vector3::zeros(); vertices.len()];
+    for f in faces.iter() {
+        let [a, b, c] = f.map(|i| vertices[i as usize]);
+        let Some(normal) = (b - a).cross(&(c - a)).try_normalize(0.0) else {
+            continue;
+        };
+
+        for k in 0..3 {
+            let p = vertices[f[k] as usize];
+            let e0 = vertices[f[(k + 1) % 3] as usize] - p;
+            let e1 = vertices[f[(k + 2) % 3] as usize] - p;
+            normals[f[k] as usize] += normal * e0.angle(&e1);
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|n| n.try_normalize(0.0).unwrap_or_else(Vector3::zeros))
+        .collect()
+}
+
+/// The vertices within `rings` edges of each vertex, not including the vertex itself.
+fn rings(count: usize, faces: &[[u32; 3]], rings: usize) -> Vec<Vec<usize>> {
+    let mut adjacent = vec![Vec::new(); count];
+    for f in faces.iter() {
+        for k in 0..3 {
+            let (a, b) = (f[k] as usize, f[(k + 1) % 3] as usize);
+            adjacent[a].push(b);
+            adjacent[b].push(a);
+        }
+    }
+    for a in adjacent.iter_mut() {
+        a.sort_unstable();
+        a.dedup();
+    }
+
+    (0..count)
+        .into_par_iter()
+        .map(|i| {
+            let mut found = vec![i];
+            let mut start = 0;
+            for _ in 0..rings {
+                let end = found.len();
+                for k in start..end {
+                    for &j in adjacent[found[k]].iter() {
+                        if !found.contains(&j) {
+                            found.push(j);
+                        }
+                    }
+                }
+                start = end;
+            }
+            found.remove(0);
+            found
+        })
+        .collect()
+}
+
+/// Estimate the curvature at every vertex. The neighborhood of a vertex is either the vertices
+/// within two edges of it, or the vertices within `radius` whose normals face the same way, which
+/// keeps the opposite side of a thin wall out of the fit. Vertices with too few neighbors for the
+/// fit get NaN curvatures and zero directions.
+pub fn curvature(vertices: &[Point3], faces: &[[u32; 3]], radius: Option<f64>) -> Curvature {
+    let normals = angle_weighted_normals(vertices, faces);
+    let neighbors = match radius {
+        None => rings(vertices.len(), faces, 2),
+        Some(r) => {
+            let tree = KdTree3::new(vertices);
+            (0..vertices.len())
+                .into_par_iter()
+                .map(|i| {
+                    tree.within(&vertices[i], r)
+                        .into_iter()
+                        .map(|(j, _)| j)
+                        .filter(|&j| j != i && normals[j].dot(&normals[i]) > 0.0)
+                        .collect()
+                })
+                .collect()
+        }
+    };
+
+    let fits = (0..vertices.len())
+        .into_par_iter()
+        .map(|i| fit(vertices, &neighbors[i], &vertices[i], &normals[i]))
+        .collect::<Vec<_>>();
+
+    let unknown = (f64::NAN, f64::NAN, Vector3::zeros(), Vector3::zeros());
+    let mut result = Curvature {
+        k1: Vec::with_capacity(fits.len()),
+        k2: Vec::with_capacity(fits.len()),
+        d1: Vec::with_capacity(fits.len()),
+        d2: Vec::with_capacity(fits.len()),
+    };
+    for (k1, k2, d1, d2) in fits.into_iter().map(|f| f.unwrap_or(unknown)) {
+        result.k1.push(k1);
+        result.k2.push(k2);
+        result.d1.push(d1);
+        result.d2.push(d2);
+    }
+    result
+}
+
+/// Fit h = a x^2 + b xy + c y^2 + d x + e y to the neighbors in a frame at `p` with `n` as its
+/// height axis, and find the principal curvatures and directions of the fitted surface at `p`.
+/// The linear terms let the fitted surface tilt away from `n` where the vertex normal is off.
+fn fit(
+    vertices: &[Point3],
+    neighbors: &[usize],
+    p: &Point3,
+    n: &Vector3,
+) -> Option<(f64, f64, Vector3, Vector3)> {
+    if neighbors.len() < 5 || n.norm_squared() == 0.0 {
+        return None;
+    }
+
+    let seed = if n.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = n.cross(&seed).normalize();
+    let v = n.cross(&u);
+
+    // Work in units of the neighborhood size to keep the normal equations well conditioned
+    let scale = neighbors
+        .iter()
+        .map(|&j| (vertices[j] - p).norm())
+        .sum::<f64>()
+        / neighbors.len() as f64;
+    if scale == 0.0 {
+        return None;
+    }
+
+    let mut ata = SMatrix::<f64, 5, 5>::zeros();
+    let mut atb = SVector::<f64, 5>::zeros();
+    for &j in neighbors.iter() {
+        let q = (vertices[j] - p) / scale;
+        let (x, y, h) = (q.dot(&u), q.dot(&v), q.dot(n));
+        let row = SVector::<f64, 5>::new(x * x, x * y, y * y, x, y);
+        ata += row * row.transpose();
+        atb += row * h;
+    }
+    let s = ata.cholesky()?.solve(&atb);
+    let (a, b, c, d, e) = (s[0] / scale, s[1] / scale, s[2] / scale, s[3], s[4]);
+
+    // The first and second fundamental forms of the height field at the origin, with the second
+    // taken against the normal of the fitted surface on the same side as `n`
+    let w = (1.0 + d * d + e * e).sqrt();
+    let first = Matrix2::new(1.0 + d * d, d * e, d * e, 1.0 + e * e);
+    let second = Matrix2::new(2.0 * a, b, b, 2.0 * c) / w;
+    let shape = first.try_inverse()? * second;
+
+    // The shape operator is not symmetric, but its eigenvalues are real
+    let half_trace = shape.trace() / 2.0;
+    let root = (half_trace * half_trace - shape.determinant())
+        .max(0.0)
+        .sqrt();
+    let lowest = half_trace - root;
+
+    let normal = (n - u * d - v * e).normalize();
+    let along = |x: f64, y: f64| (u + n * d) * x + (v + n * e) * y;
+    let candidates = [
+        along(shape[(0, 1)], lowest - shape[(0, 0)]),
+        along(lowest - shape[(1, 1)], shape[(1, 0)]),
+    ];
+    let d1 = candidates
+        .iter()
+        .max_by(|x, y| x.norm_squared().total_cmp(&y.norm_squared()))
+        .and_then(|t| t.try_normalize(1e-12))
+        .unwrap_or_else(|| along(1.0, 0.0).normalize());
+    let d2 = normal.cross(&d1);
+
+    // Bending away from the normal makes the height field curve downwards, so the signs flip
+    Some((-lowest, -(half_trace + root), d1, d2))
+}
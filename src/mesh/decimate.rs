@@ -0,0 +1,465 @@
+//! Mesh simplification by edge collapse with quadric error metrics (Garland and Heckbert,
+//! "Surface simplification using quadric error metrics", 1997). Every vertex carries a quadric
+//! which measures the sum of the squared distances to the planes of the original faces around it,
+//! and the edge whose two ends can be merged into one vertex for the lowest error is collapsed
+//! first, until the mesh is small enough or the next collapse would cost too much.
+//!
+//! Boundary edges add a plane through the edge at right angles to its face, so that open borders
+//! keep their shape instead of being pulled inwards.
+
+use engeom::{Point3, Vector3};
+use parry3d_f64::na::Matrix3;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+pub struct DecimateOptions {
+    /// Stop once the mesh has this many faces or fewer
+    pub target_faces: usize,
+
+    /// Don't make collapses whose quadric error is larger than the square of this
+    pub max_error: Option<f64>,
+
+    /// Keep every vertex on the boundary where it is
+    pub preserve_boundary: bool,
+
+    /// Keep every vertex on an edge where the faces meet at more than this angle where it is
+    pub feature_angle: Option<f64>,
+}
+
+#[derive(Clone, Copy)]
+struct Quadric {
+    a: Matrix3<f64>,
+    b: Vector3,
+    c: f64,
+}
+
+impl Quadric {
+    fn zero() -> Self {
+        Self {
+            a: Matrix3::zeros(),
+            b: Vector3::zeros(),
+            c: 0.0,
+        }
+    }
+
+    /// The squared distance to the plane through `point` with the unit `normal`
+    fn plane(normal: &Vector3, point: &Point3) -> Self {
+        let d = -normal.dot(&point.coords);
+        Self {
+            a: normal * normal.transpose(),
+            b: normal * d,
+            c: d * d,
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            a: self.a + other.a,
+            b: self.b + other.b,
+            c: self.c + other.c,
+        }
+    }
+
+    fn error(&self, p: &Point3) -> f64 {
+        let v = p.coords;
+        (v.dot(&(self.a * v)) + 2.0 * self.b.dot(&v) + self.c).max(0.0)
+    }
+
+    /// The position with the least error for merging the edge from `p0` to `p1`. The free minimum
+    /// is used when it is well defined and close to the edge, otherwise the best point along the
+    /// edge itself, which is the case on flat or gently curved patches.
+    fn best_position(&self, p0: &Point3, p1: &Point3) -> Point3 {
+        let e = p1 - p0;
+        let det = self.a.determinant();
+        let scale = self.a.norm();
+        if det.abs() > 1e-9 * scale * scale * scale
+            && let Some(inv) = self.a.try_inverse()
+        {
+            let p = Point3::from(-(inv * self.b));
+            if (p - p0).norm() <= 2.0 * e.norm() {
+                return p;
+            }
+        }
+
+        let ae = self.a * e;
+        let denom = e.dot(&ae);
+        let t = if denom > 0.0 {
+            (-(p0.coords.dot(&ae) + self.b.dot(&e)) / denom).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        p0 + e * t
+    }
+}
+
+/// A planned collapse of the vertex `remove` into the vertex `keep`, which moves to `position`.
+/// The stamps are the versions of the two vertices when the plan was made, and a plan is out of
+/// date once either vertex has changed.
+struct Collapse {
+    cost: f64,
+    keep: usize,
+    remove: usize,
+    position: Point3,
+    stamps: [u32; 2],
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+fn face_normal(points: &[Point3], f: &[usize; 3]) -> Vector3 {
+    (points[f[1]] - points[f[0]]).cross(&(points[f[2]] - points[f[0]]))
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+struct Decimator {
+    points: Vec<Point3>,
+    faces: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+
+    /// The faces around each vertex, which may include faces which have since been removed
+    vertex_faces: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    stamps: Vec<u32>,
+    heap: BinaryHeap<Collapse>,
+}
+
+impl Decimator {
+    fn new(vertices: &[Point3], faces: &[[u32; 3]], options: &DecimateOptions) -> Self {
+        let n = vertices.len();
+        let faces = faces
+            .iter()
+            .map(|f| f.map(|i| i as usize))
+            .collect::<Vec<_>>();
+
+        let mut vertex_faces = vec![Vec::new(); n];
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (fi, f) in faces.iter().enumerate() {
+            for k in 0..3 {
+                vertex_faces[f[k]].push(fi);
+                edge_faces
+                    .entry(edge_key(f[k], f[(k + 1) % 3]))
+                    .or_default()
+                    .push(fi);
+            }
+        }
+
+        let normals = faces
+            .iter()
+            .map(|f| face_normal(vertices, f).try_normalize(0.0))
+            .collect::<Vec<_>>();
+
+        let mut quadrics = vec![Quadric::zero(); n];
+        for (f, normal) in faces.iter().zip(normals.iter()) {
+            if let Some(normal) = normal {
+                let q = Quadric::plane(normal, &vertices[f[0]]);
+                for &i in f {
+                    quadrics[i] = quadrics[i].add(&q);
+                }
+            }
+        }
+
+        let mut locked = vec![false; n];
+        for (&(a, b), adjacent) in edge_faces.iter() {
+            if adjacent.len() == 1 {
+                // A plane through the boundary edge, standing upright on its face
+                if let Some(normal) = normals[adjacent[0]]
+                    && let Some(side) = (vertices[b] - vertices[a])
+                        .cross(&normal)
+                        .try_normalize(0.0)
+                {
+                    let q = Quadric::plane(&side, &vertices[a]);
+                    quadrics[a] = quadrics[a].add(&q);
+                    quadrics[b] = quadrics[b].add(&q);
+                }
+                if options.preserve_boundary {
+                    locked[a] = true;
+                    locked[b] = true;
+                }
+            } else if let Some(limit) = options.feature_angle
+                && let [f0, f1] = adjacent[..]
+                && let (Some(n0), Some(n1)) = (normals[f0], normals[f1])
+                && n0.angle(&n1) > limit
+            {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+
+        Self {
+            points: vertices.to_vec(),
+            alive: vec![true; faces.len()],
+            alive_count: faces.len(),
+            faces,
+            vertex_faces,
+            quadrics,
+            locked,
+            stamps: vec![0; n],
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    fn faces_around(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.vertex_faces[v]
+            .iter()
+            .copied()
+            .filter(|&f| self.alive[f])
+    }
+
+    fn neighbors(&self, v: usize) -> HashSet<usize> {
+        self.faces_around(v)
+            .flat_map(|f| self.faces[f])
+            .filter(|&i| i != v)
+            .collect()
+    }
+
+    fn is_boundary_edge(&self, a: usize, b: usize) -> bool {
+        self.faces_around(a)
+            .filter(|&f| self.faces[f].contains(&b))
+            .count()
+            == 1
+    }
+
+    fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.neighbors(v)
+            .into_iter()
+            .any(|n| self.is_boundary_edge(v, n))
+    }
+
+    fn plan(&mut self, a: usize, b: usize) {
+        let (keep, remove, position) = match (self.locked[a], self.locked[b]) {
+            (true, true) => return,
+            (true, false) => (a, b, self.points[a]),
+            (false, true) => (b, a, self.points[b]),
+            (false, false) => {
+                let q = self.quadrics[a].add(&self.quadrics[b]);
+                let p = q.best_position(&self.points[a], &self.points[b]);
+
+                // The closer vertex survives, so that it keeps the more fitting attributes
+                if (self.points[a] - p).norm() <= (self.points[b] - p).norm() {
+                    (a, b, p)
+                } else {
+                    (b, a, p)
+                }
+            }
+        };
+
+        let cost = self.quadrics[a].add(&self.quadrics[b]).error(&position);
+        self.heap.push(Collapse {
+            cost,
+            keep,
+            remove,
+            position,
+            stamps: [self.stamps[keep], self.stamps[remove]],
+        });
+    }
+
+    /// Whether collapsing the edge keeps the mesh manifold and doesn't turn any face over.
+    fn can_collapse(&self, keep: usize, remove: usize, position: &Point3) -> bool {
+        let opposite = self
+            .faces_around(remove)
+            .filter(|&f| self.faces[f].contains(&keep))
+            .flat_map(|f| self.faces[f])
+            .filter(|&i| i != keep && i != remove)
+            .collect::<HashSet<_>>();
+        if opposite.is_empty() || opposite.len() > 2 {
+            return false;
+        }
+
+        // The link condition, the only vertices next to both ends are the ones across the edge
+        let shared = self
+            .neighbors(keep)
+            .intersection(&self.neighbors(remove))
+            .count();
+        if shared != opposite.len() {
+            return false;
+        }
+
+        // An inner edge between two boundary vertices would pinch the mesh together
+        if opposite.len() == 2 && self.is_boundary_vertex(keep) && self.is_boundary_vertex(remove) {
+            return false;
+        }
+
+        let existing = self
+            .faces_around(keep)
+            .map(|f| {
+                let mut s = self.faces[f];
+                s.sort_unstable();
+                s
+            })
+            .collect::<HashSet<_>>();
+
+        for v in [keep, remove] {
+            for f in self.faces_around(v) {
+                let face = self.faces[f];
+                if face.contains(&keep) && face.contains(&remove) {
+                    continue;
+                }
+
+                let old = face_normal(&self.points, &face);
+                let moved = face.map(|i| {
+                    if i == keep || i == remove {
+                        None
+                    } else {
+                        Some(i)
+                    }
+                });
+                let corners = moved.map(|i| i.map_or(*position, |i| self.points[i]));
+                let new = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+                if new.dot(&old) <= 0.0 || new.norm_squared() <= 1e-12 * old.norm_squared() {
+                    return false;
+                }
+
+                // Two faces with the same corners would be left back to back
+                if v == remove {
+                    let mut s = face.map(|i| if i == remove { keep } else { i });
+                    s.sort_unstable();
+                    if existing.contains(&s) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn collapse(&mut self, keep: usize, remove: usize, position: Point3) {
+        for f in std::mem::take(&mut self.vertex_faces[remove]) {
+            if !self.alive[f] {
+                continue;
+            }
+            if self.faces[f].contains(&keep) {
+                self.alive[f] = false;
+                self.alive_count -= 1;
+            } else {
+                for i in self.faces[f].iter_mut() {
+                    if *i == remove {
+                        *i = keep;
+                    }
+                }
+                self.vertex_faces[keep].push(f);
+            }
+        }
+
+        let alive = &self.alive;
+        self.vertex_faces[keep].retain(|&f| alive[f]);
+        self.points[keep] = position;
+        self.quadrics[keep] = self.quadrics[keep].add(&self.quadrics[remove]);
+        self.stamps[keep] += 1;
+        self.stamps[remove] += 1;
+
+        for n in self.neighbors(keep) {
+            self.plan(keep, n);
+        }
+    }
+
+    fn run(&mut self, options: &DecimateOptions) {
+        for f in 0..self.faces.len() {
+            for k in 0..3 {
+                let (a, b) = (self.faces[f][k], self.faces[f][(k + 1) % 3]);
+                // Each inner edge is shared by two faces, plan it from only one of them
+                if a < b || self.is_boundary_edge(a, b) {
+                    self.plan(a, b);
+                }
+            }
+        }
+
+        let limit = options.max_error.map_or(f64::INFINITY, |e| e * e);
+        while self.alive_count > options.target_faces {
+            let Some(c) = self.heap.pop() else {
+                break;
+            };
+            if c.stamps != [self.stamps[c.keep], self.stamps[c.remove]] {
+                continue;
+            }
+            if c.cost > limit {
+                break;
+            }
+            if self.can_collapse(c.keep, c.remove, &c.position) {
+                self.collapse(c.keep, c.remove, c.position);
+            }
+        }
+    }
+}
+
+/// Decimate a mesh by collapsing edges. Returns the remaining vertices, their faces, and the
+/// index in the original mesh of each remaining vertex, which is the vertex that survived each of
+/// the collapses it took part in.
+pub fn decimate(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    options: &DecimateOptions,
+) -> (Vec<Point3>, Vec<[u32; 3]>, Vec<usize>) {
+    let mut decimator = Decimator::new(vertices, faces, options);
+    decimator.run(options);
+
+    let mut index = vec![None; vertices.len()];
+    let mut kept = Vec::new();
+    for (f, face) in decimator.faces.iter().enumerate() {
+        if decimator.alive[f] {
+            for &i in face {
+                index[i] = Some(0);
+            }
+        }
+    }
+    for (i, slot) in index.iter_mut().enumerate() {
+        if let Some(s) = slot {
+            *s = kept.len() as u32;
+            kept.push(i);
+        }
+    }
+
+    let points = kept.iter().map(|&i| decimator.points[i]).collect();
+    let faces = decimator
+        .faces
+        .iter()
+        .zip(decimator.alive.iter())
+        .filter(|(_, alive)| **alive)
+        .map(|(f, _)| f.map(|i| index[i].unwrap()))
+        .collect();
+
+    (points, faces, kept)
+}
+
+/// Points spread over the surface of a mesh for measuring how far it is from another one: the
+/// vertices, the midpoint of every edge and the centroid of every face. Measuring from the
+/// vertices alone misses places where the middle of a large face has moved off the other surface.
+pub fn surface_samples(vertices: &[Point3], faces: &[[u32; 3]]) -> Vec<Point3> {
+    let mut samples = vertices.to_vec();
+    let mut edges = HashSet::new();
+    for face in faces {
+        let [a, b, c] = face.map(|i| vertices[i as usize].coords);
+        samples.push(Point3::from((a + b + c) / 3.0));
+
+        for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+            let (vi, vj) = (face[i] as usize, face[j] as usize);
+            if edges.insert(edge_key(vi, vj)) {
+                let (pi, pj) = (vertices[vi], vertices[vj]);
+                samples.push(pi + (pj - pi) / 2.0);
+            }
+        }
+    }
+    samples
+}
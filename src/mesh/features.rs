@@ -0,0 +1,119 @@
+//! View independent feature lines of a mesh: sharp creases, open boundaries and the borders
+//! between labeled regions of faces, chained together into polylines.
+
+use super::repair::{directed_edges, edge_key, welded_faces};
+use engeom::{Point3, Vector3};
+use std::collections::BTreeMap;
+
+pub struct FeatureOptions {
+    /// Edges where the faces meet at more than this angle are creases
+    pub angle: Option<f64>,
+
+    /// Whether edges used by only one face are included
+    pub boundaries: bool,
+
+    /// A label for each face, with the edges between faces of different labels included
+    pub regions: Option<Vec<i64>>,
+}
+
+/// Find the feature edges of a mesh. Edges shared by more than two faces are always treated as
+/// creases when `angle` is given. Vertices at the same position are welded first, so a seam of
+/// split vertices is not mistaken for a pair of boundaries, and the edges use the first vertex at
+/// each position.
+pub fn feature_edges(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    options: &FeatureOptions,
+) -> Vec<[u32; 2]> {
+    let faces = welded_faces(vertices, faces);
+
+    // A sorted map so that the edges, and the chains made from them, come out in a stable order
+    let mut edges: BTreeMap<(u32, u32), Vec<usize>> = BTreeMap::new();
+    for (fi, f) in faces.iter().enumerate() {
+        for (a, b) in directed_edges(f) {
+            if a != b {
+                edges.entry(edge_key(a, b)).or_default().push(fi);
+            }
+        }
+    }
+
+    let normal = |f: usize| -> Vector3 {
+        let [a, b, c] = faces[f].map(|i| vertices[i as usize]);
+        (b - a).cross(&(c - a)).normalize()
+    };
+
+    let is_feature = |(a, b): (u32, u32), around: &[usize]| {
+        if around.len() == 1 {
+            return options.boundaries;
+        }
+
+        if let Some(regions) = &options.regions
+            && around.iter().any(|&f| regions[f] != regions[around[0]])
+        {
+            return true;
+        }
+
+        match (options.angle, around) {
+            (None, _) => false,
+            (Some(limit), &[f0, f1]) => {
+                // A neighbor wound the other way has its normal turned over to compare
+                let runs = |f: usize| directed_edges(&faces[f]).contains(&(a, b));
+                let sign = if runs(f0) == runs(f1) { -1.0 } else { 1.0 };
+                let angle = normal(f0).angle(&(normal(f1) * sign));
+                angle.is_nan() || angle > limit
+            }
+            (Some(_), _) => true,
+        }
+    };
+
+    edges
+        .iter()
+        .filter(|(e, around)| is_feature(**e, around))
+        .map(|((a, b), _)| [*a, *b])
+        .collect()
+}
+
+/// Chain edges into polylines of vertex indices. Chains run between vertices where the number of
+/// edges is not two, and loops with no such vertex come back to their first vertex at the end.
+pub fn chain(edges: &[[u32; 2]]) -> Vec<Vec<u32>> {
+    let mut adjacent: BTreeMap<u32, Vec<(u32, usize)>> = BTreeMap::new();
+    for (i, &[a, b]) in edges.iter().enumerate() {
+        adjacent.entry(a).or_default().push((b, i));
+        adjacent.entry(b).or_default().push((a, i));
+    }
+
+    let mut used = vec![false; edges.len()];
+    let walk = |start: u32, first: (u32, usize), used: &mut [bool]| {
+        let mut chain = vec![start];
+        let (mut current, mut edge) = first;
+        loop {
+            used[edge] = true;
+            chain.push(current);
+            if current == start || adjacent[&current].len() != 2 {
+                break;
+            }
+            match adjacent[&current].iter().find(|(_, e)| !used[*e]) {
+                Some(&(next, e)) => (current, edge) = (next, e),
+                None => break,
+            }
+        }
+        chain
+    };
+
+    let mut chains = Vec::new();
+    for (&v, around) in adjacent.iter().filter(|(_, around)| around.len() != 2) {
+        for &step in around.iter() {
+            if !used[step.1] {
+                chains.push(walk(v, step, &mut used));
+            }
+        }
+    }
+
+    for (i, &[a, b]) in edges.iter().enumerate() {
+        if !used[i] {
+            chains.push(walk(a, (b, i), &mut used));
+        }
+    }
+
+    chains
+}
@@ -0,0 +1,122 @@
+//! Triangulation of organized grids of points, such as range images or the rows of a scanning
+//! sensor, where samples next to each other in the grid are taken to be next to each other on the
+//! surface. Samples with a NaN coordinate are missing and leave a gap.
+
+use engeom::{Point3, Vector3};
+use numpy::ndarray::ArrayView3;
+use std::f64::consts::PI;
+
+pub struct GridOptions {
+    /// Triangles with an edge longer than this are left out
+    pub max_edge_length: Option<f64>,
+
+    /// Triangles turned further than this from facing the viewpoint are left out
+    pub max_angle: Option<f64>,
+
+    /// When set, every triangle is wound to face this point
+    pub viewpoint: Option<Point3>,
+}
+
+impl GridOptions {
+    /// Whether a triangle passes the cutoffs, and whether it has to be flipped to face the
+    /// viewpoint.
+    fn check(&self, a: &Point3, b: &Point3, c: &Point3) -> Option<bool> {
+        if let Some(limit) = self.max_edge_length
+            && [a - b, b - c, c - a].iter().any(|e| e.norm() > limit)
+        {
+            return None;
+        }
+
+        let normal = (b - a).cross(&(c - a));
+        if normal.norm_squared() == 0.0 {
+            return None;
+        }
+
+        let Some(viewpoint) = self.viewpoint else {
+            return Some(false);
+        };
+        let center = Point3::from((a.coords + b.coords + c.coords) / 3.0);
+        let sight: Vector3 = viewpoint - center;
+        let flip = normal.dot(&sight) < 0.0;
+
+        // The angle to the line of sight, whichever way the triangle is wound
+        let angle = normal.angle(&sight);
+        if self
+            .max_angle
+            .is_some_and(|limit| angle.min(PI - angle) > limit)
+        {
+            return None;
+        }
+
+        Some(flip)
+    }
+}
+
+/// Triangulate a rows x cols x 3 grid of points. Each cell of four neighboring samples is split
+/// into two triangles along its shorter diagonal, or into one triangle if one sample is missing.
+/// Without a viewpoint, triangles are wound counter-clockwise when looking at the grid with the
+/// columns increasing to the right and the rows increasing upwards. Only samples which end up in
+/// a triangle become vertices, in row major order.
+pub fn triangulate(grid: &ArrayView3<f64>, options: &GridOptions) -> (Vec<Point3>, Vec<[u32; 3]>) {
+    let (rows, cols, _) = grid.dim();
+    let sample = |r: usize, c: usize| {
+        let p = Point3::new(grid[[r, c, 0]], grid[[r, c, 1]], grid[[r, c, 2]]);
+        p.iter().all(|v| v.is_finite()).then_some(p)
+    };
+
+    let mut triangles = Vec::new();
+    for r in 0..rows.saturating_sub(1) {
+        for c in 0..cols.saturating_sub(1) {
+            // The corners of the cell in counter-clockwise order
+            let corners = [(r, c), (r, c + 1), (r + 1, c + 1), (r + 1, c)];
+            let points = corners.map(|(r, c)| sample(r, c));
+            let valid = (0..4).filter(|&i| points[i].is_some()).collect::<Vec<_>>();
+
+            let split: &[[usize; 3]] = match valid.len() {
+                4 => {
+                    let p = points.map(|p| p.unwrap());
+                    if (p[0] - p[2]).norm() <= (p[1] - p[3]).norm() {
+                        &[[0, 1, 2], [0, 2, 3]]
+                    } else {
+                        &[[0, 1, 3], [1, 2, 3]]
+                    }
+                }
+                3 => &[[valid[0], valid[1], valid[2]]],
+                _ => &[],
+            };
+
+            for t in split {
+                let [a, b, c] = t.map(|i| points[i].unwrap());
+                if let Some(flip) = options.check(&a, &b, &c) {
+                    let [i, j, k] = t.map(|i| corners[i]);
+                    triangles.push(if flip { [i, k, j] } else { [i, j, k] });
+                }
+            }
+        }
+    }
+
+    // Number the samples which are used by a triangle in row major order
+    let mut index = vec![None; rows * cols];
+    for t in triangles.iter() {
+        for (r, c) in t {
+            index[r * cols + c] = Some(0);
+        }
+    }
+
+    let mut vertices = Vec::new();
+    for r in 0..rows {
+        for c in 0..cols {
+            if let Some(i) = index[r * cols + c].as_mut() {
+                *i = vertices.len() as u32;
+                vertices.push(sample(r, c).unwrap());
+            }
+        }
+    }
+
+    let faces = triangles
+        .iter()
+        .map(|t| t.map(|(r, c)| index[r * cols + c].unwrap()))
+        .collect();
+
+    (vertices, faces)
+}
@@ -0,0 +1,495 @@
+//! Filling holes in a mesh. Each hole is a loop of boundary edges, and it is closed with a patch
+//! of triangles wound to agree with the faces around it.
+//!
+//! * A flat fill triangulates the loop by ear clipping in its plane of best fit.
+//! * A minimum area fill finds the triangulation of the loop with the least total area by dynamic
+//!   programming (Barequet and Sharir, 1995), which also works for loops which aren't planar. It
+//!   takes time cubic in the length of the loop, so longer loops fall back to the flat fill.
+//! * A curvature fill starts from the minimum area triangulation, refines it until its edges are
+//!   about as long as the edges of the loop, and then fairs the new vertices by relaxing the
+//!   bi-Laplacian of the surface (Liepa, "Filling holes in meshes", 2003), so the patch carries on
+//!   the curvature of the surface around it.
+
+use super::repair::{edge_key, welded_faces, MeshParts};
+use engeom::{Point3, Vector3};
+use parry3d_f64::na::Matrix3;
+use std::collections::{HashMap, HashSet};
+
+/// The most edges a loop can have to be triangulated by `minimum_area`
+const MAX_MINIMUM_AREA_EDGES: usize = 400;
+
+#[derive(Clone, Copy)]
+pub enum FillMethod {
+    Flat,
+    MinimumArea,
+    Curvature,
+}
+
+pub struct FillOptions {
+    pub method: FillMethod,
+
+    /// Holes whose loop is longer than this are left open
+    pub max_perimeter: Option<f64>,
+
+    /// Holes with more edges than this are left open
+    pub max_edges: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct FillCounts {
+    pub filled: usize,
+    pub skipped: usize,
+    pub faces_added: usize,
+    pub vertices_added: usize,
+}
+
+/// The loops of boundary edges, each in the direction the edges run in their faces. The faces
+/// should be welded first, or the seams of split vertices are found as loops too.
+fn boundary_loops(faces: &[[u32; 3]]) -> Vec<Vec<u32>> {
+    let directed = faces
+        .iter()
+        .flat_map(|f| [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+        .collect::<HashSet<_>>();
+
+    let mut next: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut starts = directed
+        .iter()
+        .filter(|(a, b)| !directed.contains(&(*b, *a)))
+        .copied()
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    for &(a, b) in starts.iter() {
+        next.entry(a).or_default().push(b);
+    }
+
+    let mut loops = Vec::new();
+    for (start, _) in starts {
+        let mut current = start;
+        let mut ring = Vec::new();
+        while let Some(b) = next.get_mut(&current).and_then(|n| n.pop()) {
+            ring.push(current);
+            current = b;
+            if current == start {
+                break;
+            }
+        }
+
+        if current == start && ring.len() >= 3 {
+            loops.push(ring);
+        }
+    }
+
+    loops
+}
+
+fn area(a: &Point3, b: &Point3, c: &Point3) -> f64 {
+    (b - a).cross(&(c - a)).norm() / 2.0
+}
+
+/// The triangulation of the polygon with the least total area, as triangles of indices into the
+/// polygon in increasing order.
+fn minimum_area(points: &[Point3]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    let mut weight = vec![vec![0.0; n]; n];
+    let mut best = vec![vec![0; n]; n];
+
+    for span in 2..n {
+        for i in 0..n - span {
+            let k = i + span;
+            let (m, w) = (i + 1..k)
+                .map(|m| {
+                    let w = weight[i][m] + weight[m][k] + area(&points[i], &points[m], &points[k]);
+                    (m, w)
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            weight[i][k] = w;
+            best[i][k] = m;
+        }
+    }
+
+    let mut triangles = Vec::with_capacity(n - 2);
+    let mut stack = vec![(0, n - 1)];
+    while let Some((i, k)) = stack.pop() {
+        if k - i < 2 {
+            continue;
+        }
+        let m = best[i][k];
+        triangles.push([i, m, k]);
+        stack.push((i, m));
+        stack.push((m, k));
+    }
+    triangles
+}
+
+/// Triangulate the polygon with the given method, or return `None` if it can't be. Loops too long
+/// for the minimum area triangulation are clipped flat instead.
+fn triangulate(points: &[Point3], method: FillMethod) -> Option<Vec<[usize; 3]>> {
+    let small = points.len() <= MAX_MINIMUM_AREA_EDGES;
+    match method {
+        FillMethod::Flat => flat(points).or_else(|| small.then(|| minimum_area(points))),
+        FillMethod::MinimumArea | FillMethod::Curvature if small => Some(minimum_area(points)),
+        FillMethod::MinimumArea | FillMethod::Curvature => flat(points),
+    }
+}
+
+/// Triangulate the polygon by ear clipping in its plane of best fit, as triangles of indices into
+/// the polygon in increasing order. Returns `None` if the polygon overlaps itself in that plane.
+fn flat(points: &[Point3]) -> Option<Vec<[usize; 3]>> {
+    let n = points.len() as f64;
+    let center = points
+        .iter()
+        .fold(Vector3::zeros(), |acc, p| acc + p.coords)
+        / n;
+    let covariance = points.iter().fold(Matrix3::zeros(), |acc, p| {
+        let d = p.coords - center;
+        acc + d * d.transpose()
+    });
+    let eigen = covariance.symmetric_eigen();
+    let normal = eigen
+        .eigenvectors
+        .column(eigen.eigenvalues.imin())
+        .into_owned();
+    let u = normal
+        .cross(&Vector3::x())
+        .try_normalize(1e-6)
+        .unwrap_or_else(|| normal.cross(&Vector3::y()).normalize());
+    let v = normal.cross(&u);
+    let flat = points
+        .iter()
+        .map(|p| {
+            let d = p.coords - center;
+            [d.dot(&u), d.dot(&v)]
+        })
+        .collect::<Vec<_>>();
+
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (flat[a], flat[b], flat[c]);
+        (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+    };
+    let signed_area = (0..flat.len())
+        .map(|i| {
+            let j = (i + 1) % flat.len();
+            flat[i][0] * flat[j][1] - flat[j][0] * flat[i][1]
+        })
+        .sum::<f64>();
+    let sign = signed_area.signum();
+
+    let mut remaining = (0..points.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&k| {
+            let (a, b, c) = (
+                remaining[(k + m - 1) % m],
+                remaining[k],
+                remaining[(k + 1) % m],
+            );
+            if cross(a, b, c) * sign <= 0.0 {
+                return false;
+            }
+            remaining.iter().all(|&p| {
+                p == a
+                    || p == b
+                    || p == c
+                    || cross(a, b, p) * sign < 0.0
+                    || cross(b, c, p) * sign < 0.0
+                    || cross(c, a, p) * sign < 0.0
+            })
+        })?;
+
+        let (a, b, c) = (
+            remaining[(ear + m - 1) % m],
+            remaining[ear],
+            remaining[(ear + 1) % m],
+        );
+        let mut t = [a, b, c];
+        t.sort_unstable();
+        triangles.push(t);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    Some(triangles)
+}
+
+/// A fill patch being refined, with its triangles wound like the rest of the mesh.
+struct Patch {
+    faces: Vec<[u32; 3]>,
+
+    /// Edges on the loop, which belong to the mesh around the hole and can't be changed
+    rim: HashSet<(u32, u32)>,
+}
+
+impl Patch {
+    fn faces_with_edge(&self, a: u32, b: u32) -> Vec<usize> {
+        (0..self.faces.len())
+            .filter(|&f| self.faces[f].contains(&a) && self.faces[f].contains(&b))
+            .collect()
+    }
+
+    fn inner_edges(&self) -> Vec<(u32, u32)> {
+        let mut edges = self
+            .faces
+            .iter()
+            .flat_map(|f| [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+            .map(|(a, b)| edge_key(a, b))
+            .filter(|e| !self.rim.contains(e))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Split the inner edges longer than `limit` at their midpoints, adding the new vertices to
+    /// the mesh. Returns whether any edge was split.
+    fn split_long(&mut self, parts: &mut MeshParts, limit: f64, sources: &[usize]) -> bool {
+        let mut split = false;
+        for (a, b) in self.inner_edges() {
+            let (pa, pb) = (parts.vertices[a as usize], parts.vertices[b as usize]);
+            if (pa - pb).norm() <= limit {
+                continue;
+            }
+
+            let m = parts.vertices.len() as u32;
+            parts
+                .vertices
+                .push(Point3::from((pa.coords + pb.coords) / 2.0));
+            parts.sources.push(sources.to_vec());
+            for f in self.faces_with_edge(a, b) {
+                let face = self.faces[f];
+                let k = face.iter().position(|&i| i == a).unwrap();
+                let y = if face[(k + 1) % 3] == b { b } else { a };
+                let c = face.iter().copied().find(|&i| i != a && i != b).unwrap();
+                for i in self.faces[f].iter_mut().filter(|i| **i == y) {
+                    *i = m;
+                }
+                self.faces.push([m, y, c]);
+            }
+            split = true;
+        }
+        split
+    }
+
+    /// Flip inner edges whose two opposite angles add up to more than a half turn, which moves
+    /// the patch towards a Delaunay triangulation.
+    fn relax(&mut self, vertices: &[Point3]) {
+        for (a, b) in self.inner_edges() {
+            let [f0, f1] = self.faces_with_edge(a, b)[..] else {
+                continue;
+            };
+            let face = self.faces[f0];
+            let k = face.iter().position(|&i| i == a).unwrap();
+            let (a, b) = if face[(k + 1) % 3] == b {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let c = face.iter().copied().find(|&i| i != a && i != b).unwrap();
+            let d = self.faces[f1]
+                .iter()
+                .copied()
+                .find(|&i| i != a && i != b)
+                .unwrap();
+            if c == d || !self.faces_with_edge(c, d).is_empty() {
+                continue;
+            }
+
+            let p = |i: u32| vertices[i as usize];
+            let opposite =
+                (p(a) - p(c)).angle(&(p(b) - p(c))) + (p(a) - p(d)).angle(&(p(b) - p(d)));
+            if opposite > std::f64::consts::PI + 1e-9 {
+                self.faces[f0] = [a, d, c];
+                self.faces[f1] = [d, b, c];
+            }
+        }
+    }
+}
+
+/// Move the new vertices of the patch so that the Laplacian of the surface around them is as small
+/// as possible in the least squares sense, which makes the bi-Laplacian vanish over the patch. The
+/// rows of the Laplacian reach one ring past the loop, so the patch blends into the curvature of
+/// the surface around the hole. The normal equations are solved by conjugate gradients.
+fn fair(parts: &mut MeshParts, free: &[usize]) {
+    let column = free
+        .iter()
+        .enumerate()
+        .map(|(k, &i)| (i, k))
+        .collect::<HashMap<_, _>>();
+
+    // The rows are the free vertices and the vertices next to them, which need their full rings
+    let mut rows = parts
+        .faces
+        .iter()
+        .filter(|f| f.iter().any(|i| column.contains_key(&(*i as usize))))
+        .flat_map(|f| f.map(|i| i as usize))
+        .collect::<Vec<_>>();
+    rows.sort_unstable();
+    rows.dedup();
+    let row_index = rows
+        .iter()
+        .enumerate()
+        .map(|(k, &i)| (i, k))
+        .collect::<HashMap<_, _>>();
+
+    let mut rings = vec![HashSet::new(); rows.len()];
+    for f in parts.faces.iter() {
+        for &i in f {
+            if let Some(&r) = row_index.get(&(i as usize)) {
+                rings[r].extend(f.iter().filter(|&&j| j != i).map(|&j| j as usize));
+            }
+        }
+    }
+    let rings = rings
+        .into_iter()
+        .map(|r| r.into_iter().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // L_F v, the Laplacian rows applied to values at the free vertices only
+    let apply = |v: &[f64]| -> Vec<f64> {
+        rows.iter()
+            .zip(rings.iter())
+            .map(|(&i, ring)| {
+                let own = column.get(&i).map_or(0.0, |&k| v[k] * ring.len() as f64);
+                own - ring
+                    .iter()
+                    .filter_map(|j| column.get(j))
+                    .map(|&k| v[k])
+                    .sum::<f64>()
+            })
+            .collect()
+    };
+    // L_F^T w
+    let apply_t = |w: &[f64]| -> Vec<f64> {
+        let mut out = vec![0.0; free.len()];
+        for ((&i, ring), &wr) in rows.iter().zip(rings.iter()).zip(w.iter()) {
+            if let Some(&k) = column.get(&i) {
+                out[k] += wr * ring.len() as f64;
+            }
+            for &k in ring.iter().filter_map(|j| column.get(j)) {
+                out[k] -= wr;
+            }
+        }
+        out
+    };
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+
+    for axis in 0..3 {
+        // The part of the Laplacian which comes from the fixed vertices
+        let fixed = rows
+            .iter()
+            .zip(rings.iter())
+            .map(|(&i, ring)| {
+                let own = if column.contains_key(&i) {
+                    0.0
+                } else {
+                    parts.vertices[i][axis] * ring.len() as f64
+                };
+                own - ring
+                    .iter()
+                    .filter(|j| !column.contains_key(j))
+                    .map(|&j| parts.vertices[j][axis])
+                    .sum::<f64>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut x = free
+            .iter()
+            .map(|&i| parts.vertices[i][axis])
+            .collect::<Vec<_>>();
+        let b = apply_t(&fixed).iter().map(|v| -v).collect::<Vec<_>>();
+        let ax = apply_t(&apply(&x));
+        let mut r = b.iter().zip(ax).map(|(b, a)| b - a).collect::<Vec<_>>();
+        let mut p = r.clone();
+        let mut rr = dot(&r, &r);
+        let tolerance = 1e-24 * dot(&b, &b).max(1e-300);
+
+        for _ in 0..10 * free.len() + 100 {
+            if rr <= tolerance {
+                break;
+            }
+            let ap = apply_t(&apply(&p));
+            let alpha = rr / dot(&p, &ap);
+            for k in 0..x.len() {
+                x[k] += alpha * p[k];
+                r[k] -= alpha * ap[k];
+            }
+            let next = dot(&r, &r);
+            for k in 0..p.len() {
+                p[k] = r[k] + next / rr * p[k];
+            }
+            rr = next;
+        }
+
+        for (&i, v) in free.iter().zip(x) {
+            parts.vertices[i][axis] = v;
+        }
+    }
+}
+
+impl MeshParts {
+    /// Fill the holes in the mesh which are within the size limits and can be triangulated.
+    pub fn fill_holes(&mut self, options: &FillOptions) -> FillCounts {
+        let mut counts = FillCounts::default();
+
+        for ring in boundary_loops(&welded_faces(&self.vertices, &self.faces)) {
+            let points = ring
+                .iter()
+                .map(|&i| self.vertices[i as usize])
+                .collect::<Vec<_>>();
+            let perimeter = (0..points.len())
+                .map(|i| (points[(i + 1) % points.len()] - points[i]).norm())
+                .sum::<f64>();
+            if options.max_edges.is_some_and(|m| ring.len() > m)
+                || options.max_perimeter.is_some_and(|m| perimeter > m)
+            {
+                counts.skipped += 1;
+                continue;
+            }
+
+            let Some(triangles) = triangulate(&points, options.method) else {
+                counts.skipped += 1;
+                continue;
+            };
+
+            // The loop runs the way its edges run in the faces outside it, so the patch runs it
+            // backwards
+            let mut patch = Patch {
+                faces: triangles
+                    .iter()
+                    .map(|t| [ring[t[2]], ring[t[1]], ring[t[0]]])
+                    .collect(),
+                rim: (0..ring.len())
+                    .map(|i| edge_key(ring[i], ring[(i + 1) % ring.len()]))
+                    .collect(),
+            };
+
+            let first_new = self.vertices.len();
+            if let FillMethod::Curvature = options.method {
+                let limit = perimeter / ring.len() as f64 * 4.0 / 3.0;
+                let sources = ring
+                    .iter()
+                    .flat_map(|&i| self.sources[i as usize].iter().copied())
+                    .collect::<Vec<_>>();
+                for _ in 0..20 {
+                    if !patch.split_long(self, limit, &sources) {
+                        break;
+                    }
+                    patch.relax(&self.vertices);
+                }
+            }
+
+            counts.filled += 1;
+            counts.faces_added += patch.faces.len();
+            counts.vertices_added += self.vertices.len() - first_new;
+            self.faces.extend(patch.faces);
+
+            let free = (first_new..self.vertices.len()).collect::<Vec<_>>();
+            if !free.is_empty() {
+                fair(self, &free);
+            }
+        }
+
+        counts
+    }
+}
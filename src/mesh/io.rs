@@ -0,0 +1,102 @@
+//! Readers and writers for mesh file formats beyond the STL support provided by `engeom::io`.
+//! Each reader produces a `MeshData` holding the raw vertices and triangles along with whatever
+//! per-vertex attributes the file contained, so that nothing is lost before it reaches the mesh.
+
+mod obj;
+mod ply;
+mod three_mf;
+
+pub use obj::{read_obj, write_obj};
+pub use ply::{read_ply, write_ply};
+pub use three_mf::{read_3mf, write_3mf};
+
+use super::attributes::{position_key, VertexAttributes};
+use engeom::Point3;
+use std::collections::HashMap;
+use std::io;
+
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub vertices: Vec<Point3>,
+    pub faces: Vec<[u32; 3]>,
+    pub attributes: VertexAttributes,
+}
+
+impl MeshData {
+    /// Merge vertices which have exactly the same position, keeping the attributes of the first
+    /// occurrence and re-indexing the faces to match.
+    pub fn merge_duplicates(&mut self) {
+        let mut lookup = HashMap::with_capacity(self.vertices.len());
+        let mut keep = Vec::with_capacity(self.vertices.len());
+        let mut map = Vec::with_capacity(self.vertices.len());
+
+        for (i, p) in self.vertices.iter().enumerate() {
+            let next = keep.len();
+            let index = *lookup.entry(position_key(p)).or_insert(next);
+            if index == next {
+                keep.push(Some(i));
+            }
+            map.push(index as u32);
+        }
+
+        if keep.len() == self.vertices.len() {
+            return;
+        }
+
+        self.vertices = keep.iter().map(|i| self.vertices[i.unwrap()]).collect();
+        self.attributes = self.attributes.gather(&keep);
+        for face in self.faces.iter_mut() {
+            *face = [
+                map[face[0] as usize],
+                map[face[1] as usize],
+                map[face[2] as usize],
+            ];
+        }
+    }
+
+    /// Remove faces which reference the same vertex more than once or which have zero area.
+    pub fn delete_degenerate(&mut self) {
+        let vertices = &self.vertices;
+        self.faces.retain(|f| {
+            if f[0] == f[1] || f[1] == f[2] || f[0] == f[2] {
+                return false;
+            }
+            let a = vertices[f[0] as usize];
+            let b = vertices[f[1] as usize];
+            let c = vertices[f[2] as usize];
+            (b - a).cross(&(c - a)).norm() > 0.0
+        });
+    }
+
+    /// Verify that all face indices refer to existing vertices and that the attributes line up
+    /// with the vertices.
+    pub fn validate(&self) -> io::Result<()> {
+        let n = self.vertices.len() as u32;
+        if let Some(f) = self.faces.iter().find(|f| f.iter().any(|&i| i >= n)) {
+            return Err(invalid_data(format!(
+                "Face {:?} refers to a vertex index beyond the {} vertices in the file",
+                f, n
+            )));
+        }
+
+        self.attributes
+            .check_len(self.vertices.len())
+            .map_err(invalid_data)
+    }
+}
+
+/// Split a polygon into a triangle fan around its first vertex.
+fn triangulate_fan(polygon: &[u32], faces: &mut Vec<[u32; 3]>) {
+    for i in 1..polygon.len().saturating_sub(1) {
+        faces.push([polygon[0], polygon[i], polygon[i + 1]]);
+    }
+}
+
+fn invalid_data<E: ToString>(message: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Convert a floating point color channel in the range 0.0 to 1.0 into a byte
+fn unit_to_byte(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,188 @@
+//! Wavefront OBJ files. Vertex positions, vertex normals (`vn`), and the common `v x y z r g b`
+//! vertex color extension are read. Texture coordinates, groups, and materials are ignored, and
+//! polygonal faces are triangulated as fans.
+
+use super::{invalid_data, triangulate_fan, unit_to_byte, MeshData};
+use crate::mesh::attributes::VertexAttributes;
+use engeom::{Point3, Vector3};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Resolve a 1-based (or negative, relative) OBJ index into a 0-based index
+fn resolve_index(token: &str, count: usize, line: usize) -> io::Result<usize> {
+    let value = token
+        .parse::<i64>()
+        .map_err(|e| invalid_data(format!("Line {}: {}", line, e)))?;
+    let index = if value < 0 {
+        count as i64 + value
+    } else {
+        value - 1
+    };
+
+    if index < 0 || index as usize >= count {
+        Err(invalid_data(format!(
+            "Line {}: index {} is out of range",
+            line, value
+        )))
+    } else {
+        Ok(index as usize)
+    }
+}
+
+fn parse_floats(tokens: &[&str], line: usize) -> io::Result<Vec<f64>> {
+    tokens
+        .iter()
+        .map(|t| {
+            t.parse::<f64>()
+                .map_err(|e| invalid_data(format!("Line {}: {}", line, e)))
+        })
+        .collect()
+}
+
+pub fn read_obj(path: &Path) -> io::Result<MeshData> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut vertices = Vec::new();
+    let mut colors = Vec::new();
+    let mut file_normals = Vec::new();
+    let mut faces = Vec::new();
+
+    // The vertex normals are accumulated from every face corner which references them, since in
+    // an OBJ file the normal belongs to the face corner rather than the vertex
+    let mut normal_sums: Vec<Vector3> = Vec::new();
+    let mut any_normals = false;
+    let mut polygon = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_no = i + 1;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+        match tokens.first() {
+            Some(&"v") => {
+                let values = parse_floats(&tokens[1..], line_no)?;
+                if values.len() < 3 {
+                    return Err(invalid_data(format!(
+                        "Line {}: vertex has fewer than three coordinates",
+                        line_no
+                    )));
+                }
+                vertices.push(Point3::new(values[0], values[1], values[2]));
+                if values.len() >= 6 {
+                    colors.push(Some([
+                        unit_to_byte(values[3]),
+                        unit_to_byte(values[4]),
+                        unit_to_byte(values[5]),
+                        255,
+                    ]));
+                } else {
+                    colors.push(None);
+                }
+            }
+            Some(&"vn") => {
+                let values = parse_floats(&tokens[1..], line_no)?;
+                if values.len() < 3 {
+                    return Err(invalid_data(format!(
+                        "Line {}: normal has fewer than three components",
+                        line_no
+                    )));
+                }
+                file_normals.push(Vector3::new(values[0], values[1], values[2]));
+            }
+            Some(&"f") => {
+                polygon.clear();
+                normal_sums.resize(vertices.len(), Vector3::zeros());
+                for corner in tokens[1..].iter() {
+                    let mut parts = corner.split('/');
+                    let v = resolve_index(parts.next().unwrap_or(""), vertices.len(), line_no)?;
+                    polygon.push(v as u32);
+
+                    // v, v/vt, v//vn, or v/vt/vn
+                    if let Some(n) = parts.nth(1).filter(|s| !s.is_empty()) {
+                        let n = resolve_index(n, file_normals.len(), line_no)?;
+                        normal_sums[v] += file_normals[n];
+                        any_normals = true;
+                    }
+                }
+                triangulate_fan(&polygon, &mut faces);
+            }
+            _ => {}
+        }
+    }
+
+    normal_sums.resize(vertices.len(), Vector3::zeros());
+    let normals = any_normals.then(|| {
+        normal_sums
+            .into_iter()
+            .map(|n| n.try_normalize(1.0e-12).unwrap_or(n))
+            .collect()
+    });
+
+    // Colors are only kept if every vertex had them
+    let colors = if !colors.is_empty() && colors.iter().all(|c| c.is_some()) {
+        Some(colors.into_iter().flatten().collect())
+    } else {
+        None
+    };
+
+    let data = MeshData {
+        vertices,
+        faces,
+        attributes: VertexAttributes {
+            normals,
+            colors,
+            scalars: Vec::new(),
+        },
+    };
+
+    data.validate()?;
+    Ok(data)
+}
+
+pub fn write_obj(
+    path: &Path,
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    attributes: &VertexAttributes,
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    writeln!(w, "# Written by engeom")?;
+
+    for (i, p) in vertices.iter().enumerate() {
+        match &attributes.colors {
+            Some(colors) => {
+                let c = colors[i];
+                writeln!(
+                    w,
+                    "v {} {} {} {} {} {}",
+                    p.x,
+                    p.y,
+                    p.z,
+                    c[0] as f64 / 255.0,
+                    c[1] as f64 / 255.0,
+                    c[2] as f64 / 255.0
+                )?;
+            }
+            None => writeln!(w, "v {} {} {}", p.x, p.y, p.z)?,
+        }
+    }
+
+    if let Some(normals) = &attributes.normals {
+        for n in normals.iter() {
+            writeln!(w, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+    }
+
+    // Normals are written one per vertex, so the normal index always matches the vertex index
+    for f in faces.iter() {
+        let [a, b, c] = [f[0] + 1, f[1] + 1, f[2] + 1];
+        if attributes.normals.is_some() {
+            writeln!(w, "f {}//{} {}//{} {}//{}", a, a, b, b, c, c)?;
+        } else {
+            writeln!(w, "f {} {} {}", a, b, c)?;
+        }
+    }
+
+    w.flush()
+}
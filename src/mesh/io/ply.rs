@@ -0,0 +1,494 @@
+//! Stanford PLY files, in ASCII, binary little endian, and binary big endian encodings. Vertex
+//! positions, normals, colors, and any additional scalar vertex properties are read, and faces with
+//! more than three vertices are triangulated as fans.
+
+use super::{invalid_data, triangulate_fan, unit_to_byte, MeshData};
+use crate::mesh::attributes::VertexAttributes;
+use engeom::{Point3, Vector3};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLe,
+    BinaryBe,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Kind {
+    fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "char" | "int8" => Ok(Kind::I8),
+            "uchar" | "uint8" => Ok(Kind::U8),
+            "short" | "int16" => Ok(Kind::I16),
+            "ushort" | "uint16" => Ok(Kind::U16),
+            "int" | "int32" => Ok(Kind::I32),
+            "uint" | "uint32" => Ok(Kind::U32),
+            "float" | "float32" => Ok(Kind::F32),
+            "double" | "float64" => Ok(Kind::F64),
+            _ => Err(invalid_data(format!(
+                "Unknown PLY property type '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Value {
+        name: String,
+        kind: Kind,
+    },
+    List {
+        name: String,
+        count: Kind,
+        item: Kind,
+    },
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Value { name, .. } => name,
+            Property::List { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    body_start: usize,
+}
+
+fn parse_header(data: &[u8]) -> io::Result<Header> {
+    if !data.starts_with(b"ply") {
+        return Err(invalid_data(
+            "File does not start with the 'ply' magic number",
+        ));
+    }
+
+    let marker = b"end_header";
+    let end = data
+        .windows(marker.len())
+        .position(|w| w == marker)
+        .ok_or_else(|| invalid_data("PLY header has no 'end_header' line"))?;
+
+    // The body starts after the line ending following the end_header marker
+    let mut body_start = end + marker.len();
+    while body_start < data.len() && data[body_start] != b'\n' {
+        body_start += 1;
+    }
+    body_start += 1;
+
+    let text = std::str::from_utf8(&data[..end]).map_err(invalid_data)?;
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+
+    for line in text.lines().skip(1) {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["format", "ascii", _] => format = Some(Format::Ascii),
+            ["format", "binary_little_endian", _] => format = Some(Format::BinaryLe),
+            ["format", "binary_big_endian", _] => format = Some(Format::BinaryBe),
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(invalid_data)?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid_data("PLY property declared before any element"))?;
+                element.properties.push(Property::List {
+                    name: name.to_string(),
+                    count: Kind::parse(count)?,
+                    item: Kind::parse(item)?,
+                });
+            }
+            ["property", kind, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid_data("PLY property declared before any element"))?;
+                element.properties.push(Property::Value {
+                    name: name.to_string(),
+                    kind: Kind::parse(kind)?,
+                });
+            }
+            ["format", ..] => return Err(invalid_data(format!("Unknown PLY format '{}'", line))),
+            _ => {} // comments, obj_info, and blank lines
+        }
+    }
+
+    Ok(Header {
+        format: format.ok_or_else(|| invalid_data("PLY header has no format line"))?,
+        elements,
+        body_start,
+    })
+}
+
+/// A source of values from the body of the file, which hides the difference between the ASCII
+/// and binary encodings.
+trait Source {
+    fn next(&mut self, kind: Kind) -> io::Result<f64>;
+}
+
+struct AsciiSource<'a> {
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl Source for AsciiSource<'_> {
+    fn next(&mut self, _kind: Kind) -> io::Result<f64> {
+        let token = self
+            .tokens
+            .next()
+            .ok_or_else(|| invalid_data("Unexpected end of PLY data"))?;
+        token.parse::<f64>().map_err(invalid_data)
+    }
+}
+
+struct BinarySource<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl BinarySource<'_> {
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| invalid_data("Unexpected end of PLY data"))?;
+        self.pos += N;
+        let mut result: [u8; N] = bytes.try_into().unwrap();
+        if self.big_endian {
+            result.reverse();
+        }
+        Ok(result)
+    }
+}
+
+impl Source for BinarySource<'_> {
+    fn next(&mut self, kind: Kind) -> io::Result<f64> {
+        // Bytes are reversed into little endian order by take() when needed
+        Ok(match kind {
+            Kind::I8 => i8::from_le_bytes(self.take()?) as f64,
+            Kind::U8 => u8::from_le_bytes(self.take()?) as f64,
+            Kind::I16 => i16::from_le_bytes(self.take()?) as f64,
+            Kind::U16 => u16::from_le_bytes(self.take()?) as f64,
+            Kind::I32 => i32::from_le_bytes(self.take()?) as f64,
+            Kind::U32 => u32::from_le_bytes(self.take()?) as f64,
+            Kind::F32 => f32::from_le_bytes(self.take()?) as f64,
+            Kind::F64 => f64::from_le_bytes(self.take()?),
+        })
+    }
+}
+
+/// The role a vertex property plays when building the mesh
+enum Role {
+    Position(usize),
+    Normal(usize),
+    Color(usize, Kind),
+    Scalar(usize),
+    Skip,
+}
+
+fn vertex_roles(element: &Element) -> (Vec<Role>, Vec<String>) {
+    let mut scalars = Vec::new();
+    let roles = element
+        .properties
+        .iter()
+        .map(|p| match p {
+            Property::List { .. } => Role::Skip,
+            Property::Value { name, kind } => match name.as_str() {
+                "x" => Role::Position(0),
+                "y" => Role::Position(1),
+                "z" => Role::Position(2),
+                "nx" => Role::Normal(0),
+                "ny" => Role::Normal(1),
+                "nz" => Role::Normal(2),
+                "red" | "r" | "diffuse_red" => Role::Color(0, *kind),
+                "green" | "g" | "diffuse_green" => Role::Color(1, *kind),
+                "blue" | "b" | "diffuse_blue" => Role::Color(2, *kind),
+                "alpha" | "a" | "diffuse_alpha" => Role::Color(3, *kind),
+                _ => {
+                    scalars.push(name.clone());
+                    Role::Scalar(scalars.len() - 1)
+                }
+            },
+        })
+        .collect();
+
+    (roles, scalars)
+}
+
+fn skip_property(source: &mut dyn Source, property: &Property) -> io::Result<()> {
+    match property {
+        Property::Value { kind, .. } => {
+            source.next(*kind)?;
+        }
+        Property::List { count, item, .. } => {
+            let n = source.next(*count)? as usize;
+            for _ in 0..n {
+                source.next(*item)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_vertices(
+    source: &mut dyn Source,
+    element: &Element,
+    data: &mut MeshData,
+) -> io::Result<()> {
+    let (roles, scalar_names) = vertex_roles(element);
+    let has_normals = roles.iter().any(|r| matches!(r, Role::Normal(_)));
+    let has_colors = roles.iter().any(|r| matches!(r, Role::Color(..)));
+
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    let mut scalars = vec![Vec::with_capacity(element.count); scalar_names.len()];
+    data.vertices.reserve(element.count);
+
+    for _ in 0..element.count {
+        let mut p = [0.0; 3];
+        let mut n = [0.0; 3];
+        let mut c = [255u8; 4];
+
+        for (property, role) in element.properties.iter().zip(roles.iter()) {
+            let kind = match property {
+                Property::Value { kind, .. } => *kind,
+                Property::List { .. } => {
+                    skip_property(source, property)?;
+                    continue;
+                }
+            };
+
+            let value = source.next(kind)?;
+            match role {
+                Role::Position(i) => p[*i] = value,
+                Role::Normal(i) => n[*i] = value,
+                Role::Color(i, Kind::F32 | Kind::F64) => c[*i] = unit_to_byte(value),
+                Role::Color(i, _) => c[*i] = value.clamp(0.0, 255.0) as u8,
+                Role::Scalar(i) => scalars[*i].push(value),
+                Role::Skip => {}
+            }
+        }
+
+        data.vertices.push(Point3::new(p[0], p[1], p[2]));
+        if has_normals {
+            normals.push(Vector3::new(n[0], n[1], n[2]));
+        }
+        if has_colors {
+            colors.push(c);
+        }
+    }
+
+    data.attributes = VertexAttributes {
+        normals: has_normals.then_some(normals),
+        colors: has_colors.then_some(colors),
+        scalars: scalar_names.into_iter().zip(scalars).collect(),
+    };
+
+    Ok(())
+}
+
+/// Read a list count or vertex index, which must be a whole number that fits in a `u32`. The upper
+/// bound on vertex indices is checked once the whole file is read.
+fn read_index(source: &mut dyn Source, kind: Kind) -> io::Result<u32> {
+    let value = source.next(kind)?;
+    if value.fract() != 0.0 || !(0.0..=u32::MAX as f64).contains(&value) {
+        return Err(invalid_data(format!(
+            "PLY face data holds {}, which is not a valid index",
+            value
+        )));
+    }
+    Ok(value as u32)
+}
+
+fn read_faces(source: &mut dyn Source, element: &Element, data: &mut MeshData) -> io::Result<()> {
+    data.faces.reserve(element.count);
+    let mut polygon = Vec::new();
+
+    for _ in 0..element.count {
+        for property in element.properties.iter() {
+            match property {
+                Property::List { name, count, item }
+                    if name == "vertex_indices" || name == "vertex_index" =>
+                {
+                    let n = read_index(source, *count)?;
+                    polygon.clear();
+                    for _ in 0..n {
+                        polygon.push(read_index(source, *item)?);
+                    }
+                    triangulate_fan(&polygon, &mut data.faces);
+                }
+                _ => skip_property(source, property)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_ply(path: &Path) -> io::Result<MeshData> {
+    let bytes = std::fs::read(path)?;
+    let header = parse_header(&bytes)?;
+    let body = &bytes[header.body_start.min(bytes.len())..];
+
+    let mut ascii;
+    let mut binary;
+    let source: &mut dyn Source = match header.format {
+        Format::Ascii => {
+            let text = std::str::from_utf8(body).map_err(invalid_data)?;
+            ascii = AsciiSource {
+                tokens: text.split_ascii_whitespace(),
+            };
+            &mut ascii
+        }
+        Format::BinaryLe | Format::BinaryBe => {
+            binary = BinarySource {
+                data: body,
+                pos: 0,
+                big_endian: header.format == Format::BinaryBe,
+            };
+            &mut binary
+        }
+    };
+
+    let mut data = MeshData::default();
+    for element in header.elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(source, element, &mut data)?,
+            "face" => read_faces(source, element, &mut data)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        skip_property(source, property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if header
+        .elements
+        .iter()
+        .all(|e| e.name != "vertex" || !e.properties.iter().any(|p| p.name() == "x"))
+    {
+        return Err(invalid_data("PLY file has no vertex positions"));
+    }
+
+    data.validate()?;
+    Ok(data)
+}
+
+/// PLY property names may not contain whitespace
+fn property_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+pub fn write_ply(
+    path: &Path,
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    attributes: &VertexAttributes,
+    binary: bool,
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+
+    writeln!(w, "ply")?;
+    if binary {
+        writeln!(w, "format binary_little_endian 1.0")?;
+    } else {
+        writeln!(w, "format ascii 1.0")?;
+    }
+    writeln!(w, "comment Written by engeom")?;
+    writeln!(w, "element vertex {}", vertices.len())?;
+    for c in ["x", "y", "z"] {
+        writeln!(w, "property double {}", c)?;
+    }
+    if attributes.normals.is_some() {
+        for c in ["nx", "ny", "nz"] {
+            writeln!(w, "property double {}", c)?;
+        }
+    }
+    if attributes.colors.is_some() {
+        for c in ["red", "green", "blue", "alpha"] {
+            writeln!(w, "property uchar {}", c)?;
+        }
+    }
+    for (name, _) in attributes.scalars.iter() {
+        writeln!(w, "property double {}", property_name(name))?;
+    }
+    writeln!(w, "element face {}", faces.len())?;
+    writeln!(w, "property list uchar int vertex_indices")?;
+    writeln!(w, "end_header")?;
+
+    for (i, p) in vertices.iter().enumerate() {
+        let mut values = vec![p.x, p.y, p.z];
+        if let Some(normals) = &attributes.normals {
+            values.extend_from_slice(normals[i].as_slice());
+        }
+        let color = attributes.colors.as_ref().map(|c| c[i]);
+        let scalars = attributes
+            .scalars
+            .iter()
+            .map(|(_, v)| v[i])
+            .collect::<Vec<_>>();
+
+        if binary {
+            for v in values.iter() {
+                w.write_all(&v.to_le_bytes())?;
+            }
+            if let Some(c) = color {
+                w.write_all(&c)?;
+            }
+            for v in scalars.iter() {
+                w.write_all(&v.to_le_bytes())?;
+            }
+        } else {
+            let mut line = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            if let Some(c) = color {
+                line.extend(c.iter().map(|v| v.to_string()));
+            }
+            line.extend(scalars.iter().map(|v| v.to_string()));
+            writeln!(w, "{}", line.join(" "))?;
+        }
+    }
+
+    for f in faces.iter() {
+        if binary {
+            w.write_all(&[3u8])?;
+            for i in f.iter() {
+                w.write_all(&(*i as i32).to_le_bytes())?;
+            }
+        } else {
+            writeln!(w, "3 {} {} {}", f[0], f[1], f[2])?;
+        }
+    }
+
+    w.flush()
+}
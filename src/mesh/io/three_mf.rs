@@ -0,0 +1,507 @@
+//! 3D Manufacturing Format (3MF) packages. Every mesh object referenced by the build is placed
+//! with its build and component transforms and merged into a single mesh. Colors from the
+//! materials extension (`m:colorgroup`) and core `basematerials` are carried over to the vertices
+//! which reference them.
+
+use super::{invalid_data, MeshData};
+use crate::mesh::attributes::{VertexAttributes, DEFAULT_COLOR};
+use engeom::Point3;
+use parry3d_f64::na::{Matrix3, Vector3};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MODEL_PATH: &str = "3D/3dmodel.model";
+const MODEL_REL_TYPE: &str = "http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel";
+
+// ================================================================================================
+// A minimal XML tag scanner
+// ================================================================================================
+
+/// A single XML start, end, or empty-element tag. Namespace prefixes are stripped from both the
+/// tag and the attribute names, which is sufficient for the well-known 3MF vocabulary.
+struct Tag<'a> {
+    name: &'a str,
+    attrs: Vec<(&'a str, &'a str)>,
+    closing: bool,
+    empty: bool,
+}
+
+impl<'a> Tag<'a> {
+    fn attr(&self, name: &str) -> Option<&'a str> {
+        self.attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    fn attr_f64(&self, name: &str) -> io::Result<f64> {
+        self.attr(name)
+            .ok_or_else(|| invalid_data(format!("<{}> is missing '{}'", self.name, name)))?
+            .trim()
+            .parse()
+            .map_err(invalid_data)
+    }
+
+    fn attr_index(&self, name: &str) -> io::Result<u32> {
+        self.attr(name)
+            .ok_or_else(|| invalid_data(format!("<{}> is missing '{}'", self.name, name)))?
+            .trim()
+            .parse()
+            .map_err(invalid_data)
+    }
+
+    fn attr_usize(&self, name: &str) -> io::Result<Option<usize>> {
+        self.attr(name)
+            .map(|v| v.trim().parse().map_err(invalid_data))
+            .transpose()
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn scan_tags(xml: &str) -> Vec<Tag<'_>> {
+    let mut tags = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        // Skip comments, declarations, and processing instructions
+        if rest.starts_with("!--") {
+            rest = rest.find("-->").map_or("", |e| &rest[e + 3..]);
+            continue;
+        }
+        if rest.starts_with('?') || rest.starts_with('!') {
+            rest = rest.find('>').map_or("", |e| &rest[e + 1..]);
+            continue;
+        }
+
+        // Find the end of the tag, respecting quoted attribute values
+        let mut quote = None;
+        let mut end = None;
+        for (i, c) in rest.char_indices() {
+            match (quote, c) {
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (Some(q), _) if c == q => quote = None,
+                (None, '>') => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let Some(end) = end else { break };
+        let body = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let closing = body.starts_with('/');
+        let empty = body.ends_with('/');
+        let body = body.trim_start_matches('/').trim_end_matches('/');
+
+        let name_end = body.find(char::is_whitespace).unwrap_or(body.len());
+        let name = local_name(&body[..name_end]);
+        let mut attrs = Vec::new();
+        let mut a = &body[name_end..];
+        while let Some(eq) = a.find('=') {
+            let key = local_name(a[..eq].trim());
+            let after = a[eq + 1..].trim_start();
+            let Some(q) = after.chars().next() else { break };
+            let Some(close) = after[1..].find(q) else {
+                break;
+            };
+            attrs.push((key, &after[1..1 + close]));
+            a = &after[close + 2..];
+        }
+
+        tags.push(Tag {
+            name,
+            attrs,
+            closing,
+            empty,
+        });
+    }
+
+    tags
+}
+
+// ================================================================================================
+// Reading
+// ================================================================================================
+
+/// An affine transform in the 3MF convention, where points are row vectors multiplied on the left
+#[derive(Clone, Copy)]
+struct Affine {
+    m: Matrix3<f64>,
+    t: Vector3<f64>,
+}
+
+impl Affine {
+    fn identity() -> Self {
+        Self {
+            m: Matrix3::identity(),
+            t: Vector3::zeros(),
+        }
+    }
+
+    fn parse(text: Option<&str>) -> io::Result<Self> {
+        let Some(text) = text else {
+            return Ok(Self::identity());
+        };
+
+        let v = text
+            .split_whitespace()
+            .map(|s| s.parse::<f64>().map_err(invalid_data))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if v.len() != 12 {
+            return Err(invalid_data(format!("Invalid 3MF transform '{}'", text)));
+        }
+
+        // The matrix is given as m00 m01 m02 m10 m11 m12 m20 m21 m22 m30 m31 m32, so the columns
+        // of the column-vector form are the first three rows of the 3MF form
+        Ok(Self {
+            m: Matrix3::new(v[0], v[3], v[6], v[1], v[4], v[7], v[2], v[5], v[8]),
+            t: Vector3::new(v[9], v[10], v[11]),
+        })
+    }
+
+    fn apply(&self, p: &Point3) -> Point3 {
+        Point3::from(self.m * p.coords + self.t)
+    }
+
+    /// The transform which applies `self` first and then `outer`
+    fn then(&self, outer: &Affine) -> Affine {
+        Affine {
+            m: outer.m * self.m,
+            t: outer.m * self.t + outer.t,
+        }
+    }
+}
+
+/// The property group and the per-vertex property indices `[p1, p2, p3]` of a triangle
+type TriangleProperty = (usize, [usize; 3]);
+
+#[derive(Default)]
+struct Object {
+    vertices: Vec<Point3>,
+
+    /// The vertex indices and the optional property of each triangle
+    triangles: Vec<([u32; 3], Option<TriangleProperty>)>,
+    components: Vec<(usize, Affine)>,
+
+    /// The object level property group and index, used for triangles without their own
+    pid: Option<usize>,
+    pindex: Option<usize>,
+}
+
+fn parse_color(text: &str) -> io::Result<[u8; 4]> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 && hex.len() != 8 {
+        return Err(invalid_data(format!("Invalid 3MF color '{}'", text)));
+    }
+
+    let mut c = DEFAULT_COLOR;
+    for (i, chunk) in hex.as_bytes().chunks(2).enumerate() {
+        let s = std::str::from_utf8(chunk).map_err(invalid_data)?;
+        c[i] = u8::from_str_radix(s, 16).map_err(invalid_data)?;
+    }
+    Ok(c)
+}
+
+fn model_path(archive: &mut ZipArchive<File>) -> io::Result<String> {
+    if let Ok(mut rels) = archive.by_name("_rels/.rels") {
+        let mut text = String::new();
+        rels.read_to_string(&mut text)?;
+        for tag in scan_tags(&text) {
+            if tag.name == "Relationship"
+                && tag.attr("Type") == Some(MODEL_REL_TYPE)
+                && let Some(target) = tag.attr("Target")
+            {
+                return Ok(target.trim_start_matches('/').to_string());
+            }
+        }
+    }
+
+    Ok(MODEL_PATH.to_string())
+}
+
+/// Place an object and all of its components into the output mesh
+fn instantiate(
+    id: usize,
+    transform: &Affine,
+    objects: &HashMap<usize, Object>,
+    groups: &HashMap<usize, Vec<[u8; 4]>>,
+    out: &mut MeshData,
+    colors: &mut Vec<Option<[u8; 4]>>,
+    depth: usize,
+) -> io::Result<()> {
+    if depth > 32 {
+        return Err(invalid_data("3MF components are nested too deeply"));
+    }
+
+    let object = objects
+        .get(&id)
+        .ok_or_else(|| invalid_data(format!("3MF object {} does not exist", id)))?;
+
+    let offset = out.vertices.len() as u32;
+    out.vertices
+        .extend(object.vertices.iter().map(|p| transform.apply(p)));
+    colors.resize(out.vertices.len(), None);
+
+    let color = |group: usize, index: usize| groups.get(&group).and_then(|g| g.get(index)).copied();
+
+    for (face, props) in object.triangles.iter() {
+        out.faces
+            .push([face[0] + offset, face[1] + offset, face[2] + offset]);
+
+        let fallback = object.pid.zip(object.pindex).map(|(g, i)| (g, [i; 3]));
+        if let Some((group, p)) = props.or(fallback) {
+            for (v, i) in face.iter().zip(p.iter()) {
+                if let Some(c) = color(group, *i) {
+                    colors[(v + offset) as usize] = Some(c);
+                }
+            }
+        }
+    }
+
+    for (child, child_transform) in object.components.iter() {
+        let combined = child_transform.then(transform);
+        instantiate(*child, &combined, objects, groups, out, colors, depth + 1)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_3mf(path: &Path) -> io::Result<MeshData> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(invalid_data)?;
+    let model_path = model_path(&mut archive)?;
+
+    let mut text = String::new();
+    archive
+        .by_name(&model_path)
+        .map_err(invalid_data)?
+        .read_to_string(&mut text)?;
+
+    let mut objects: HashMap<usize, Object> = HashMap::new();
+    let mut groups: HashMap<usize, Vec<[u8; 4]>> = HashMap::new();
+    let mut build = Vec::new();
+
+    let mut current_object: Option<usize> = None;
+    let mut current_group: Option<usize> = None;
+
+    for tag in scan_tags(&text) {
+        match (tag.name, tag.closing) {
+            ("object", false) => {
+                let id = tag
+                    .attr_usize("id")?
+                    .ok_or_else(|| invalid_data("3MF object has no id"))?;
+                let object = Object {
+                    pid: tag.attr_usize("pid")?,
+                    pindex: tag.attr_usize("pindex")?,
+                    ..Default::default()
+                };
+                objects.insert(id, object);
+                current_object = (!tag.empty).then_some(id);
+            }
+            ("object", true) => current_object = None,
+            ("colorgroup" | "basematerials", false) => {
+                let id = tag
+                    .attr_usize("id")?
+                    .ok_or_else(|| invalid_data("3MF property group has no id"))?;
+                groups.insert(id, Vec::new());
+                current_group = (!tag.empty).then_some(id);
+            }
+            ("colorgroup" | "basematerials", true) => current_group = None,
+            ("color" | "base", false) => {
+                if let Some(group) = current_group.and_then(|g| groups.get_mut(&g)) {
+                    let value = tag.attr("color").or(tag.attr("displaycolor"));
+                    group.push(value.map_or(Ok(DEFAULT_COLOR), parse_color)?);
+                }
+            }
+            ("vertex", false) => {
+                if let Some(object) = current_object.and_then(|o| objects.get_mut(&o)) {
+                    object.vertices.push(Point3::new(
+                        tag.attr_f64("x")?,
+                        tag.attr_f64("y")?,
+                        tag.attr_f64("z")?,
+                    ));
+                }
+            }
+            ("triangle", false) => {
+                if let Some(object) = current_object.and_then(|o| objects.get_mut(&o)) {
+                    let face = [
+                        tag.attr_index("v1")?,
+                        tag.attr_index("v2")?,
+                        tag.attr_index("v3")?,
+                    ];
+                    if face.iter().any(|&i| i as usize >= object.vertices.len()) {
+                        return Err(invalid_data(format!(
+                            "3MF triangle {:?} refers to a missing vertex",
+                            face
+                        )));
+                    }
+
+                    // Triangle properties fall back on the object's property group, and corners
+                    // without their own index use the first corner's
+                    let pid = tag.attr_usize("pid")?.or(object.pid);
+                    let props = match (pid, tag.attr_usize("p1")?) {
+                        (Some(pid), Some(p1)) => {
+                            let p2 = tag.attr_usize("p2")?.unwrap_or(p1);
+                            let p3 = tag.attr_usize("p3")?.unwrap_or(p1);
+                            Some((pid, [p1, p2, p3]))
+                        }
+                        _ => None,
+                    };
+                    object.triangles.push((face, props));
+                }
+            }
+            ("component", false) => {
+                if let Some(object) = current_object.and_then(|o| objects.get_mut(&o)) {
+                    let id = tag
+                        .attr_usize("objectid")?
+                        .ok_or_else(|| invalid_data("3MF component has no objectid"))?;
+                    object
+                        .components
+                        .push((id, Affine::parse(tag.attr("transform"))?));
+                }
+            }
+            ("item", false) => {
+                let id = tag
+                    .attr_usize("objectid")?
+                    .ok_or_else(|| invalid_data("3MF build item has no objectid"))?;
+                build.push((id, Affine::parse(tag.attr("transform"))?));
+            }
+            _ => {}
+        }
+    }
+
+    // A package without build items is not valid 3MF, but we can still recover its meshes
+    if build.is_empty() {
+        let mut ids = objects
+            .iter()
+            .filter(|(_, o)| !o.vertices.is_empty())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        build = ids.into_iter().map(|id| (id, Affine::identity())).collect();
+    }
+
+    let mut data = MeshData::default();
+    let mut colors = Vec::new();
+    for (id, transform) in build.iter() {
+        instantiate(*id, transform, &objects, &groups, &mut data, &mut colors, 0)?;
+    }
+
+    if colors.iter().any(|c| c.is_some()) {
+        data.attributes = VertexAttributes {
+            normals: None,
+            colors: Some(
+                colors
+                    .into_iter()
+                    .map(|c| c.unwrap_or(DEFAULT_COLOR))
+                    .collect(),
+            ),
+            scalars: Vec::new(),
+        };
+    }
+
+    data.validate()?;
+    Ok(data)
+}
+
+// ================================================================================================
+// Writing
+// ================================================================================================
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>
+</Types>
+"#;
+
+const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Target="/3D/3dmodel.model" Id="rel0" Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>
+</Relationships>
+"#;
+
+fn model_xml(vertices: &[Point3], faces: &[[u32; 3]], colors: Option<&[[u8; 4]]>) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<model unit=\"millimeter\" xml:lang=\"en-US\" ");
+    xml.push_str("xmlns=\"http://schemas.microsoft.com/3dmanufacturing/core/2015/02\" ");
+    xml.push_str("xmlns:m=\"http://schemas.microsoft.com/3dmanufacturing/material/2015/02\">\n");
+    xml.push_str("  <resources>\n");
+
+    // Vertex colors are written as a color group with one color per vertex, so that the property
+    // index of each triangle corner is the same as its vertex index
+    if let Some(colors) = colors {
+        xml.push_str("    <m:colorgroup id=\"1\">\n");
+        for c in colors.iter() {
+            xml.push_str(&format!(
+                "      <m:color color=\"#{:02X}{:02X}{:02X}{:02X}\"/>\n",
+                c[0], c[1], c[2], c[3]
+            ));
+        }
+        xml.push_str("    </m:colorgroup>\n");
+    }
+
+    xml.push_str("    <object id=\"2\" type=\"model\">\n");
+    xml.push_str("      <mesh>\n        <vertices>\n");
+    for p in vertices.iter() {
+        xml.push_str(&format!(
+            "          <vertex x=\"{}\" y=\"{}\" z=\"{}\"/>\n",
+            p.x, p.y, p.z
+        ));
+    }
+    xml.push_str("        </vertices>\n        <triangles>\n");
+    for f in faces.iter() {
+        if colors.is_some() {
+            xml.push_str(&format!(
+                "          <triangle v1=\"{0}\" v2=\"{1}\" v3=\"{2}\" pid=\"1\" p1=\"{0}\" p2=\"{1}\" p3=\"{2}\"/>\n",
+                f[0], f[1], f[2]
+            ));
+        } else {
+            xml.push_str(&format!(
+                "          <triangle v1=\"{}\" v2=\"{}\" v3=\"{}\"/>\n",
+                f[0], f[1], f[2]
+            ));
+        }
+    }
+    xml.push_str("        </triangles>\n      </mesh>\n    </object>\n");
+    xml.push_str("  </resources>\n");
+    xml.push_str("  <build>\n    <item objectid=\"2\"/>\n  </build>\n");
+    xml.push_str("</model>\n");
+    xml
+}
+
+pub fn write_3mf(
+    path: &Path,
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    attributes: &VertexAttributes,
+) -> io::Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let files = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", RELS.to_string()),
+        (
+            MODEL_PATH,
+            model_xml(vertices, faces, attributes.colors.as_deref()),
+        ),
+    ];
+
+    for (name, content) in files.iter() {
+        zip.start_file(*name, options).map_err(io::Error::other)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    zip.finish().map_err(io::Error::other)?;
+    Ok(())
+}
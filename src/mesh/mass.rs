@@ -0,0 +1,100 @@
+//! Area, volume and inertia of a mesh. The volume integrals are sums over the tetrahedra formed
+//! by each face and a common reference point, which is why they need a closed, consistently wound
+//! mesh to mean anything.
+
+use engeom::{Iso3, Point3, Vector3};
+use parry3d_f64::na::{Matrix3, Rotation3, SymmetricEigen, Translation3, UnitQuaternion};
+use std::collections::HashSet;
+
+pub struct MassProperties {
+    /// The enclosed volume, which is negative if the faces point inwards
+    pub volume: f64,
+
+    pub centroid: Point3,
+
+    /// The integral of r * r^T over the enclosed volume, with r measured from the centroid
+    covariance: Matrix3<f64>,
+}
+
+/// Whether every edge is used by exactly two faces which run it in opposite directions.
+pub fn is_closed(faces: &[[u32; 3]]) -> bool {
+    let mut directed = HashSet::new();
+    for f in faces.iter() {
+        for (a, b) in [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])] {
+            if !directed.insert((a, b)) {
+                return false;
+            }
+        }
+    }
+
+    directed.iter().all(|(a, b)| directed.contains(&(*b, *a)))
+}
+
+/// The total area of the faces and the area weighted centroid of the surface.
+pub fn surface(vertices: &[Point3], faces: &[[u32; 3]]) -> (f64, Point3) {
+    let mut area = 0.0;
+    let mut weighted = Vector3::zeros();
+    for f in faces.iter() {
+        let [a, b, c] = f.map(|i| vertices[i as usize]);
+        let face_area = (b - a).cross(&(c - a)).norm() / 2.0;
+        area += face_area;
+        weighted += (a.coords + b.coords + c.coords) * (face_area / 3.0);
+    }
+
+    (area, Point3::from(weighted / area))
+}
+
+pub fn mass_properties(vertices: &[Point3], faces: &[[u32; 3]]) -> MassProperties {
+    // Measuring from a point near the mesh keeps the sums from losing precision far from the origin
+    let origin = vertices.iter().map(|p| p.coords).sum::<Vector3>() / vertices.len().max(1) as f64;
+
+    let mut volume = 0.0;
+    let mut first = Vector3::zeros();
+    let mut second = Matrix3::zeros();
+    for f in faces.iter() {
+        let [a, b, c] = f.map(|i| vertices[i as usize].coords - origin);
+        let v = a.dot(&b.cross(&c)) / 6.0;
+        let s = a + b + c;
+
+        volume += v;
+        first += s * (v / 4.0);
+        second += (a * a.transpose() + b * b.transpose() + c * c.transpose() + s * s.transpose())
+            * (v / 20.0);
+    }
+
+    let offset = first / volume;
+    MassProperties {
+        volume,
+        centroid: Point3::from(origin + offset),
+        covariance: second - offset * offset.transpose() * volume,
+    }
+}
+
+impl MassProperties {
+    /// The inertia tensor about the centroid for a solid of uniform density. The solid is the one
+    /// enclosed by the mesh whichever way its faces point.
+    pub fn inertia(&self, density: f64) -> Matrix3<f64> {
+        let c = self.covariance * self.volume.signum();
+        (Matrix3::identity() * c.trace() - c) * density
+    }
+
+    /// A frame at the centroid with its x, y and z axes along the principal axes of inertia, in
+    /// order of increasing moment.
+    pub fn principal_axes(&self) -> Iso3 {
+        let eigen = SymmetricEigen::new(self.inertia(1.0));
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| eigen.eigenvalues[i].total_cmp(&eigen.eigenvalues[j]));
+
+        let mut axes = Matrix3::from_columns(&order.map(|i| eigen.eigenvectors.column(i)));
+        if axes.determinant() < 0.0 {
+            let flipped = -axes.column(2);
+            axes.set_column(2, &flipped);
+        }
+
+        let rotation = Rotation3::from_matrix_unchecked(axes);
+        Iso3::from_parts(
+            Translation3::from(self.centroid.coords),
+            UnitQuaternion::from_rotation_matrix(&rotation),
+        )
+    }
+}
@@ -0,0 +1,477 @@
+//! Isotropic remeshing following Botsch and Kobbelt ("A remeshing approach to multiresolution
+//! modeling", 2004). Each iteration splits the edges which are too long, collapses the ones which
+//! are too short, flips edges to bring the vertices closer to the ideal valence, and then relaxes
+//! the vertices along the surface and projects them back onto the original mesh.
+//!
+//! Boundary edges and feature edges are constrained: they are split at their midpoints, but never
+//! flipped, and their vertices are never smoothed. A vertex on a constrained edge can only be
+//! removed by collapsing it along a straight run of constrained edges, so the boundaries and
+//! features keep exactly their original shape.
+
+use engeom::{Point3, Vector3};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+pub struct RemeshOptions {
+    pub target_length: f64,
+    pub iterations: usize,
+
+    /// Edges where the faces meet at more than this angle are kept as features
+    pub feature_angle: Option<f64>,
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn face_normal(corners: [Point3; 3]) -> Vector3 {
+    (corners[1] - corners[0]).cross(&(corners[2] - corners[0]))
+}
+
+struct Remesher<'a, P> {
+    points: Vec<Point3>,
+    faces: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    vertex_faces: Vec<Vec<usize>>,
+    constrained: HashSet<(usize, usize)>,
+    project: &'a P,
+
+    /// Edges shorter than this are collapsed
+    low: f64,
+
+    /// Edges longer than this are split
+    high: f64,
+}
+
+impl<'a, P> Remesher<'a, P>
+where
+    P: Fn(&Point3) -> Point3 + Sync,
+{
+    fn new(
+        vertices: &[Point3],
+        faces: &[[u32; 3]],
+        options: &RemeshOptions,
+        project: &'a P,
+    ) -> Self {
+        let faces = faces
+            .iter()
+            .map(|f| f.map(|i| i as usize))
+            .collect::<Vec<_>>();
+        let mut vertex_faces = vec![Vec::new(); vertices.len()];
+        for (fi, f) in faces.iter().enumerate() {
+            for &i in f {
+                vertex_faces[i].push(fi);
+            }
+        }
+
+        let mut remesher = Self {
+            points: vertices.to_vec(),
+            alive: vec![true; faces.len()],
+            faces,
+            vertex_faces,
+            constrained: HashSet::new(),
+            project,
+            low: options.target_length * 4.0 / 5.0,
+            high: options.target_length * 4.0 / 3.0,
+        };
+
+        for (a, b) in remesher.edges() {
+            let adjacent = remesher.faces_with_edge(a, b);
+            let feature = match (options.feature_angle, &adjacent[..]) {
+                (Some(limit), &[f0, f1]) => {
+                    let n0 = remesher.normal(f0);
+                    let n1 = remesher.normal(f1);
+                    n0.angle(&n1) > limit
+                }
+                _ => false,
+            };
+            if adjacent.len() != 2 || feature {
+                remesher.constrained.insert(edge_key(a, b));
+            }
+        }
+
+        remesher
+    }
+
+    fn corners(&self, f: usize) -> [Point3; 3] {
+        self.faces[f].map(|i| self.points[i])
+    }
+
+    fn normal(&self, f: usize) -> Vector3 {
+        face_normal(self.corners(f))
+    }
+
+    fn length(&self, a: usize, b: usize) -> f64 {
+        (self.points[a] - self.points[b]).norm()
+    }
+
+    /// Every edge of the mesh once, with the lower vertex index first
+    fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = self
+            .faces
+            .iter()
+            .zip(self.alive.iter())
+            .filter(|(_, alive)| **alive)
+            .flat_map(|(f, _)| [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+            .map(|(a, b)| edge_key(a, b))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    fn faces_with_edge(&self, a: usize, b: usize) -> Vec<usize> {
+        self.vertex_faces[a]
+            .iter()
+            .copied()
+            .filter(|&f| self.faces[f].contains(&b))
+            .collect()
+    }
+
+    fn neighbors(&self, v: usize) -> Vec<usize> {
+        let mut n = self.vertex_faces[v]
+            .iter()
+            .flat_map(|&f| self.faces[f])
+            .filter(|&i| i != v)
+            .collect::<Vec<_>>();
+        n.sort_unstable();
+        n.dedup();
+        n
+    }
+
+    fn is_constrained(&self, a: usize, b: usize) -> bool {
+        self.constrained.contains(&edge_key(a, b))
+    }
+
+    /// The neighbors of `v` which it shares a constrained edge with
+    fn constrained_neighbors(&self, v: usize) -> Vec<usize> {
+        self.neighbors(v)
+            .into_iter()
+            .filter(|&n| self.is_constrained(v, n))
+            .collect()
+    }
+
+    fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.neighbors(v)
+            .into_iter()
+            .any(|n| self.faces_with_edge(v, n).len() == 1)
+    }
+
+    fn kill_face(&mut self, f: usize) {
+        self.alive[f] = false;
+        for i in self.faces[f] {
+            self.vertex_faces[i].retain(|&g| g != f);
+        }
+    }
+
+    fn add_face(&mut self, face: [usize; 3]) {
+        let f = self.faces.len();
+        self.faces.push(face);
+        self.alive.push(true);
+        for i in face {
+            self.vertex_faces[i].push(f);
+        }
+    }
+
+    /// Replace the corner `from` of the face `f` with `to`
+    fn move_corner(&mut self, f: usize, from: usize, to: usize) {
+        for i in self.faces[f].iter_mut() {
+            if *i == from {
+                *i = to;
+            }
+        }
+        self.vertex_faces[from].retain(|&g| g != f);
+        self.vertex_faces[to].push(f);
+    }
+
+    fn split(&mut self, a: usize, b: usize) {
+        let m = self.points.len();
+        self.points.push(Point3::from(
+            (self.points[a].coords + self.points[b].coords) / 2.0,
+        ));
+        self.vertex_faces.push(Vec::new());
+
+        for f in self.faces_with_edge(a, b) {
+            // Keep the winding of the face, the corner which follows `a` is either `b` or `c`
+            let face = self.faces[f];
+            let k = face.iter().position(|&i| i == a).unwrap();
+            let y = if face[(k + 1) % 3] == b { b } else { a };
+            let c = face.iter().copied().find(|&i| i != a && i != b).unwrap();
+
+            self.move_corner(f, y, m);
+            self.add_face([m, y, c]);
+        }
+
+        if self.constrained.remove(&edge_key(a, b)) {
+            self.constrained.insert(edge_key(a, m));
+            self.constrained.insert(edge_key(m, b));
+        }
+    }
+
+    fn split_long_edges(&mut self) {
+        loop {
+            let long = self
+                .edges()
+                .into_iter()
+                .filter(|&(a, b)| self.length(a, b) > self.high)
+                .collect::<Vec<_>>();
+            if long.is_empty() {
+                break;
+            }
+            for (a, b) in long {
+                self.split(a, b);
+            }
+        }
+    }
+
+    /// Where `remove` would end up when collapsed into `keep`, or `None` if it can't be removed
+    /// without changing a boundary or feature.
+    fn collapse_target(&self, remove: usize, keep: usize) -> Option<Point3> {
+        let along = self.constrained_neighbors(remove);
+        let keep_constrained = !self.constrained_neighbors(keep).is_empty();
+        match along[..] {
+            [] if keep_constrained => Some(self.points[keep]),
+            [] => Some(Point3::from(
+                (self.points[remove].coords + self.points[keep].coords) / 2.0,
+            )),
+            [p, q] if p == keep || q == keep => {
+                // Only in the middle of a straight run, so the line keeps its shape
+                let other = if p == keep { q } else { p };
+                let d0 = self.points[remove] - self.points[keep];
+                let d1 = self.points[other] - self.points[remove];
+                (d0.angle(&d1) < 1e-6).then_some(self.points[keep])
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `remove` can be merged into `keep` at `position` while keeping the mesh manifold,
+    /// without turning faces over, and without making edges longer than the split length.
+    fn can_collapse(&self, remove: usize, keep: usize, position: &Point3) -> bool {
+        let across = self.faces_with_edge(remove, keep);
+        let keep_neighbors = self.neighbors(keep);
+        let remove_neighbors = self.neighbors(remove);
+        let shared = remove_neighbors
+            .iter()
+            .filter(|n| keep_neighbors.contains(n))
+            .count();
+        if across.is_empty() || across.len() > 2 || shared != across.len() {
+            return false;
+        }
+        if across.len() == 2 && self.is_boundary_vertex(remove) && self.is_boundary_vertex(keep) {
+            return false;
+        }
+        if remove_neighbors
+            .iter()
+            .any(|&n| (self.points[n] - position).norm() > self.high)
+        {
+            return false;
+        }
+
+        let existing = self.vertex_faces[keep]
+            .iter()
+            .map(|&f| {
+                let mut s = self.faces[f];
+                s.sort_unstable();
+                s
+            })
+            .collect::<HashSet<_>>();
+
+        for v in [remove, keep] {
+            for &f in self.vertex_faces[v].iter().filter(|f| !across.contains(f)) {
+                let face = self.faces[f];
+                let moved = face.map(|i| {
+                    if i == remove || i == keep {
+                        *position
+                    } else {
+                        self.points[i]
+                    }
+                });
+                let old = self.normal(f);
+                let new = face_normal(moved);
+                if new.dot(&old) <= 0.0 || new.norm_squared() <= 1e-12 * old.norm_squared() {
+                    return false;
+                }
+
+                if v == remove {
+                    let mut s = face.map(|i| if i == remove { keep } else { i });
+                    s.sort_unstable();
+                    if existing.contains(&s) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn collapse(&mut self, remove: usize, keep: usize, position: Point3) {
+        for f in self.faces_with_edge(remove, keep) {
+            self.kill_face(f);
+        }
+        for f in self.vertex_faces[remove].clone() {
+            self.move_corner(f, remove, keep);
+        }
+        self.points[keep] = position;
+
+        for n in self.constrained_neighbors(remove) {
+            self.constrained.remove(&edge_key(remove, n));
+            if n != keep {
+                self.constrained.insert(edge_key(keep, n));
+            }
+        }
+    }
+
+    fn collapse_short_edges(&mut self) {
+        for (a, b) in self.edges() {
+            // Either end may already have been removed, or the edge may have grown
+            if self.vertex_faces[a].is_empty()
+                || self.faces_with_edge(a, b).is_empty()
+                || self.length(a, b) >= self.low
+            {
+                continue;
+            }
+
+            for (remove, keep) in [(a, b), (b, a)] {
+                if let Some(p) = self.collapse_target(remove, keep)
+                    && self.can_collapse(remove, keep, &p)
+                {
+                    self.collapse(remove, keep, p);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn valence_error(&self, v: usize, change: i64) -> i64 {
+        let ideal = if self.is_boundary_vertex(v) { 4 } else { 6 };
+        (self.neighbors(v).len() as i64 + change - ideal).abs()
+    }
+
+    fn flip_edges(&mut self) {
+        for (a, b) in self.edges() {
+            if self.is_constrained(a, b) {
+                continue;
+            }
+            let [f0, f1] = self.faces_with_edge(a, b)[..] else {
+                continue;
+            };
+
+            // Wind the edge the way it runs in the first face, the second face runs it backwards
+            let face = self.faces[f0];
+            let k = face.iter().position(|&i| i == a).unwrap();
+            let (a, b) = if face[(k + 1) % 3] == b {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let c = face.iter().copied().find(|&i| i != a && i != b).unwrap();
+            let d = self.faces[f1]
+                .iter()
+                .copied()
+                .find(|&i| i != a && i != b)
+                .unwrap();
+            if c == d || self.neighbors(c).contains(&d) {
+                continue;
+            }
+
+            let before = [(a, 0), (b, 0), (c, 0), (d, 0)].map(|(v, ch)| self.valence_error(v, ch));
+            let after = [(a, -1), (b, -1), (c, 1), (d, 1)].map(|(v, ch)| self.valence_error(v, ch));
+            if after.iter().sum::<i64>() >= before.iter().sum::<i64>() {
+                continue;
+            }
+
+            // The new faces must face the same way as the old ones, or the quad folds over
+            let reference = self.normal(f0) + self.normal(f1);
+            let new0 = [a, d, c];
+            let new1 = [d, b, c];
+            if [new0, new1].iter().any(|f| {
+                let n = face_normal(f.map(|i| self.points[i]));
+                n.dot(&reference) <= 0.0 || n.norm_squared() == 0.0
+            }) {
+                continue;
+            }
+
+            self.kill_face(f0);
+            self.kill_face(f1);
+            self.add_face(new0);
+            self.add_face(new1);
+        }
+    }
+
+    /// Move each free vertex towards the center of its neighbors, within its tangent plane, and
+    /// then back onto the original surface.
+    fn smooth(&mut self) {
+        let moved = (0..self.points.len())
+            .into_par_iter()
+            .filter(|&v| {
+                !self.vertex_faces[v].is_empty() && self.constrained_neighbors(v).is_empty()
+            })
+            .map(|v| {
+                let neighbors = self.neighbors(v);
+                let center = neighbors
+                    .iter()
+                    .fold(Vector3::zeros(), |acc, &n| acc + self.points[n].coords)
+                    / neighbors.len() as f64;
+                let normal = self.vertex_faces[v]
+                    .iter()
+                    .fold(Vector3::zeros(), |acc, &f| acc + self.normal(f))
+                    .try_normalize(0.0)
+                    .unwrap_or_else(Vector3::zeros);
+                let d = center - self.points[v].coords;
+                (
+                    v,
+                    (self.project)(&(self.points[v] + (d - normal * normal.dot(&d)))),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (v, p) in moved {
+            self.points[v] = p;
+        }
+    }
+
+    fn run(&mut self, iterations: usize) {
+        for _ in 0..iterations {
+            self.split_long_edges();
+            self.collapse_short_edges();
+            self.flip_edges();
+            self.smooth();
+        }
+    }
+}
+
+/// Remesh a triangle mesh so that its edges are close to the target length and its triangles are
+/// close to equilateral. `project` moves a point onto the closest point of the original surface.
+pub fn remesh<P>(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    options: &RemeshOptions,
+    project: &P,
+) -> (Vec<Point3>, Vec<[u32; 3]>)
+where
+    P: Fn(&Point3) -> Point3 + Sync,
+{
+    let mut remesher = Remesher::new(vertices, faces, options, project);
+    remesher.run(options.iterations);
+
+    let mut index = vec![None; remesher.points.len()];
+    let mut points = Vec::new();
+    for (v, slot) in index.iter_mut().enumerate() {
+        if !remesher.vertex_faces[v].is_empty() {
+            *slot = Some(points.len() as u32);
+            points.push(remesher.points[v]);
+        }
+    }
+
+    let faces = remesher
+        .faces
+        .iter()
+        .zip(remesher.alive.iter())
+        .filter(|(_, alive)| **alive)
+        .map(|(f, _)| f.map(|i| index[i].unwrap()))
+        .collect();
+
+    (points, faces)
+}
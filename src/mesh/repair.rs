@@ -0,0 +1,325 @@
+//! Repairs of broken mesh topology. The repairs work on a loose copy of the mesh which remembers,
+//! for every vertex, the vertices of the original mesh it came from, so that the vertex
+//! attributes can follow the vertices through splits and new vertices.
+//!
+//! Vertices at exactly the same position count as one when finding the edges and connected patches
+//! of the mesh, so a mesh with split vertices along its seams is repaired as one surface.
+
+use super::attributes::position_key;
+use engeom::Point3;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub struct MeshParts {
+    pub vertices: Vec<Point3>,
+    pub faces: Vec<[u32; 3]>,
+
+    /// The original vertices each vertex came from, whose attributes are averaged for it
+    pub sources: Vec<Vec<usize>>,
+}
+
+pub(super) fn edge_key(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+pub(super) fn directed_edges(f: &[u32; 3]) -> [(u32, u32); 3] {
+    [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])]
+}
+
+/// The faces with every vertex replaced by the first vertex at exactly the same position, so that
+/// meshes with split vertices along their sharp edges or seams are connected across them.
+pub(super) fn welded_faces(vertices: &[Point3], faces: &[[u32; 3]]) -> Vec<[u32; 3]> {
+    let mut first = HashMap::with_capacity(vertices.len());
+    let canonical = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, p)| *first.entry(position_key(p)).or_insert(i as u32))
+        .collect::<Vec<_>>();
+
+    faces
+        .iter()
+        .map(|f| f.map(|i| canonical[i as usize]))
+        .collect()
+}
+
+/// Finds the root of `i`, compressing the path along the way.
+pub(super) fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+pub(super) fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[a.max(b)] = a.min(b);
+}
+
+/// Group the faces `around` vertex `v` into fans, where faces are in the same fan if they share an
+/// edge out of the vertex. Returns the group of each face, which is the same for faces of the same
+/// fan.
+pub(super) fn vertex_fans(faces: &[[u32; 3]], v: u32, around: &[usize]) -> Vec<usize> {
+    let mut parents = (0..around.len()).collect::<Vec<_>>();
+    let mut by_neighbor: HashMap<u32, usize> = HashMap::new();
+    for (k, &f) in around.iter().enumerate() {
+        for &n in faces[f].iter().filter(|&&n| n != v) {
+            if let Some(&other) = by_neighbor.get(&n) {
+                union(&mut parents, k, other);
+            } else {
+                by_neighbor.insert(n, k);
+            }
+        }
+    }
+
+    (0..around.len()).map(|k| find(&mut parents, k)).collect()
+}
+
+/// The faces which use each undirected edge
+fn edge_faces(faces: &[[u32; 3]]) -> HashMap<(u32, u32), Vec<usize>> {
+    let mut edges: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (fi, f) in faces.iter().enumerate() {
+        for (a, b) in directed_edges(f) {
+            edges.entry(edge_key(a, b)).or_default().push(fi);
+        }
+    }
+    edges
+}
+
+impl MeshParts {
+    pub fn new(vertices: &[Point3], faces: &[[u32; 3]]) -> Self {
+        Self {
+            vertices: vertices.to_vec(),
+            faces: faces.to_vec(),
+            sources: (0..vertices.len()).map(|i| vec![i]).collect(),
+        }
+    }
+
+    /// Remove faces which use the same vertex more than once. Returns the number removed.
+    pub fn remove_degenerate_faces(&mut self) -> usize {
+        let before = self.faces.len();
+        self.faces
+            .retain(|f| f[0] != f[1] && f[1] != f[2] && f[2] != f[0]);
+        before - self.faces.len()
+    }
+
+    /// Remove faces with the same three vertices as an earlier face, in either winding. Returns
+    /// the number removed.
+    pub fn remove_duplicate_faces(&mut self) -> usize {
+        let before = self.faces.len();
+        let mut seen = HashSet::new();
+        let mut welded = welded_faces(&self.vertices, &self.faces).into_iter();
+        self.faces.retain(|_| {
+            let mut key = welded.next().unwrap();
+            key.sort_unstable();
+            seen.insert(key)
+        });
+        before - self.faces.len()
+    }
+
+    /// Remove faces from edges shared by more than two faces, keeping the first face on the edge
+    /// and, preferably, a face which runs the edge the opposite way. Returns the number removed.
+    pub fn fix_non_manifold_edges(&mut self) -> usize {
+        let welded = welded_faces(&self.vertices, &self.faces);
+        let mut edges = edge_faces(&welded)
+            .into_iter()
+            .filter(|(_, faces)| faces.len() > 2)
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+
+        let mut removed = vec![false; self.faces.len()];
+        for ((a, b), faces) in edges {
+            let faces = faces
+                .into_iter()
+                .filter(|&f| !removed[f])
+                .collect::<Vec<_>>();
+            if faces.len() <= 2 {
+                continue;
+            }
+
+            let runs_forward = |f: usize| directed_edges(&welded[f]).contains(&(a, b));
+            let first = faces[0];
+            let partner = faces[1..]
+                .iter()
+                .copied()
+                .find(|&f| runs_forward(f) != runs_forward(first))
+                .unwrap_or(faces[1]);
+            for f in faces {
+                if f != first && f != partner {
+                    removed[f] = true;
+                }
+            }
+        }
+
+        let count = removed.iter().filter(|r| **r).count();
+        let mut i = 0;
+        self.faces.retain(|_| {
+            i += 1;
+            !removed[i - 1]
+        });
+        count
+    }
+
+    /// Split vertices where separate fans of faces only touch at the vertex, giving each extra fan
+    /// its own copy of the vertex. Returns the number of vertices added.
+    pub fn fix_non_manifold_vertices(&mut self) -> usize {
+        let mut vertex_faces = vec![Vec::new(); self.vertices.len()];
+        for (fi, f) in self.faces.iter().enumerate() {
+            for &v in f {
+                vertex_faces[v as usize].push(fi);
+            }
+        }
+
+        let mut added = 0;
+        for (v, around) in vertex_faces.iter().enumerate() {
+            if around.len() < 2 {
+                continue;
+            }
+
+            let fans = vertex_fans(&self.faces, v as u32, around);
+            let mut copies: HashMap<usize, u32> = HashMap::new();
+            for (&f, &root) in around.iter().zip(fans.iter()) {
+                if root == fans[0] {
+                    continue;
+                }
+
+                let copy = *copies.entry(root).or_insert_with(|| {
+                    self.vertices.push(self.vertices[v]);
+                    self.sources.push(self.sources[v].clone());
+                    added += 1;
+                    (self.vertices.len() - 1) as u32
+                });
+                for i in self.faces[f].iter_mut().filter(|i| **i as usize == v) {
+                    *i = copy;
+                }
+            }
+        }
+
+        added
+    }
+
+    /// Make the winding of the faces consistent across each connected patch of faces. A closed
+    /// patch is wound so that its faces point outwards, and an open one keeps the winding of the
+    /// majority of its faces. Returns the number of faces which were turned over.
+    pub fn orient_faces(&mut self) -> usize {
+        let mut faces = welded_faces(&self.vertices, &self.faces);
+        let edges = edge_faces(&faces);
+        let mut flipped = vec![false; self.faces.len()];
+        let mut visited = vec![false; self.faces.len()];
+        let mut queue = VecDeque::new();
+
+        for seed in 0..self.faces.len() {
+            if visited[seed] {
+                continue;
+            }
+
+            let mut patch = Vec::new();
+            let mut closed = true;
+            visited[seed] = true;
+            queue.push_back(seed);
+            while let Some(f) = queue.pop_front() {
+                patch.push(f);
+                for (a, b) in directed_edges(&faces[f]) {
+                    let across = &edges[&edge_key(a, b)];
+                    if across.len() != 2 {
+                        closed &= across.len() > 2;
+                        continue;
+                    }
+
+                    let g = if across[0] == f { across[1] } else { across[0] };
+                    if visited[g] {
+                        continue;
+                    }
+                    visited[g] = true;
+
+                    // A neighbor running the shared edge the same way is wound the other way
+                    if directed_edges(&faces[g]).contains(&(a, b)) {
+                        faces[g].swap(1, 2);
+                        flipped[g] = !flipped[g];
+                    }
+                    queue.push_back(g);
+                }
+            }
+
+            let turn_over = if closed {
+                let volume = patch
+                    .iter()
+                    .map(|&f| {
+                        let [a, b, c] = faces[f].map(|i| self.vertices[i as usize].coords);
+                        a.dot(&b.cross(&c))
+                    })
+                    .sum::<f64>();
+                volume < 0.0
+            } else {
+                2 * patch.iter().filter(|&&f| flipped[f]).count() > patch.len()
+            };
+
+            if turn_over {
+                for &f in patch.iter() {
+                    faces[f].swap(1, 2);
+                    flipped[f] = !flipped[f];
+                }
+            }
+        }
+
+        for (f, _) in flipped.iter().enumerate().filter(|(_, f)| **f) {
+            self.faces[f].swap(1, 2);
+        }
+        flipped.iter().filter(|f| **f).count()
+    }
+
+    /// Remove the groups of faces connected through shared vertices which have fewer than
+    /// `min_faces` faces. Returns the number of groups and the number of faces removed.
+    pub fn remove_islands(&mut self, min_faces: usize) -> (usize, usize) {
+        let welded = welded_faces(&self.vertices, &self.faces);
+        let mut parents = (0..self.vertices.len()).collect::<Vec<_>>();
+        for f in welded.iter() {
+            union(&mut parents, f[0] as usize, f[1] as usize);
+            union(&mut parents, f[0] as usize, f[2] as usize);
+        }
+
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        let roots = welded
+            .iter()
+            .map(|f| find(&mut parents, f[0] as usize))
+            .collect::<Vec<_>>();
+        for &r in roots.iter() {
+            *sizes.entry(r).or_default() += 1;
+        }
+
+        let islands = sizes.values().filter(|&&s| s < min_faces).count();
+        let before = self.faces.len();
+        let mut i = 0;
+        self.faces.retain(|_| {
+            i += 1;
+            sizes[&roots[i - 1]] >= min_faces
+        });
+
+        (islands, before - self.faces.len())
+    }
+
+    /// Drop the vertices which aren't used by any face.
+    pub fn compact(&mut self) {
+        let mut index = vec![None; self.vertices.len()];
+        for f in self.faces.iter() {
+            for &i in f {
+                index[i as usize] = Some(0);
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut sources = Vec::new();
+        for (i, slot) in index.iter_mut().enumerate() {
+            if let Some(s) = slot {
+                *s = vertices.len() as u32;
+                vertices.push(self.vertices[i]);
+                sources.push(std::mem::take(&mut self.sources[i]));
+            }
+        }
+
+        for f in self.faces.iter_mut() {
+            *f = f.map(|i| index[i as usize].unwrap());
+        }
+        self.vertices = vertices;
+        self.sources = sources;
+    }
+}
@@ -0,0 +1,89 @@
+//! Sections of a mesh by a plane. Every face is cut by the plane on its own, which is done in
+//! parallel, and the segments are then joined into polylines where they share a crossing point on
+//! an edge or a vertex of the mesh.
+
+use super::features::chain;
+use super::repair::{directed_edges, edge_key, welded_faces};
+use engeom::{Plane3, Point3};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Where the plane crosses the mesh, either at a vertex or strictly inside an edge
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Crossing {
+    Vertex(u32),
+    Edge(u32, u32),
+}
+
+/// The segment where the plane cuts a face, if it cuts it along a line. Faces lying in the plane
+/// and faces which only touch it at a vertex have no segment.
+fn cut(face: &[u32; 3], distance: &[f64]) -> Option<[Crossing; 2]> {
+    let mut found = [None; 3];
+    let mut n = 0;
+    for (a, b) in directed_edges(face) {
+        let (da, db) = (distance[a as usize], distance[b as usize]);
+        let crossing = if da == 0.0 {
+            Crossing::Vertex(a)
+        } else if da * db < 0.0 {
+            let (a, b) = edge_key(a, b);
+            Crossing::Edge(a, b)
+        } else {
+            continue;
+        };
+        found[n] = Some(crossing);
+        n += 1;
+    }
+
+    match found {
+        [Some(a), Some(b), None] => Some([a.min(b), a.max(b)]),
+        _ => None,
+    }
+}
+
+/// The polylines where the plane cuts the mesh, as the points along each one. Closed loops come
+/// back to their first point at the end. Vertices at the same position are welded first, so the
+/// polylines run across the seams of meshes with split vertices.
+pub fn section(vertices: &[Point3], faces: &[[u32; 3]], plane: &Plane3) -> Vec<Vec<Point3>> {
+    let faces = welded_faces(vertices, faces);
+    let distance = vertices
+        .par_iter()
+        .map(|p| plane.signed_distance_to_point(p))
+        .collect::<Vec<_>>();
+
+    let mut segments = faces
+        .par_iter()
+        .filter_map(|f| cut(f, &distance))
+        .collect::<Vec<_>>();
+
+    // An edge lying in the plane is found by the faces on both sides of it
+    segments.sort_unstable();
+    segments.dedup();
+
+    let mut ids = HashMap::new();
+    let mut crossings = Vec::new();
+    let edges = segments
+        .iter()
+        .map(|s| {
+            s.map(|c| {
+                *ids.entry(c).or_insert_with(|| {
+                    crossings.push(c);
+                    (crossings.len() - 1) as u32
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let point = |c: Crossing| match c {
+        Crossing::Vertex(i) => vertices[i as usize],
+        Crossing::Edge(a, b) => {
+            let (pa, pb) = (vertices[a as usize], vertices[b as usize]);
+            let (da, db) = (distance[a as usize], distance[b as usize]);
+            pa + (pb - pa) * (da / (da - db))
+        }
+    };
+
+    chain(&edges)
+        .into_iter()
+        .map(|c| c.iter().map(|&i| point(crossings[i as usize])).collect())
+        .collect()
+}
@@ -0,0 +1,227 @@
+//! Smoothing filters for noisy meshes. Each filter only moves the vertices marked as movable and
+//! leaves the faces as they are.
+
+use engeom::{Point3, Vector3};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The distinct vertices sharing an edge with each vertex.
+fn neighbors(count: usize, faces: &[[u32; 3]]) -> Vec<Vec<usize>> {
+    let mut adjacent = vec![Vec::new(); count];
+    for f in faces.iter() {
+        for k in 0..3 {
+            let (a, b) = (f[k] as usize, f[(k + 1) % 3] as usize);
+            if a != b {
+                adjacent[a].push(b);
+                adjacent[b].push(a);
+            }
+        }
+    }
+    for a in adjacent.iter_mut() {
+        a.sort_unstable();
+        a.dedup();
+    }
+    adjacent
+}
+
+/// Mark the vertices on edges used by only one face.
+fn boundary_vertices(count: usize, faces: &[[u32; 3]]) -> Vec<bool> {
+    let mut uses: HashMap<(u32, u32), usize> = HashMap::new();
+    for f in faces.iter() {
+        for k in 0..3 {
+            let (a, b) = (f[k], f[(k + 1) % 3]);
+            *uses.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+
+    let mut boundary = vec![false; count];
+    for ((a, b), _) in uses.into_iter().filter(|(_, n)| *n == 1) {
+        boundary[a as usize] = true;
+        boundary[b as usize] = true;
+    }
+    boundary
+}
+
+/// Move each movable vertex by `factor` of the way towards the average of its neighbors.
+fn umbrella_step(
+    points: &[Point3],
+    adjacent: &[Vec<usize>],
+    movable: &[bool],
+    factor: f64,
+) -> Vec<Point3> {
+    points
+        .par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if !movable[i] || adjacent[i].is_empty() {
+                return *p;
+            }
+            let sum = adjacent[i]
+                .iter()
+                .fold(Vector3::zeros(), |acc, &j| acc + points[j].coords);
+            let average = sum / adjacent[i].len() as f64;
+            p + (average - p.coords) * factor
+        })
+        .collect()
+}
+
+fn pin_boundary(vertices: &[Point3], faces: &[[u32; 3]], movable: &[bool]) -> Vec<bool> {
+    let boundary = boundary_vertices(vertices.len(), faces);
+    movable
+        .iter()
+        .zip(boundary.iter())
+        .map(|(m, b)| *m && !b)
+        .collect()
+}
+
+/// Uniform Laplacian smoothing. Vertices on the open boundaries of the mesh stay in place, since
+/// they would otherwise be pulled inwards.
+pub fn laplacian(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    movable: &[bool],
+    iterations: usize,
+    factor: f64,
+) -> Vec<Point3> {
+    let adjacent = neighbors(vertices.len(), faces);
+    let movable = pin_boundary(vertices, faces, movable);
+
+    let mut points = vertices.to_vec();
+    for _ in 0..iterations {
+        points = umbrella_step(&points, &adjacent, &movable, factor);
+    }
+    points
+}
+
+/// Taubin smoothing, where each shrinking Laplacian step with the positive `lambda` is followed by
+/// an inflating step with the negative `mu`, so that the mesh keeps its size.
+pub fn taubin(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    movable: &[bool],
+    iterations: usize,
+    lambda: f64,
+    mu: f64,
+) -> Vec<Point3> {
+    let adjacent = neighbors(vertices.len(), faces);
+    let movable = pin_boundary(vertices, faces, movable);
+
+    let mut points = vertices.to_vec();
+    for _ in 0..iterations {
+        points = umbrella_step(&points, &adjacent, &movable, lambda);
+        points = umbrella_step(&points, &adjacent, &movable, mu);
+    }
+    points
+}
+
+struct FaceGeometry {
+    normals: Vec<Vector3>,
+    centers: Vec<Point3>,
+    areas: Vec<f64>,
+}
+
+impl FaceGeometry {
+    fn new(points: &[Point3], faces: &[[u32; 3]]) -> Self {
+        let mut result = Self {
+            normals: Vec::with_capacity(faces.len()),
+            centers: Vec::with_capacity(faces.len()),
+            areas: Vec::with_capacity(faces.len()),
+        };
+        for f in faces.iter() {
+            let [a, b, c] = f.map(|i| points[i as usize]);
+            let cross = (b - a).cross(&(c - a));
+            result
+                .normals
+                .push(cross.try_normalize(0.0).unwrap_or_else(Vector3::zeros));
+            result
+                .centers
+                .push(Point3::from((a.coords + b.coords + c.coords) / 3.0));
+            result.areas.push(cross.norm() / 2.0);
+        }
+        result
+    }
+}
+
+/// Bilateral normal filtering. The face normals are first smoothed with weights that fall off with
+/// the distance between faces and with the difference between their normals, so that normals on
+/// either side of a sharp edge don't blend, and then the vertices are moved to fit the filtered
+/// normals. `sigma` is the difference between unit normals at which the weight falls off.
+pub fn bilateral(
+    vertices: &[Point3],
+    faces: &[[u32; 3]],
+    movable: &[bool],
+    normal_iterations: usize,
+    vertex_iterations: usize,
+    sigma: f64,
+) -> Vec<Point3> {
+    let mut vertex_faces = vec![Vec::new(); vertices.len()];
+    for (fi, f) in faces.iter().enumerate() {
+        for &v in f {
+            vertex_faces[v as usize].push(fi);
+        }
+    }
+
+    // Faces are neighbors when they share a vertex
+    let face_neighbors = faces
+        .par_iter()
+        .map(|f| {
+            let mut around = f
+                .iter()
+                .flat_map(|&v| vertex_faces[v as usize].iter().copied())
+                .collect::<Vec<_>>();
+            around.sort_unstable();
+            around.dedup();
+            around
+        })
+        .collect::<Vec<_>>();
+
+    let geometry = FaceGeometry::new(vertices, faces);
+
+    // The spatial falloff is the average distance between neighboring faces
+    let (total, count) = face_neighbors
+        .iter()
+        .enumerate()
+        .flat_map(|(i, around)| around.iter().map(move |&j| (i, j)))
+        .filter(|(i, j)| i != j)
+        .fold((0.0, 0usize), |(t, n), (i, j)| {
+            (
+                t + (geometry.centers[i] - geometry.centers[j]).norm(),
+                n + 1,
+            )
+        });
+    let sigma_space = if count > 0 { total / count as f64 } else { 1.0 };
+
+    let mut normals = geometry.normals.clone();
+    for _ in 0..normal_iterations {
+        normals = (0..faces.len())
+            .into_par_iter()
+            .map(|i| {
+                let sum = face_neighbors[i].iter().fold(Vector3::zeros(), |acc, &j| {
+                    let d = (geometry.centers[i] - geometry.centers[j]).norm() / sigma_space;
+                    let r = (normals[i] - normals[j]).norm() / sigma;
+                    acc + normals[j] * (geometry.areas[j] * (-0.5 * (d * d + r * r)).exp())
+                });
+                sum.try_normalize(0.0).unwrap_or(normals[i])
+            })
+            .collect();
+    }
+
+    let mut points = vertices.to_vec();
+    for _ in 0..vertex_iterations {
+        let centers = FaceGeometry::new(&points, faces).centers;
+        points = points
+            .par_iter()
+            .enumerate()
+            .map(|(i, p)| {
+                if !movable[i] || vertex_faces[i].is_empty() {
+                    return *p;
+                }
+                let shift = vertex_faces[i].iter().fold(Vector3::zeros(), |acc, &f| {
+                    acc + normals[f] * normals[f].dot(&(centers[f] - p))
+                });
+                p + shift / vertex_faces[i].len() as f64
+            })
+            .collect();
+    }
+    points
+}
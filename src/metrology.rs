@@ -1,10 +1,12 @@
 use crate::geom2::{Point2, SurfacePoint2, Vector2};
 use crate::geom3::{Iso3, Point3, SurfacePoint3, Vector3};
+use crate::serialization::{serial_methods, Serial};
 use engeom::metrology::Measurement;
-use engeom::UnitVec2;
+use engeom::{UnitVec2, UnitVec3};
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[pyclass]
+#[pyclass(module = "engeom.metrology")]
 pub struct Distance2 {
     inner: engeom::metrology::Distance2,
 }
@@ -19,6 +21,37 @@ impl Distance2 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Distance2State {
+    a: [f64; 2],
+    b: [f64; 2],
+    direction: [f64; 2],
+}
+
+impl Serial for Distance2 {
+    const KIND: &'static str = "Distance2";
+    type State = Distance2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Distance2State {
+            a: self.inner.a.coords.into(),
+            b: self.inner.b.coords.into(),
+            direction: self.inner.direction.into_inner().into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let direction = UnitVec2::new_normalize(state.direction.into());
+        Ok(Self::from_inner(engeom::metrology::Distance2::new(
+            state.a.into(),
+            state.b.into(),
+            Some(direction),
+        )))
+    }
+}
+
+serial_methods!(Distance2);
+
 #[pymethods]
 impl Distance2 {
     fn __repr__(&self) -> String {
@@ -78,7 +111,7 @@ impl Distance2 {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.metrology")]
 pub struct Distance3 {
     inner: engeom::metrology::Distance3,
 }
@@ -93,6 +126,37 @@ impl Distance3 {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Distance3State {
+    a: [f64; 3],
+    b: [f64; 3],
+    direction: [f64; 3],
+}
+
+impl Serial for Distance3 {
+    const KIND: &'static str = "Distance3";
+    type State = Distance3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        Distance3State {
+            a: self.inner.a.coords.into(),
+            b: self.inner.b.coords.into(),
+            direction: self.inner.direction.into_inner().into(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let direction = UnitVec3::new_normalize(state.direction.into());
+        Ok(Self::from_inner(engeom::metrology::Distance3::new(
+            state.a.into(),
+            state.b.into(),
+            Some(direction),
+        )))
+    }
+}
+
+serial_methods!(Distance3);
+
 #[pymethods]
 impl Distance3 {
     fn __repr__(&self) -> String {
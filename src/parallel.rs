@@ -0,0 +1,29 @@
+//! Running long computations without holding the GIL, spread over a pool of worker threads.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Release the GIL and run `op`. Any rayon parallel iterators inside of `op`, including the ones
+/// used within engeom, will run on `threads` worker threads, or on the global pool with one
+/// thread per core if `threads` is `None`.
+pub fn run<T, F>(py: Python<'_>, threads: Option<usize>, op: F) -> PyResult<T>
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    let pool = match threads {
+        None => None,
+        Some(0) => return Err(PyValueError::new_err("threads must be at least 1")),
+        Some(n) => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+        ),
+    };
+
+    Ok(py.allow_threads(move || match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }))
+}
@@ -0,0 +1,667 @@
+mod ball_pivot;
+mod clusters;
+mod filters;
+mod normals;
+
+use crate::conversions::{
+    array_to_colors, array_to_points3, array_to_vectors3, colors_to_array, points_to_array3,
+    vectors_to_array3,
+};
+use crate::geom3::{Iso3, Point3};
+use crate::kdtree::KdTree3;
+use crate::mesh::{Mesh, VertexAttributes};
+use crate::parallel;
+use crate::serialization::{serial_methods, Serial};
+use normals::Neighborhood;
+use numpy::ndarray::{Array1, Array2, Ix1, Ix2};
+use numpy::{IntoPyArray, PyArray, PyArray1, PyArrayDyn, PyReadonlyArray1, PyReadonlyArrayDyn};
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The indices of neighboring points and the distances to them
+type Neighbors<'py, D> = (Bound<'py, PyArray<usize, D>>, Bound<'py, PyArray<f64, D>>);
+
+#[pyclass(module = "engeom.geom3")]
+pub struct PointCloud {
+    tree: KdTree3,
+    attributes: VertexAttributes,
+    points: Option<Py<PyArrayDyn<f64>>>,
+    normals: Option<Py<PyArrayDyn<f64>>>,
+    colors: Option<Py<PyArrayDyn<u8>>>,
+}
+
+impl PointCloud {
+    fn clear_cached(&mut self) {
+        self.points = None;
+        self.normals = None;
+        self.colors = None;
+    }
+
+    pub fn get_tree(&self) -> &KdTree3 {
+        &self.tree
+    }
+
+    pub fn get_points(&self) -> &[engeom::Point3] {
+        self.tree.points()
+    }
+
+    pub fn get_attributes(&self) -> &VertexAttributes {
+        &self.attributes
+    }
+
+    /// The tree and the normals of the cloud, for changing the signs of the normals in place.
+    fn tree_and_normals(&mut self) -> PyResult<(&KdTree3, &mut [engeom::Vector3])> {
+        self.normals = None;
+        let normals = self
+            .attributes
+            .normals
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("The point cloud has no normals to orient"))?;
+        Ok((&self.tree, normals))
+    }
+
+    /// Create a point cloud from its points and attributes, which must have already been checked
+    /// to have one entry for each point.
+    pub fn from_parts(points: &[engeom::Point3], attributes: VertexAttributes) -> Self {
+        Self {
+            tree: KdTree3::new(points),
+            attributes,
+            points: None,
+            normals: None,
+            colors: None,
+        }
+    }
+}
+
+impl Clone for PointCloud {
+    fn clone(&self) -> Self {
+        Self::from_parts(self.tree.points(), self.attributes.clone())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PointCloudState {
+    points: Vec<[f64; 3]>,
+    normals: Option<Vec<[f64; 3]>>,
+    colors: Option<Vec<[u8; 4]>>,
+    scalars: Vec<(String, Vec<f64>)>,
+}
+
+impl Serial for PointCloud {
+    const KIND: &'static str = "PointCloud";
+    type State = PointCloudState;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        let attributes = &self.attributes;
+        PointCloudState {
+            points: self.tree.points().iter().map(|p| p.coords.into()).collect(),
+            normals: attributes
+                .normals
+                .as_ref()
+                .map(|n| n.iter().map(|&v| v.into()).collect()),
+            colors: attributes.colors.clone(),
+            scalars: attributes.scalars.clone(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let points = state
+            .points
+            .into_iter()
+            .map(engeom::Point3::from)
+            .collect::<Vec<_>>();
+        let attributes = VertexAttributes {
+            normals: state
+                .normals
+                .map(|n| n.into_iter().map(engeom::Vector3::from).collect()),
+            colors: state.colors,
+            scalars: state.scalars,
+        };
+        attributes
+            .check_len(points.len())
+            .map_err(PyValueError::new_err)?;
+
+        Ok(Self::from_parts(&points, attributes))
+    }
+}
+
+serial_methods!(PointCloud);
+
+#[pymethods]
+impl PointCloud {
+    #[new]
+    #[pyo3(signature=(points, normals = None, colors = None))]
+    fn new<'py>(
+        points: PyReadonlyArrayDyn<'py, f64>,
+        normals: Option<PyReadonlyArrayDyn<'py, f64>>,
+        colors: Option<PyReadonlyArrayDyn<'py, u8>>,
+    ) -> PyResult<Self> {
+        let points = array_to_points3(&points.as_array())?;
+        let attributes = VertexAttributes {
+            normals: normals
+                .map(|n| array_to_vectors3(&n.as_array()))
+                .transpose()?,
+            colors: colors.map(|c| array_to_colors(&c.as_array())).transpose()?,
+            scalars: Vec::new(),
+        };
+        attributes
+            .check_len(points.len())
+            .map_err(PyValueError::new_err)?;
+
+        Ok(Self::from_parts(&points, attributes))
+    }
+
+    fn __len__(&self) -> usize {
+        self.tree.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<PointCloud {} points>", self.tree.len())
+    }
+
+    fn cloned(&self) -> Self {
+        self.clone()
+    }
+
+    #[getter]
+    fn points<'py>(&mut self, py: Python<'py>) -> &Bound<'py, PyArrayDyn<f64>> {
+        if self.points.is_none() {
+            let array = points_to_array3(self.tree.points());
+            self.points = Some(array.into_pyarray(py).unbind());
+        }
+        self.points.as_ref().unwrap().bind(py)
+    }
+
+    #[getter]
+    fn normals<'py>(&mut self, py: Python<'py>) -> Option<&Bound<'py, PyArrayDyn<f64>>> {
+        let normals = self.attributes.normals.as_ref()?;
+        if self.normals.is_none() {
+            let array = vectors_to_array3(normals);
+            self.normals = Some(array.into_pyarray(py).unbind());
+        }
+
+        self.normals.as_ref().map(|n| n.bind(py))
+    }
+
+    #[pyo3(signature=(normals = None))]
+    fn set_normals(&mut self, normals: Option<PyReadonlyArrayDyn<'_, f64>>) -> PyResult<()> {
+        let normals = normals
+            .map(|n| array_to_vectors3(&n.as_array()))
+            .transpose()?;
+
+        if let Some(n) = &normals
+            && n.len() != self.tree.len()
+        {
+            return Err(PyValueError::new_err(
+                "There must be one normal for each point",
+            ));
+        }
+
+        self.attributes.normals = normals;
+        self.normals = None;
+        Ok(())
+    }
+
+    #[pyo3(signature=(k = None, radius = None, threads = None))]
+    fn estimate_normals(
+        &mut self,
+        py: Python<'_>,
+        k: Option<usize>,
+        radius: Option<f64>,
+        threads: Option<usize>,
+    ) -> PyResult<()> {
+        let neighborhood = Neighborhood::from_options(k, radius).map_err(PyValueError::new_err)?;
+        let tree = &self.tree;
+        let mut estimated = parallel::run(py, threads, || normals::estimate(tree, neighborhood))?;
+
+        // Re-estimating should not undo an orientation which has already been chosen
+        if let Some(existing) = &self.attributes.normals {
+            normals::orient_like(&mut estimated, existing);
+        }
+
+        self.attributes.normals = Some(estimated);
+        self.normals = None;
+        Ok(())
+    }
+
+    fn orient_normals_towards(&mut self, point: Point3) -> PyResult<()> {
+        let (tree, current) = self.tree_and_normals()?;
+        let viewpoints = std::iter::repeat(point.get_inner());
+        normals::orient_towards(tree.points(), current, viewpoints);
+        Ok(())
+    }
+
+    fn orient_normals_to_origins(&mut self, origins: PyReadonlyArrayDyn<'_, f64>) -> PyResult<()> {
+        let origins = array_to_points3(&origins.as_array())?;
+        if origins.len() != self.tree.len() {
+            return Err(PyValueError::new_err(
+                "There must be one sensor origin for each point",
+            ));
+        }
+
+        let (tree, current) = self.tree_and_normals()?;
+        normals::orient_towards(tree.points(), current, origins.iter());
+        Ok(())
+    }
+
+    #[pyo3(signature=(k = 16, threads = None))]
+    fn orient_normals_mst(
+        &mut self,
+        py: Python<'_>,
+        k: usize,
+        threads: Option<usize>,
+    ) -> PyResult<()> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k must be at least 1"));
+        }
+
+        let (tree, current) = self.tree_and_normals()?;
+        parallel::run(py, threads, || normals::orient_mst(tree, current, k))
+    }
+
+    #[getter]
+    fn colors<'py>(&mut self, py: Python<'py>) -> Option<&Bound<'py, PyArrayDyn<u8>>> {
+        let colors = self.attributes.colors.as_ref()?;
+        if self.colors.is_none() {
+            let array = colors_to_array(colors);
+            self.colors = Some(array.into_pyarray(py).unbind());
+        }
+
+        self.colors.as_ref().map(|c| c.bind(py))
+    }
+
+    #[pyo3(signature=(colors = None))]
+    fn set_colors(&mut self, colors: Option<PyReadonlyArrayDyn<'_, u8>>) -> PyResult<()> {
+        let colors = colors.map(|c| array_to_colors(&c.as_array())).transpose()?;
+
+        if let Some(c) = &colors
+            && c.len() != self.tree.len()
+        {
+            return Err(PyValueError::new_err(
+                "There must be one color for each point",
+            ));
+        }
+
+        self.attributes.colors = colors;
+        self.colors = None;
+        Ok(())
+    }
+
+    #[getter]
+    fn scalar_field_names(&self) -> Vec<String> {
+        self.attributes
+            .scalars
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    fn get_scalar_field<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let values = self
+            .attributes
+            .scalar(name)
+            .ok_or_else(|| PyKeyError::new_err(format!("No scalar field named '{}'", name)))?;
+
+        Ok(Array1::from(values.to_vec()).into_pyarray(py))
+    }
+
+    fn set_scalar_field(&mut self, name: &str, values: PyReadonlyArray1<'_, f64>) -> PyResult<()> {
+        let values = values.as_array().to_vec();
+        if values.len() != self.tree.len() {
+            return Err(PyValueError::new_err(
+                "There must be one value for each point",
+            ));
+        }
+
+        self.attributes.set_scalar(name, values);
+        Ok(())
+    }
+
+    fn remove_scalar_field(&mut self, name: &str) -> bool {
+        self.attributes.remove_scalar(name)
+    }
+
+    fn transform_by(&mut self, iso: &Iso3) {
+        let points = self
+            .tree
+            .points()
+            .iter()
+            .map(|p| iso.get_inner() * p)
+            .collect::<Vec<_>>();
+        self.tree = KdTree3::new(&points);
+        self.attributes.transform(iso.get_inner());
+
+        self.clear_cached()
+    }
+
+    fn append(&mut self, other: &PointCloud) {
+        let points = [self.tree.points(), other.tree.points()].concat();
+        self.attributes =
+            self.attributes
+                .extended(self.tree.len(), &other.attributes, other.tree.len());
+        self.tree = KdTree3::new(&points);
+
+        self.clear_cached()
+    }
+
+    fn create_from_indices(&self, indices: Vec<usize>) -> PyResult<Self> {
+        let n = self.tree.len();
+        if let Some(i) = indices.iter().find(|&&i| i >= n) {
+            return Err(PyValueError::new_err(format!(
+                "Index {} is out of range for a cloud of {} points",
+                i, n
+            )));
+        }
+
+        let points = indices
+            .iter()
+            .map(|&i| self.tree.points()[i])
+            .collect::<Vec<_>>();
+        let gather = indices.into_iter().map(Some).collect::<Vec<_>>();
+
+        Ok(Self::from_parts(&points, self.attributes.gather(&gather)))
+    }
+
+    #[pyo3(signature=(points, threads = None))]
+    fn nearest<'py>(
+        &self,
+        py: Python<'py>,
+        points: PyReadonlyArrayDyn<'py, f64>,
+        threads: Option<usize>,
+    ) -> PyResult<Neighbors<'py, Ix1>> {
+        if self.tree.is_empty() {
+            return Err(PyValueError::new_err("The point cloud is empty"));
+        }
+
+        let queries = array_to_points3(&points.as_array())?;
+        let tree = &self.tree;
+        let found: Vec<(usize, f64)> = parallel::run(py, threads, || {
+            queries
+                .par_iter()
+                .map(|p| tree.nearest(p).unwrap())
+                .collect()
+        })?;
+
+        let (indices, distances): (Vec<_>, Vec<_>) = found.into_iter().unzip();
+        Ok((
+            Array1::from(indices).into_pyarray(py),
+            Array1::from(distances).into_pyarray(py),
+        ))
+    }
+
+    #[pyo3(signature=(points, k, threads = None))]
+    fn nearest_k<'py>(
+        &self,
+        py: Python<'py>,
+        points: PyReadonlyArrayDyn<'py, f64>,
+        k: usize,
+        threads: Option<usize>,
+    ) -> PyResult<Neighbors<'py, Ix2>> {
+        if k == 0 || k > self.tree.len() {
+            return Err(PyValueError::new_err(format!(
+                "k must be between 1 and the number of points ({})",
+                self.tree.len()
+            )));
+        }
+
+        let queries = array_to_points3(&points.as_array())?;
+        let tree = &self.tree;
+        let found: Vec<Vec<(usize, f64)>> = parallel::run(py, threads, || {
+            queries.par_iter().map(|p| tree.nearest_k(p, k)).collect()
+        })?;
+
+        let mut indices = Array2::zeros((found.len(), k));
+        let mut distances = Array2::zeros((found.len(), k));
+        for (row, neighbors) in found.iter().enumerate() {
+            for (col, (i, d)) in neighbors.iter().enumerate() {
+                indices[[row, col]] = *i;
+                distances[[row, col]] = *d;
+            }
+        }
+
+        Ok((indices.into_pyarray(py), distances.into_pyarray(py)))
+    }
+
+    #[pyo3(signature=(points, radius, threads = None))]
+    fn within_radius<'py>(
+        &self,
+        py: Python<'py>,
+        points: PyReadonlyArrayDyn<'py, f64>,
+        radius: f64,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<Bound<'py, PyArray1<usize>>>> {
+        if radius.is_nan() || radius < 0.0 {
+            return Err(PyValueError::new_err("The radius must not be negative"));
+        }
+
+        let queries = array_to_points3(&points.as_array())?;
+        let tree = &self.tree;
+        let found: Vec<Vec<(usize, f64)>> = parallel::run(py, threads, || {
+            queries.par_iter().map(|p| tree.within(p, radius)).collect()
+        })?;
+
+        Ok(found
+            .into_iter()
+            .map(|n| Array1::from_iter(n.into_iter().map(|(i, _)| i)).into_pyarray(py))
+            .collect())
+    }
+
+    #[pyo3(signature=(voxel_size, threads = None))]
+    fn voxel_downsample<'py>(
+        &self,
+        py: Python<'py>,
+        voxel_size: f64,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArray1<usize>>> {
+        check_voxel_size(voxel_size)?;
+        let points = self.tree.points();
+        let kept = parallel::run(py, threads, || filters::voxel_nearest(points, voxel_size))?;
+        Ok(Array1::from(kept).into_pyarray(py))
+    }
+
+    fn voxel_centroids(&self, voxel_size: f64) -> PyResult<Self> {
+        check_voxel_size(voxel_size)?;
+        let points = self.tree.points();
+        let groups = filters::voxel_groups(points, voxel_size);
+        let centroids = groups
+            .iter()
+            .map(|g| filters::centroid(points, g))
+            .collect::<Vec<_>>();
+
+        Ok(Self::from_parts(
+            &centroids,
+            self.attributes.averaged(&groups),
+        ))
+    }
+
+    #[pyo3(signature=(count, seed = None))]
+    fn sample_random<'py>(
+        &self,
+        py: Python<'py>,
+        count: usize,
+        seed: Option<u64>,
+    ) -> Bound<'py, PyArray1<usize>> {
+        let kept = filters::random(self.tree.len(), count, seed);
+        Array1::from(kept).into_pyarray(py)
+    }
+
+    #[pyo3(signature=(count, start = 0, threads = None))]
+    fn sample_farthest<'py>(
+        &self,
+        py: Python<'py>,
+        count: usize,
+        start: usize,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArray1<usize>>> {
+        if start >= self.tree.len() {
+            return Err(PyValueError::new_err(format!(
+                "Start index {} is out of range for a cloud of {} points",
+                start,
+                self.tree.len()
+            )));
+        }
+
+        let points = self.tree.points();
+        let kept = parallel::run(py, threads, || filters::farthest(points, count, start))?;
+        Ok(Array1::from(kept).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(k, std_ratio, threads = None))]
+    fn filter_statistical_outliers<'py>(
+        &self,
+        py: Python<'py>,
+        k: usize,
+        std_ratio: f64,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArray1<usize>>> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k must be at least 1"));
+        }
+        if std_ratio.is_nan() {
+            return Err(PyValueError::new_err(
+                "The standard deviation ratio must be a number",
+            ));
+        }
+
+        let tree = &self.tree;
+        let kept = parallel::run(py, threads, || {
+            filters::statistical_inliers(tree, k, std_ratio)
+        })?;
+        Ok(Array1::from(kept).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(radius, min_neighbors, threads = None))]
+    fn filter_radius_outliers<'py>(
+        &self,
+        py: Python<'py>,
+        radius: f64,
+        min_neighbors: usize,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArray1<usize>>> {
+        if radius.is_nan() || radius < 0.0 {
+            return Err(PyValueError::new_err("The radius must not be negative"));
+        }
+
+        let tree = &self.tree;
+        let kept = parallel::run(py, threads, || {
+            filters::radius_inliers(tree, radius, min_neighbors)
+        })?;
+        Ok(Array1::from(kept).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(tolerance, min_size = 1))]
+    fn cluster_euclidean<'py>(
+        &self,
+        py: Python<'py>,
+        tolerance: f64,
+        min_size: usize,
+    ) -> PyResult<Bound<'py, PyArray1<i64>>> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(PyValueError::new_err("The tolerance must not be negative"));
+        }
+
+        let tree = &self.tree;
+        let labels = py.allow_threads(|| clusters::euclidean(tree, tolerance, min_size));
+        Ok(Array1::from(labels).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(eps, min_points, threads = None))]
+    fn cluster_dbscan<'py>(
+        &self,
+        py: Python<'py>,
+        eps: f64,
+        min_points: usize,
+        threads: Option<usize>,
+    ) -> PyResult<Bound<'py, PyArray1<i64>>> {
+        if eps.is_nan() || eps < 0.0 {
+            return Err(PyValueError::new_err("eps must not be negative"));
+        }
+
+        let tree = &self.tree;
+        let labels = parallel::run(py, threads, || clusters::dbscan(tree, eps, min_points))?;
+        Ok(Array1::from(labels).into_pyarray(py))
+    }
+
+    #[pyo3(signature=(max_angle, k = None, radius = None, min_size = 1))]
+    fn cluster_region_growing<'py>(
+        &self,
+        py: Python<'py>,
+        max_angle: f64,
+        k: Option<usize>,
+        radius: Option<f64>,
+        min_size: usize,
+    ) -> PyResult<Bound<'py, PyArray1<i64>>> {
+        let neighborhood = Neighborhood::from_options(k, radius).map_err(PyValueError::new_err)?;
+        let normals = self.attributes.normals.as_ref().ok_or_else(|| {
+            PyValueError::new_err("The point cloud needs normals for region growing")
+        })?;
+
+        let tree = &self.tree;
+        let labels = py.allow_threads(|| {
+            clusters::region_growing(tree, normals, neighborhood, max_angle, min_size)
+        });
+        Ok(Array1::from(labels).into_pyarray(py))
+    }
+
+    fn reconstruct_ball_pivoting(&self, py: Python<'_>, radii: Vec<f64>) -> PyResult<Mesh> {
+        let normals = self.attributes.normals.as_ref().ok_or_else(|| {
+            PyValueError::new_err("The point cloud needs normals for ball pivoting")
+        })?;
+        if radii.is_empty() || radii.iter().any(|r| !(*r > 0.0 && r.is_finite())) {
+            return Err(PyValueError::new_err(
+                "At least one radius is needed, and every radius must be greater than zero",
+            ));
+        }
+
+        let mut radii = radii;
+        radii.sort_by(f64::total_cmp);
+        let tree = &self.tree;
+        let faces = parallel::run(py, None, || ball_pivot::reconstruct(tree, normals, &radii))?;
+        if faces.is_empty() {
+            return Err(PyValueError::new_err(
+                "No triangles could be made, the radius may be too small for the point spacing",
+            ));
+        }
+
+        // Only the points which ended up in a triangle become vertices
+        let mut index = vec![None; self.tree.len()];
+        let mut kept = Vec::new();
+        let faces = faces
+            .into_iter()
+            .map(|f| {
+                f.map(|i| {
+                    *index[i as usize].get_or_insert_with(|| {
+                        kept.push(i as usize);
+                        (kept.len() - 1) as u32
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let vertices = kept.iter().map(|&i| self.tree.points()[i]).collect();
+        let inner = engeom::Mesh::new_with_options(vertices, faces, false, false, false, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let gather = kept.into_iter().map(Some).collect::<Vec<_>>();
+
+        Ok(Mesh::from_inner_with_attributes(
+            inner,
+            self.attributes.gather(&gather),
+        ))
+    }
+}
+
+fn check_voxel_size(voxel_size: f64) -> PyResult<()> {
+    if voxel_size > 0.0 && voxel_size.is_finite() {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(
+            "The voxel size must be greater than zero",
+        ))
+    }
+}
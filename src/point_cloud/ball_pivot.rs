@@ -0,0 +1,258 @@
+//! Surface reconstruction by ball pivoting (Bernardini et al., "The ball-pivoting algorithm for
+//! surface reconstruction", 1999). A ball of a given radius is placed so that it touches three
+//! points without containing any others, and the triangle between them is the seed of a mesh. The
+//! ball is then rolled over each edge on the boundary of the mesh until it touches another point,
+//! which forms the next triangle, until no edge can be pivoted any further.
+//!
+//! Triangles are wound counter-clockwise when seen from the side the point normals face, and the
+//! ball always sits on that side. Every edge on the front of the growing mesh is kept directed the
+//! same way as in its triangle, so the triangle behind edge `(a, b)` with opposite point `c` is
+//! `(a, b, c)`.
+
+use crate::kdtree::KdTree3;
+use engeom::{Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::TAU;
+
+struct FrontEdge {
+    opposite: usize,
+
+    /// Set once the ball has failed to pivot over the edge at the current radius
+    boundary: bool,
+}
+
+struct Pivoter<'a> {
+    tree: &'a KdTree3,
+    normals: &'a [Vector3],
+    radius: f64,
+    faces: Vec<[u32; 3]>,
+
+    /// Whether each point is a vertex of a triangle
+    used: Vec<bool>,
+
+    /// The number of front edges which touch each point
+    on_front: Vec<usize>,
+
+    /// Every directed edge of every triangle, an edge can only be used once in each direction
+    directed: HashSet<(usize, usize)>,
+    front: HashMap<(usize, usize), FrontEdge>,
+    queue: Vec<(usize, usize)>,
+}
+
+/// The center of the ball of `radius` touching the three points, on the side which the triangle
+/// `(a, b, c)` faces when wound counter-clockwise. Returns `None` if the triangle is degenerate or
+/// the ball is too small to touch all three points.
+fn ball_center(a: &Point3, b: &Point3, c: &Point3, radius: f64) -> Option<Point3> {
+    let ab = b - a;
+    let ac = c - a;
+    let n = ab.cross(&ac);
+    let n2 = n.norm_squared();
+    if n2 <= (1e-12 * ab.norm() * ac.norm()).powi(2) {
+        return None;
+    }
+
+    let circumcenter =
+        a + (n.cross(&ab) * ac.norm_squared() + ac.cross(&n) * ab.norm_squared()) / (2.0 * n2);
+    let h2 = radius * radius - (circumcenter - a).norm_squared();
+    if h2 < 0.0 {
+        return None;
+    }
+
+    Some(circumcenter + n / n2.sqrt() * h2.sqrt())
+}
+
+impl Pivoter<'_> {
+    fn point(&self, i: usize) -> &Point3 {
+        &self.tree.points()[i]
+    }
+
+    /// Whether the triangle `(a, b, c)` faces the same way as the normals of its points.
+    fn agrees_with_normals(&self, a: usize, b: usize, c: usize) -> bool {
+        let n = (self.point(b) - self.point(a)).cross(&(self.point(c) - self.point(a)));
+        [a, b, c].iter().all(|&i| n.dot(&self.normals[i]) >= 0.0)
+    }
+
+    /// Whether there are no points inside the ball, other than on its surface.
+    fn is_empty_ball(&self, center: &Point3, touching: [usize; 3]) -> bool {
+        self.tree
+            .within(center, self.radius * (1.0 - 1e-9))
+            .iter()
+            .all(|(i, _)| touching.contains(i))
+    }
+
+    fn add_edge(&mut self, a: usize, b: usize, opposite: usize) {
+        // If the edge is already on the front in the other direction, the two triangles close it
+        if self.front.remove(&(b, a)).is_some() {
+            self.on_front[a] -= 1;
+            self.on_front[b] -= 1;
+        } else {
+            self.front.insert(
+                (a, b),
+                FrontEdge {
+                    opposite,
+                    boundary: false,
+                },
+            );
+            self.on_front[a] += 1;
+            self.on_front[b] += 1;
+            self.queue.push((a, b));
+        }
+    }
+
+    fn add_triangle(&mut self, a: usize, b: usize, c: usize) {
+        self.faces.push([a as u32, b as u32, c as u32]);
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            self.directed.insert((u, v));
+        }
+        for i in [a, b, c] {
+            self.used[i] = true;
+        }
+
+        self.add_edge(a, b, c);
+        self.add_edge(b, c, a);
+        self.add_edge(c, a, b);
+    }
+
+    /// Try to place the ball on a triangle with `seed` as one of its corners, taking the closest
+    /// unused neighbors first.
+    fn find_seed(&mut self, seed: usize) -> bool {
+        let neighbors = self
+            .tree
+            .within(self.point(seed), 2.0 * self.radius)
+            .into_iter()
+            .map(|(i, _)| i)
+            .filter(|&i| i != seed && !self.used[i])
+            .collect::<Vec<_>>();
+
+        for (n, &i) in neighbors.iter().enumerate() {
+            for &j in &neighbors[n + 1..] {
+                let (a, b, c) = if self.agrees_with_normals(seed, i, j) {
+                    (seed, i, j)
+                } else {
+                    (seed, j, i)
+                };
+                if !self.agrees_with_normals(a, b, c) {
+                    continue;
+                }
+
+                let Some(center) =
+                    ball_center(self.point(a), self.point(b), self.point(c), self.radius)
+                else {
+                    continue;
+                };
+                if self.is_empty_ball(&center, [a, b, c]) {
+                    self.add_triangle(a, b, c);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Roll the ball over the front edge `(a, b)` and find the first point it touches, returning
+    /// that point if a valid triangle can be made with it.
+    fn pivot(&self, a: usize, b: usize, opposite: usize) -> Option<usize> {
+        let pa = self.point(a);
+        let pb = self.point(b);
+        let start = ball_center(pa, pb, self.point(opposite), self.radius)?;
+
+        let axis = (pb - pa).normalize();
+        let mid = Point3::from((pa.coords + pb.coords) / 2.0);
+        let flatten = |v: Vector3| v - axis * axis.dot(&v);
+        let from = flatten(start - mid);
+
+        let mut best: Option<(f64, usize)> = None;
+        for (x, _) in self.tree.within(&mid, 2.0 * self.radius) {
+            if x == a || x == b || x == opposite {
+                continue;
+            }
+
+            // The new triangle is on the other side of the edge, so it runs the edge backwards
+            let Some(center) = ball_center(pb, pa, self.point(x), self.radius) else {
+                continue;
+            };
+            if !self.agrees_with_normals(b, a, x) {
+                continue;
+            }
+
+            let to = flatten(center - mid);
+            let mut angle = axis.dot(&from.cross(&to)).atan2(from.dot(&to));
+            if angle < 0.0 {
+                angle += TAU;
+            }
+            if best.is_none_or(|(best_angle, _)| angle < best_angle) {
+                best = Some((angle, x));
+            }
+        }
+
+        let (_, x) = best?;
+
+        // Joining onto a point inside the mesh, or reusing a directed edge, would make the mesh
+        // non-manifold
+        let inner = self.used[x] && self.on_front[x] == 0;
+        if inner || self.directed.contains(&(a, x)) || self.directed.contains(&(x, b)) {
+            return None;
+        }
+
+        Some(x)
+    }
+
+    fn expand_front(&mut self) {
+        while let Some((a, b)) = self.queue.pop() {
+            let opposite = match self.front.get(&(a, b)) {
+                Some(edge) if !edge.boundary => edge.opposite,
+                _ => continue,
+            };
+
+            match self.pivot(a, b, opposite) {
+                Some(x) => self.add_triangle(b, a, x),
+                None => self.front.get_mut(&(a, b)).unwrap().boundary = true,
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        for seed in 0..self.used.len() {
+            if !self.used[seed] && self.find_seed(seed) {
+                self.expand_front();
+            }
+        }
+    }
+}
+
+/// Reconstruct a triangle mesh from points with normals by ball pivoting, with one pass for each
+/// radius in increasing order. Later passes with a larger ball continue from the boundary left by
+/// the earlier ones, which fills in areas where the points are sparser. The faces index into the
+/// points of the tree, and points which were not reached are not used by any face.
+pub fn reconstruct(tree: &KdTree3, normals: &[Vector3], radii: &[f64]) -> Vec<[u32; 3]> {
+    let n = tree.len();
+    let mut pivoter = Pivoter {
+        tree,
+        normals,
+        radius: 0.0,
+        faces: Vec::new(),
+        used: vec![false; n],
+        on_front: vec![0; n],
+        directed: HashSet::new(),
+        front: HashMap::new(),
+        queue: Vec::new(),
+    };
+
+    for &radius in radii {
+        pivoter.radius = radius;
+
+        // The boundary left by a smaller ball gets another chance with this one
+        for (key, edge) in pivoter.front.iter_mut() {
+            if edge.boundary {
+                edge.boundary = false;
+                pivoter.queue.push(*key);
+            }
+        }
+
+        pivoter.expand_front();
+        pivoter.run();
+    }
+
+    pivoter.faces
+}
@@ -0,0 +1,155 @@
+//! Clustering of point clouds into connected groups. Each method produces one label per point,
+//! where clusters are numbered from zero in the order of their lowest point index and points
+//! which belong to no cluster are labeled -1.
+
+use super::normals::Neighborhood;
+use crate::kdtree::KdTree3;
+use engeom::Vector3;
+use rayon::prelude::*;
+
+/// The label given to points which are not part of any cluster
+pub const UNCLUSTERED: i64 = -1;
+
+/// Flood fill clusters from each unlabeled point in index order. `expand` decides whether a point
+/// which has been reached can spread the cluster further, and `neighbors` gives the points it
+/// spreads to. Points which are not allowed to start a cluster are skipped as seeds.
+fn flood(
+    n: usize,
+    can_seed: impl Fn(usize) -> bool,
+    expand: impl Fn(usize) -> bool,
+    neighbors: impl Fn(usize) -> Vec<usize>,
+) -> Vec<i64> {
+    let mut labels = vec![UNCLUSTERED; n];
+    let mut next = 0;
+    let mut stack = Vec::new();
+
+    for seed in 0..n {
+        if labels[seed] != UNCLUSTERED || !can_seed(seed) {
+            continue;
+        }
+
+        labels[seed] = next;
+        stack.push(seed);
+        while let Some(i) = stack.pop() {
+            if !expand(i) {
+                continue;
+            }
+            for j in neighbors(i) {
+                if labels[j] == UNCLUSTERED {
+                    labels[j] = next;
+                    stack.push(j);
+                }
+            }
+        }
+        next += 1;
+    }
+
+    labels
+}
+
+/// Remove the clusters with fewer than `min_size` points and number the rest consecutively,
+/// keeping their order.
+fn drop_small(labels: &mut [i64], min_size: usize) {
+    let count = labels.iter().max().map_or(0, |m| (m + 1) as usize);
+    let mut sizes = vec![0; count];
+    for &l in labels.iter().filter(|l| **l >= 0) {
+        sizes[l as usize] += 1;
+    }
+
+    let mut next = 0;
+    let renumber = sizes
+        .iter()
+        .map(|&s| {
+            if s < min_size {
+                UNCLUSTERED
+            } else {
+                next += 1;
+                next - 1
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for l in labels.iter_mut().filter(|l| **l >= 0) {
+        *l = renumber[*l as usize];
+    }
+}
+
+/// Group points which can be reached from each other in steps no longer than `tolerance`.
+pub fn euclidean(tree: &KdTree3, tolerance: f64, min_size: usize) -> Vec<i64> {
+    let points = tree.points();
+    let mut labels = flood(
+        points.len(),
+        |_| true,
+        |_| true,
+        |i| {
+            tree.within(&points[i], tolerance)
+                .into_iter()
+                .map(|(j, _)| j)
+                .collect()
+        },
+    );
+
+    drop_small(&mut labels, min_size);
+    labels
+}
+
+/// Density based clustering (DBSCAN, Ester et al. 1996). A point with at least `min_points`
+/// points within `eps` of it (counting itself) is a core point, clusters are the groups of core
+/// points within `eps` of each other plus the points within `eps` of them, and all other points
+/// are noise.
+pub fn dbscan(tree: &KdTree3, eps: f64, min_points: usize) -> Vec<i64> {
+    let points = tree.points();
+    let core = points
+        .par_iter()
+        .map(|p| tree.within(p, eps).len() >= min_points)
+        .collect::<Vec<_>>();
+
+    flood(
+        points.len(),
+        |i| core[i],
+        |i| core[i],
+        |i| {
+            tree.within(&points[i], eps)
+                .into_iter()
+                .map(|(j, _)| j)
+                .collect()
+        },
+    )
+}
+
+/// Grow smooth regions across the neighborhoods of the points, only crossing from one point to
+/// a neighbor when the angle between their normals is no more than `max_angle`. The normals may
+/// be unoriented, so opposite normals are treated as parallel. Points with a zero normal are left
+/// out of every region.
+pub fn region_growing(
+    tree: &KdTree3,
+    normals: &[Vector3],
+    neighborhood: Neighborhood,
+    max_angle: f64,
+    min_size: usize,
+) -> Vec<i64> {
+    let points = tree.points();
+    let unit = normals
+        .iter()
+        .map(|n| n.try_normalize(0.0))
+        .collect::<Vec<_>>();
+    let min_cos = max_angle.cos();
+    let mut labels = flood(
+        points.len(),
+        |i| unit[i].is_some(),
+        |_| true,
+        |i| {
+            let Some(ni) = unit[i] else {
+                return Vec::new();
+            };
+            neighborhood
+                .find(tree, &points[i])
+                .into_iter()
+                .filter(|&j| unit[j].is_some_and(|nj| ni.dot(&nj).abs() >= min_cos))
+                .collect()
+        },
+    );
+
+    drop_small(&mut labels, min_size);
+    labels
+}
@@ -0,0 +1,126 @@
+//! Filters which thin out a point cloud or remove stray points from it. Every filter works out
+//! which of the original points survive, so that whatever else is carried per point can be
+//! filtered along with them.
+
+use crate::kdtree::KdTree3;
+use engeom::{Point3, Vector3};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Group the points by the cubic voxel of side `size` they fall in. The members of each group are
+/// in ascending order, and the groups are ordered by their first member.
+pub fn voxel_groups(points: &[Point3], size: f64) -> Vec<Vec<usize>> {
+    let mut groups: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        let key = [p.x, p.y, p.z].map(|v| (v / size).floor() as i64);
+        groups.entry(key).or_default().push(i);
+    }
+
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    groups.sort_unstable_by_key(|g| g[0]);
+    groups
+}
+
+/// The centroid of a group of points
+pub fn centroid(points: &[Point3], group: &[usize]) -> Point3 {
+    let sum = group
+        .iter()
+        .fold(Vector3::zeros(), |acc, &i| acc + points[i].coords);
+    Point3::from(sum / group.len() as f64)
+}
+
+/// Keep one point from each voxel, the one closest to the centroid of the points in the voxel.
+pub fn voxel_nearest(points: &[Point3], size: f64) -> Vec<usize> {
+    let mut kept = voxel_groups(points, size)
+        .par_iter()
+        .map(|group| {
+            let c = centroid(points, group);
+            *group
+                .iter()
+                .min_by(|a, b| {
+                    let da = (points[**a] - c).norm_squared();
+                    let db = (points[**b] - c).norm_squared();
+                    da.total_cmp(&db)
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    kept.sort_unstable();
+    kept
+}
+
+/// Keep `count` points chosen uniformly at random, in ascending order.
+pub fn random(n: usize, count: usize, seed: Option<u64>) -> Vec<usize> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    let mut kept = rand::seq::index::sample(&mut rng, n, count.min(n)).into_vec();
+    kept.sort_unstable();
+    kept
+}
+
+/// Keep `count` points by farthest point sampling, starting from the point at `start` and then
+/// repeatedly taking the point furthest from all the points taken so far. The indices are in the
+/// order they were taken.
+pub fn farthest(points: &[Point3], count: usize, start: usize) -> Vec<usize> {
+    let count = count.min(points.len());
+    let mut kept = Vec::with_capacity(count);
+    let mut nearest = vec![f64::INFINITY; points.len()];
+    let mut next = start;
+
+    while kept.len() < count {
+        kept.push(next);
+        let taken = points[next];
+        nearest
+            .par_iter_mut()
+            .zip(points.par_iter())
+            .for_each(|(d, p)| *d = d.min((p - taken).norm_squared()));
+
+        next = nearest
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+    }
+
+    kept
+}
+
+/// Keep the points whose mean distance to their `k` nearest neighbors is no more than
+/// `std_ratio` standard deviations above the mean of that distance over the whole cloud.
+pub fn statistical_inliers(tree: &KdTree3, k: usize, std_ratio: f64) -> Vec<usize> {
+    let points = tree.points();
+    let means = points
+        .par_iter()
+        .map(|p| {
+            // The closest point found is the point itself, which doesn't count as a neighbor
+            let found = tree.nearest_k(p, k + 1);
+            let sum = found.iter().skip(1).map(|(_, d)| d).sum::<f64>();
+            sum / (found.len() - 1).max(1) as f64
+        })
+        .collect::<Vec<_>>();
+
+    let n = means.len() as f64;
+    let mean = means.iter().sum::<f64>() / n;
+    let std = (means.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let limit = mean + std_ratio * std;
+
+    (0..points.len()).filter(|&i| means[i] <= limit).collect()
+}
+
+/// Keep the points which have at least `min_neighbors` other points within `radius`.
+pub fn radius_inliers(tree: &KdTree3, radius: f64, min_neighbors: usize) -> Vec<usize> {
+    let points = tree.points();
+    let keep = points
+        .par_iter()
+        .map(|p| tree.within(p, radius).len() > min_neighbors)
+        .collect::<Vec<_>>();
+
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
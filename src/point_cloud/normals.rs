@@ -0,0 +1,193 @@
+//! Normal estimation for point clouds by principal component analysis of each point's
+//! neighborhood, and the ways of choosing a consistent sign for the resulting normals, which PCA
+//! alone leaves arbitrary.
+
+use crate::kdtree::KdTree3;
+use engeom::{Point3, Vector3};
+use parry3d_f64::na::Matrix3;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The points around each point which are used to estimate its normal
+#[derive(Clone, Copy)]
+pub enum Neighborhood {
+    /// The `k` nearest points, including the point itself
+    Nearest(usize),
+
+    /// All points within the radius, including the point itself
+    Radius(f64),
+}
+
+impl Neighborhood {
+    pub fn from_options(k: Option<usize>, radius: Option<f64>) -> Result<Self, String> {
+        match (k, radius) {
+            (Some(k), None) if k >= 3 => Ok(Self::Nearest(k)),
+            (Some(_), None) => Err("k must be at least 3".to_string()),
+            (None, Some(r)) if r > 0.0 && r.is_finite() => Ok(Self::Radius(r)),
+            (None, Some(_)) => Err("The neighborhood radius must be greater than zero".to_string()),
+            _ => Err("Exactly one of k or radius must be given".to_string()),
+        }
+    }
+
+    /// The indices of the points in the neighborhood of `point`.
+    pub fn find(&self, tree: &KdTree3, point: &Point3) -> Vec<usize> {
+        let found = match *self {
+            Self::Nearest(k) => tree.nearest_k(point, k),
+            Self::Radius(r) => tree.within(point, r),
+        };
+        found.into_iter().map(|(i, _)| i).collect()
+    }
+}
+
+/// Estimate the normal at each point of the tree as the direction of least variance of its
+/// neighborhood. Points with fewer than three neighbors get a zero normal, and the sign of every
+/// normal is arbitrary.
+pub fn estimate(tree: &KdTree3, neighborhood: Neighborhood) -> Vec<Vector3> {
+    let points = tree.points();
+    points
+        .par_iter()
+        .map(|p| {
+            let indices = neighborhood.find(tree, p);
+            if indices.len() < 3 {
+                return Vector3::zeros();
+            }
+
+            let n = indices.len() as f64;
+            let center = indices
+                .iter()
+                .fold(Vector3::zeros(), |acc, &i| acc + points[i].coords)
+                / n;
+            let covariance = indices.iter().fold(Matrix3::zeros(), |acc, &i| {
+                let d = points[i].coords - center;
+                acc + d * d.transpose()
+            });
+
+            let eigen = covariance.symmetric_eigen();
+            eigen
+                .eigenvectors
+                .column(eigen.eigenvalues.imin())
+                .normalize()
+        })
+        .collect()
+}
+
+/// Flip each normal to agree in sign with the matching reference normal, for keeping the
+/// orientation of normals which are being re-estimated.
+pub fn orient_like(normals: &mut [Vector3], reference: &[Vector3]) {
+    for (n, r) in normals.iter_mut().zip(reference) {
+        if n.dot(r) < 0.0 {
+            *n = -*n;
+        }
+    }
+}
+
+/// Flip each normal so that it points towards the matching viewpoint, which is the position of
+/// the sensor that saw the point.
+pub fn orient_towards<'a>(
+    points: &[Point3],
+    normals: &mut [Vector3],
+    viewpoints: impl Iterator<Item = &'a Point3>,
+) {
+    for ((p, n), v) in points.iter().zip(normals.iter_mut()).zip(viewpoints) {
+        if n.dot(&(v - p)) < 0.0 {
+            *n = -*n;
+        }
+    }
+}
+
+/// An edge waiting in the heap of Prim's algorithm, ordered so that the cheapest edge is popped
+/// first.
+struct Edge {
+    cost: f64,
+    to: usize,
+    from: usize,
+}
+
+impl PartialEq for Edge {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Edge {}
+
+impl PartialOrd for Edge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Edge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Make the signs of the normals consistent by propagating them across a minimum spanning tree
+/// of the k-nearest neighbor graph, following Hoppe et al. ("Surface reconstruction from
+/// unorganized points", 1992). The cost of an edge is `1 - |n_i · n_j|`, so the orientation is
+/// passed first between neighbors whose normals are nearly parallel. Each connected part of the
+/// graph is seeded at its highest point, whose normal is made to point up in z.
+pub fn orient_mst(tree: &KdTree3, normals: &mut [Vector3], k: usize) {
+    let points = tree.points();
+    let mut adjacent = points
+        .par_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            tree.nearest_k(p, k + 1)
+                .into_iter()
+                .map(|(j, _)| j)
+                .filter(|&j| j != i)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // The k-nearest relation isn't symmetric, but the spanning tree needs an undirected graph
+    for i in 0..adjacent.len() {
+        for n in 0..adjacent[i].len() {
+            let j = adjacent[i][n];
+            if !adjacent[j].contains(&i) {
+                adjacent[j].push(i);
+            }
+        }
+    }
+
+    let mut seeds = (0..points.len()).collect::<Vec<_>>();
+    seeds.sort_by(|a, b| points[*b].z.total_cmp(&points[*a].z));
+
+    let mut visited = vec![false; points.len()];
+    let mut heap = BinaryHeap::new();
+    for seed in seeds {
+        if visited[seed] {
+            continue;
+        }
+
+        if normals[seed].z < 0.0 {
+            normals[seed] = -normals[seed];
+        }
+        heap.push(Edge {
+            cost: 0.0,
+            to: seed,
+            from: seed,
+        });
+
+        while let Some(Edge { to, from, .. }) = heap.pop() {
+            if visited[to] {
+                continue;
+            }
+            visited[to] = true;
+            if normals[to].dot(&normals[from]) < 0.0 {
+                normals[to] = -normals[to];
+            }
+
+            for &j in adjacent[to].iter().filter(|&&j| !visited[j]) {
+                heap.push(Edge {
+                    cost: 1.0 - normals[to].dot(&normals[j]).abs(),
+                    to: j,
+                    from: to,
+                });
+            }
+        }
+    }
+}
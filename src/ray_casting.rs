@@ -1,6 +1,8 @@
+use crate::conversions::points_to_array3;
 use crate::geom3::Iso3;
 use crate::mesh::Mesh;
-use numpy::ndarray::{Array1, ArrayD};
+use crate::parallel;
+use numpy::ndarray::Array1;
 use numpy::{IntoPyArray, PyArray1, PyArrayDyn, PyReadonlyArrayDyn, PyUntypedArrayMethods};
 use parry3d_f64::query::{Ray as PRay3, RayCast};
 use pyo3::exceptions::PyValueError;
@@ -9,6 +11,7 @@ use pyo3::{
     pyclass, pymethods, Bound, FromPyObject, IntoPyObject, IntoPyObjectExt, Py, PyAny, PyResult,
     Python,
 };
+use rayon::prelude::*;
 
 #[pyclass]
 #[derive(Clone)]
@@ -57,54 +60,38 @@ impl RayBundle3 {
         format!("<RayBundle3 n={}>", self.inner.len())
     }
 
-    #[pyo3(signature=(mesh, mesh_iso = None, angle = None))]
+    #[pyo3(signature=(mesh, mesh_iso = None, angle = None, threads = None))]
     fn intersect_mesh<'py>(
         &self,
         py: Python<'py>,
         mesh: &Mesh,
         mesh_iso: Option<&Iso3>,
         angle: Option<f64>,
+        threads: Option<usize>,
     ) -> PyResult<Bound<'py, PyArrayDyn<f64>>> {
-        let mut result = Vec::new();
-        let iso = if let Some(mesh_iso) = mesh_iso {
-            mesh_iso.get_inner()
-        } else {
-            &engeom::Iso3::identity()
-        };
+        let iso = mesh_iso.map_or(engeom::Iso3::identity(), |i| *i.get_inner());
+        let tri_mesh = mesh.get_inner().tri_mesh();
+        let rays = &self.inner;
 
-        if let Some(angle_limit) = angle {
-            for ray in self.inner.iter() {
-                if let Some(ri) =
-                    mesh.get_inner()
-                        .tri_mesh()
-                        .cast_ray_and_get_normal(iso, ray, f64::MAX, false)
-                {
-                    let n = ri.normal * -1.0;
-                    if ray.dir.angle(&n) < angle_limit {
-                        let t = ri.time_of_impact;
-                        result.push(ray.point_at(t))
-                    }
-                }
+        let result: Vec<engeom::Point3> = parallel::run(py, threads, || {
+            if let Some(angle_limit) = angle {
+                rays.par_iter()
+                    .filter_map(|ray| {
+                        let ri = tri_mesh.cast_ray_and_get_normal(&iso, ray, f64::MAX, false)?;
+                        let n = ri.normal * -1.0;
+                        (ray.dir.angle(&n) < angle_limit).then(|| ray.point_at(ri.time_of_impact))
+                    })
+                    .collect()
+            } else {
+                rays.par_iter()
+                    .filter_map(|ray| {
+                        let t = tri_mesh.cast_ray(&iso, ray, f64::MAX, false)?;
+                        Some(ray.point_at(t))
+                    })
+                    .collect()
             }
-        } else {
-            for ray in self.inner.iter() {
-                if let Some(t) = mesh
-                    .get_inner()
-                    .tri_mesh()
-                    .cast_ray(iso, ray, f64::MAX, false)
-                {
-                    result.push(ray.point_at(t))
-                }
-            }
-        }
-
-        let mut result_array = ArrayD::zeros(vec![result.len(), 3]);
-        for (i, point) in result.iter().enumerate() {
-            result_array[[i, 0]] = point.x;
-            result_array[[i, 1]] = point.y;
-            result_array[[i, 2]] = point.z;
-        }
+        })?;
 
-        Ok(result_array.into_pyarray(py))
+        Ok(points_to_array3(&result).into_pyarray(py))
     }
 }
@@ -4,14 +4,145 @@ use crate::mesh::Mesh;
 use crate::geom3::{Iso3, Point3, Vector3};
 use numpy::{IntoPyArray, PyArrayDyn, PyReadonlyArrayDyn};
 use numpy::ndarray::ArrayD;
+use parry3d_f64::math::Isometry;
+use parry3d_f64::query::{Ray, RayCast};
+use parry3d_f64::shape::TriMesh;
 use pyo3::exceptions::PyValueError;
 use pyo3::{pyclass, pymethods, Bound, PyResult, Python};
+use rayon::prelude::*;
 use crate::conversions::points_to_array3;
+use crate::parallel;
+
+/// How far short of a point, relative to its distance from the detector, something in the line of
+/// sight must be to hide the point. Without it the surface the point lies on would hide it.
+const SIGHT_TOL: f64 = 1e-6;
+
+/// The geometry of a laser line in the sensor's own frame, kept alongside the engeom sensor so
+/// that its rays can be cast here, in parallel.
+#[derive(Clone)]
+struct Fan {
+    ray_origin: engeom::Point3,
+    detect_origin: engeom::Point3,
+    line_start: engeom::Point3,
+    line_end: engeom::Point3,
+    min_range: f64,
+    max_range: f64,
+    rays: usize,
+    angle_limit: Option<f64>,
+}
+
+impl Fan {
+    /// The point found by ray `i` of the laser line at `iso`, if the ray hits the target within
+    /// range and the detector can see the spot where it lands.
+    fn cast(
+        &self,
+        iso: &engeom::Iso3,
+        i: usize,
+        target: &TriMesh,
+        obstruction: Option<&TriMesh>,
+    ) -> Option<engeom::Point3> {
+        let f = if self.rays > 1 {
+            i as f64 / (self.rays - 1) as f64
+        } else {
+            0.0
+        };
+        let aim = self.line_start + (self.line_end - self.line_start) * f;
+        let ray = Ray::new(
+            iso * self.ray_origin,
+            iso * (aim - self.ray_origin).normalize(),
+        );
+
+        let identity = Isometry::identity();
+        let blocked = |mesh: &TriMesh, ray: &Ray, distance: f64| {
+            mesh.cast_ray(&identity, ray, distance, false).is_some()
+        };
+
+        let hit = target.cast_ray_and_get_normal(&identity, &ray, self.max_range, false)?;
+        if hit.time_of_impact < self.min_range
+            || obstruction.is_some_and(|o| blocked(o, &ray, hit.time_of_impact))
+        {
+            return None;
+        }
+
+        let point = ray.point_at(hit.time_of_impact);
+        let detector = iso * self.detect_origin;
+        let distance = (point - detector).norm();
+        let sight = Ray::new(detector, (point - detector) / distance);
+        let clear = distance * (1.0 - SIGHT_TOL);
+        if blocked(target, &sight, clear) || obstruction.is_some_and(|o| blocked(o, &sight, clear))
+        {
+            return None;
+        }
+
+        // The normal of the surface on the side the laser came from
+        let normal = if hit.normal.dot(&ray.dir) > 0.0 {
+            -hit.normal
+        } else {
+            hit.normal
+        };
+        if self.angle_limit.is_some_and(|limit| {
+            normal.angle(&-ray.dir) > limit || normal.angle(&-sight.dir) > limit
+        }) {
+            return None;
+        }
+
+        Some(point)
+    }
+
+    /// Cast every ray of the laser line at each of the positions in `isos`, spread over the
+    /// worker threads. There is one entry for each ray at each position, in order of position and
+    /// then of ray, so ray `j` at position `i` is entry `i * rays + j`.
+    fn scan(
+        &self,
+        isos: &[engeom::Iso3],
+        target: &engeom::Mesh,
+        obstruction: Option<&engeom::Mesh>,
+    ) -> Vec<Option<engeom::Point3>> {
+        let (target, obstruction) = (target.tri_mesh(), obstruction.map(|o| o.tri_mesh()));
+        (0..isos.len() * self.rays)
+            .into_par_iter()
+            .map(|k| self.cast(&isos[k / self.rays], k % self.rays, target, obstruction))
+            .collect()
+    }
+}
+
+/// The points found by a scan as an n x 3 array of the rays which hit something, or organized into
+/// a rows x rays x 3 grid with NaN where a ray found nothing.
+fn scan_array<'py>(
+    py: Python<'py>,
+    hits: &[Option<engeom::Point3>],
+    rows: usize,
+    rays: usize,
+    organized: bool,
+) -> Bound<'py, PyArrayDyn<f64>> {
+    if !organized {
+        let points = hits.iter().flatten().copied().collect::<Vec<_>>();
+        return points_to_array3(&points).into_pyarray(py);
+    }
+
+    let mut grid = ArrayD::from_elem(vec![rows, rays, 3], f64::NAN);
+    for (k, p) in hits.iter().enumerate() {
+        if let Some(p) = p {
+            for i in 0..3 {
+                grid[[k / rays, k % rays, i]] = p[i];
+            }
+        }
+    }
+    grid.into_pyarray(py)
+}
+
+/// The error for organized output from a sensor whose laser line geometry isn't known.
+fn no_fan() -> String {
+    "Organized points need the geometry of the laser line, which is only known for sensors created \
+     with the LaserLine constructor"
+        .to_string()
+}
 
 #[pyclass]
 #[derive(Clone)]
 pub struct LaserLine {
     pub inner: engeom::sensor::LaserLine,
+    fan: Option<Fan>,
 }
 
 impl LaserLine {
@@ -20,7 +151,7 @@ impl LaserLine {
     }
 
     pub fn from_inner(inner: engeom::sensor::LaserLine) -> Self {
-        Self { inner }
+        Self { inner, fan: None }
     }
 }
 
@@ -50,28 +181,79 @@ impl LaserLine {
         )
         .map_err(|e| PyValueError::new_err(e.to_string()))?;
 
-        Ok(Self { inner })
+        let fan = Fan {
+            ray_origin: *ray_origin.get_inner(),
+            detect_origin: *detect_origin.get_inner(),
+            line_start: *line_start.get_inner(),
+            line_end: *line_end.get_inner(),
+            min_range,
+            max_range,
+            rays,
+            angle_limit,
+        };
+
+        Ok(Self {
+            inner,
+            fan: Some(fan),
+        })
     }
 
+    #[pyo3(signature = (target, obstruction, iso, organized = false, threads = None))]
     fn get_points<'py>(
         &self,
         py: Python<'py>,
         target: &Mesh,
         obstruction: Option<&Mesh>,
         iso: &Iso3,
+        organized: bool,
+        threads: Option<usize>,
     ) -> PyResult<Bound<'py, PyArrayDyn<f64>>> {
-        let result = self
-            .inner
-            .get_points(target.get_inner(), obstruction.map(|o| o.get_inner()), iso.get_inner());
-        Ok(points_to_array3(&result).into_pyarray(py))
+        let (target, obstruction) = (target.get_inner(), obstruction.map(|o| o.get_inner()));
+        let Some(fan) = &self.fan else {
+            if organized {
+                return Err(PyValueError::new_err(no_fan()));
+            }
+            let sensor = &self.inner;
+            let result = parallel::run(py, threads, || {
+                sensor.get_points(target, obstruction, iso.get_inner())
+            })?;
+            return Ok(points_to_array3(&result).into_pyarray(py));
+        };
+
+        let isos = [*iso.get_inner()];
+        let hits = parallel::run(py, threads, || fan.scan(&isos, target, obstruction))?;
+        Ok(scan_array(py, &hits, 1, fan.rays, organized))
     }
 }
 
 
+/// The laser line and pan of a panning sensor, kept alongside the engeom sensor so that the rays of
+/// every step can be cast here, in parallel.
+#[derive(Clone)]
+struct Pan {
+    fan: Fan,
+    vector: engeom::Vector3,
+    steps: usize,
+}
+
+impl Pan {
+    /// The positions of the laser line at each step, which is moved along the pan vector in the
+    /// sensor's own frame.
+    fn positions(&self, iso: &engeom::Iso3) -> Vec<engeom::Iso3> {
+        (0..self.steps)
+            .map(|i| {
+                let v = self.vector * i as f64;
+                iso * engeom::Iso3::translation(v.x, v.y, v.z)
+            })
+            .collect()
+    }
+}
+
 #[pyclass]
 #[derive(Clone)]
 pub struct PanningLaserLine {
     pub inner: engeom::sensor::PanningLaserLine,
+    pan: Option<Pan>,
 }
 
 impl PanningLaserLine {
@@ -80,7 +262,7 @@ impl PanningLaserLine {
     }
 
     pub fn from_inner(inner: engeom::sensor::PanningLaserLine) -> Self {
-        Self { inner }
+        Self { inner, pan: None }
     }
 }
 
@@ -98,20 +280,39 @@ impl PanningLaserLine {
             steps,
         );
 
-        Ok(Self { inner })
+        let pan = laser_line.fan.map(|fan| Pan {
+            fan,
+            vector: *pan_vector.get_inner(),
+            steps,
+        });
+
+        Ok(Self { inner, pan })
     }
 
+    #[pyo3(signature = (target, obstruction, iso, organized = false, threads = None))]
     fn get_points<'py>(
         &self,
         py: Python<'py>,
         target: &Mesh,
         obstruction: Option<&Mesh>,
         iso: &Iso3,
+        organized: bool,
+        threads: Option<usize>,
     ) -> PyResult<Bound<'py, PyArrayDyn<f64>>> {
-        // let start = Instant::now();
-        let result = self
-            .inner
-            .get_points(target.get_inner(), obstruction.map(|o| o.get_inner()), iso.get_inner());
-        Ok(points_to_array3(&result).into_pyarray(py))
+        let (target, obstruction) = (target.get_inner(), obstruction.map(|o| o.get_inner()));
+        let Some(pan) = &self.pan else {
+            if organized {
+                return Err(PyValueError::new_err(no_fan()));
+            }
+            let sensor = &self.inner;
+            let result = parallel::run(py, threads, || {
+                sensor.get_points(target, obstruction, iso.get_inner())
+            })?;
+            return Ok(points_to_array3(&result).into_pyarray(py));
+        };
+
+        let isos = pan.positions(iso.get_inner());
+        let hits = parallel::run(py, threads, || pan.fan.scan(&isos, target, obstruction))?;
+        Ok(scan_array(py, &hits, pan.steps, pan.fan.rays, organized))
     }
 }
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! The versioned binary format behind `to_bytes`/`from_bytes` and pickling of the geometry
+//! classes.
+//!
+//! Every payload is a MessagePack array of four elements: the `"engeom"` marker, the format
+//! version, the name of the class, and the state of the object. The state is a plain data struct
+//! defined next to each class rather than the serde form of the engeom type it wraps, so that the
+//! payloads do not change when the underlying library does.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::PyClass;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const MARKER: &str = "engeom";
+
+/// The version written into new payloads. Payloads with this version or any earlier one can be
+/// read. When a state struct changes, new fields must be given `#[serde(default)]` so that older
+/// payloads still deserialize, and anything that can't be handled that way must be migrated in
+/// `from_bytes` based on the version before the state is handed to the class.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A class which can be written to and read from the versioned binary format.
+pub trait Serial: Sized {
+    /// The class name written into the payload, checked when the payload is read back.
+    const KIND: &'static str;
+
+    type State: Serialize + DeserializeOwned;
+
+    fn to_state(&self, py: Python<'_>) -> Self::State;
+
+    fn from_state(py: Python<'_>, state: Self::State) -> PyResult<Self>;
+}
+
+/// The return value of `__reduce__`, a callable and the arguments to call it with.
+pub type Reduced<'py> = (Bound<'py, PyAny>, (Bound<'py, PyBytes>,));
+
+pub fn to_bytes<T: Serial>(py: Python<'_>, value: &T) -> PyResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write_header(&mut bytes, T::KIND)
+        .map_err(|e| PyValueError::new_err(format!("Failed to serialize: {}", e)))?;
+    rmp_serde::encode::write_named(&mut bytes, &value.to_state(py))
+        .map_err(|e| PyValueError::new_err(format!("Failed to serialize: {}", e)))?;
+
+    Ok(bytes)
+}
+
+pub fn from_bytes<T: Serial>(py: Python<'_>, bytes: &[u8]) -> PyResult<T> {
+    let (version, kind, payload) = read_header(bytes).ok_or_else(|| {
+        PyValueError::new_err("Failed to deserialize: data is not an engeom payload")
+    })?;
+
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "Failed to deserialize: format version {} is not supported (newest is {})",
+            version, FORMAT_VERSION
+        )));
+    }
+
+    if kind != T::KIND {
+        return Err(PyValueError::new_err(format!(
+            "Failed to deserialize: data holds a {}, not a {}",
+            kind,
+            T::KIND
+        )));
+    }
+
+    let state = rmp_serde::from_slice(payload)
+        .map_err(|e| PyValueError::new_err(format!("Failed to deserialize: {}", e)))?;
+
+    T::from_state(py, state)
+}
+
+/// Build the `__reduce__` value for an object, which rebuilds it through the `from_bytes`
+/// classmethod of its type so that pickle only needs to find the class by name.
+pub fn reduce<'py, T: Serial + PyClass>(slf: &Bound<'py, T>) -> PyResult<Reduced<'py>> {
+    let py = slf.py();
+    let bytes = to_bytes(py, &*slf.borrow())?;
+    let from_bytes = slf.as_any().get_type().getattr("from_bytes")?;
+
+    Ok((from_bytes, (PyBytes::new(py, &bytes),)))
+}
+
+/// Give classes which implement `Serial` their `to_bytes`/`from_bytes` methods and pickle
+/// support, in a `#[pymethods]` block of their own next to the class's main one.
+macro_rules! serial_methods {
+    ($($class:ident),+ $(,)?) => {$(
+        #[pyo3::pymethods]
+        impl $class {
+            fn to_bytes(&self, py: pyo3::Python<'_>) -> pyo3::PyResult<Vec<u8>> {
+                $crate::serialization::to_bytes(py, self)
+            }
+
+            #[classmethod]
+            fn from_bytes(
+                cls: &pyo3::Bound<'_, pyo3::types::PyType>,
+                data: &[u8],
+            ) -> pyo3::PyResult<Self> {
+                $crate::serialization::from_bytes(cls.py(), data)
+            }
+
+            fn __getstate__(&self, py: pyo3::Python<'_>) -> pyo3::PyResult<Vec<u8>> {
+                $crate::serialization::to_bytes(py, self)
+            }
+
+            fn __setstate__(&mut self, py: pyo3::Python<'_>, state: &[u8]) -> pyo3::PyResult<()> {
+                *self = $crate::serialization::from_bytes(py, state)?;
+                Ok(())
+            }
+
+            fn __reduce__<'py>(
+                slf: &pyo3::Bound<'py, Self>,
+            ) -> pyo3::PyResult<$crate::serialization::Reduced<'py>> {
+                $crate::serialization::reduce(slf)
+            }
+        }
+    )+};
+}
+
+pub(crate) use serial_methods;
+
+fn write_header(bytes: &mut Vec<u8>, kind: &str) -> Result<(), rmp::encode::ValueWriteError> {
+    rmp::encode::write_array_len(bytes, 4)?;
+    rmp::encode::write_str(bytes, MARKER)?;
+    rmp::encode::write_uint(bytes, FORMAT_VERSION as u64)?;
+    rmp::encode::write_str(bytes, kind)?;
+    Ok(())
+}
+
+fn read_header(bytes: &[u8]) -> Option<(u32, &str, &[u8])> {
+    let mut rd = bytes;
+    if rmp::decode::read_array_len(&mut rd).ok()? != 4 {
+        return None;
+    }
+
+    let (marker, mut rd) = rmp::decode::read_str_from_slice(rd).ok()?;
+    if marker != MARKER {
+        return None;
+    }
+
+    let version: u32 = rmp::decode::read_int(&mut rd).ok()?;
+    let (kind, rd) = rmp::decode::read_str_from_slice(rd).ok()?;
+
+    Some((version, kind, rd))
+}
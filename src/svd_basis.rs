@@ -1,21 +1,61 @@
 use crate::conversions::{array_to_points2, array_to_points3};
 use crate::geom2::{Iso2, Vector2};
 use crate::geom3::{Iso3, Vector3};
+use crate::serialization::{serial_methods, Serial};
 use numpy::ndarray::Array1;
 use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1, PyReadonlyArrayDyn};
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[pyclass]
+#[pyclass(module = "engeom.geom2")]
 pub struct SvdBasis2 {
     inner: engeom::SvdBasis2,
+
+    // The basis is rebuilt from its inputs when it is deserialized
+    points: Vec<engeom::Point2>,
+    weights: Option<Vec<f64>>,
 }
 
 impl SvdBasis2 {
     pub fn get_inner(&self) -> &engeom::SvdBasis2 {
         &self.inner
     }
+
+    fn from_points(points: Vec<engeom::Point2>, weights: Option<Vec<f64>>) -> Self {
+        let inner = engeom::SvdBasis2::from_points(&points, weights.as_deref());
+        Self {
+            inner,
+            points,
+            weights,
+        }
+    }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SvdBasis2State {
+    points: Vec<[f64; 2]>,
+    weights: Option<Vec<f64>>,
+}
+
+impl Serial for SvdBasis2 {
+    const KIND: &'static str = "SvdBasis2";
+    type State = SvdBasis2State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        SvdBasis2State {
+            points: self.points.iter().map(|p| p.coords.into()).collect(),
+            weights: self.weights.clone(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let points = state.points.into_iter().map(engeom::Point2::from).collect();
+        Ok(Self::from_points(points, state.weights))
+    }
+}
+
+serial_methods!(SvdBasis2);
+
 #[pymethods]
 impl SvdBasis2 {
     #[new]
@@ -25,16 +65,9 @@ impl SvdBasis2 {
         weights: Option<PyReadonlyArray1<'py, f64>>,
     ) -> PyResult<Self> {
         let points = array_to_points2(&points.as_array())?;
+        let weights = weights.map(|w| w.as_array().to_vec());
 
-        let basis = match weights {
-            Some(weights) => engeom::SvdBasis2::from_points(
-                &points,
-                Some(weights.as_array().as_slice().unwrap()),
-            ),
-            None => engeom::SvdBasis2::from_points(&points, None),
-        };
-
-        Ok(Self { inner: basis })
+        Ok(Self::from_points(points, weights))
     }
 
     fn rank(&self, tol: f64) -> usize {
@@ -72,17 +105,55 @@ impl SvdBasis2 {
     }
 }
 
-#[pyclass]
+#[pyclass(module = "engeom.geom3")]
 pub struct SvdBasis3 {
     inner: engeom::SvdBasis3,
+
+    // The basis is rebuilt from its inputs when it is deserialized
+    points: Vec<engeom::Point3>,
+    weights: Option<Vec<f64>>,
 }
 
 impl SvdBasis3 {
     pub fn get_inner(&self) -> &engeom::SvdBasis3 {
         &self.inner
     }
+
+    fn from_points(points: Vec<engeom::Point3>, weights: Option<Vec<f64>>) -> Self {
+        let inner = engeom::SvdBasis3::from_points(&points, weights.as_deref());
+        Self {
+            inner,
+            points,
+            weights,
+        }
+    }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SvdBasis3State {
+    points: Vec<[f64; 3]>,
+    weights: Option<Vec<f64>>,
+}
+
+impl Serial for SvdBasis3 {
+    const KIND: &'static str = "SvdBasis3";
+    type State = SvdBasis3State;
+
+    fn to_state(&self, _py: Python<'_>) -> Self::State {
+        SvdBasis3State {
+            points: self.points.iter().map(|p| p.coords.into()).collect(),
+            weights: self.weights.clone(),
+        }
+    }
+
+    fn from_state(_py: Python<'_>, state: Self::State) -> PyResult<Self> {
+        let points = state.points.into_iter().map(engeom::Point3::from).collect();
+        Ok(Self::from_points(points, state.weights))
+    }
+}
+
+serial_methods!(SvdBasis3);
+
 #[pymethods]
 impl SvdBasis3 {
     #[new]
@@ -92,17 +163,9 @@ impl SvdBasis3 {
         weights: Option<PyReadonlyArray1<'py, f64>>,
     ) -> PyResult<Self> {
         let points = array_to_points3(&points.as_array())?;
+        let weights = weights.map(|w| w.as_array().to_vec());
 
-        // TODO: Is there some way to pass it back as a reference?
-        let basis = match weights {
-            Some(weights) => engeom::SvdBasis3::from_points(
-                &points,
-                Some(weights.as_array().as_slice().unwrap()),
-            ),
-            None => engeom::SvdBasis3::from_points(&points, None),
-        };
-
-        Ok(Self { inner: basis })
+        Ok(Self::from_points(points, weights))
     }
 
     fn rank(&self, tol: f64) -> usize {